@@ -0,0 +1,276 @@
+//! Cost of going through the adapters compared to the plain serializers and deserializers.
+//!
+//! Documents without bytes show the overhead of the forwarding wrappers alone, also with the
+//! Base64 adapter, whose engine is shared rather than copied into every nested adapter.
+//! Documents with bytes are compared against the same document with the bytes already stored as
+//! HEX strings, which is what a type has to do without the adapter.
+//!
+//! Run with `cargo bench --features toml,yaml --bench overhead`.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [10, 100, 1000];
+
+#[derive(Serialize, Deserialize)]
+struct Doc<R> {
+    records: Vec<R>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    id: u64,
+    name: String,
+    email: String,
+    active: bool,
+    score: f64,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BytesRecord {
+    id: u64,
+    #[serde(with = "serde_bytes")]
+    key: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+}
+
+/// `BytesRecord` with the bytes encoded by hand.
+#[derive(Serialize, Deserialize)]
+struct HexRecord {
+    id: u64,
+    key: String,
+    payload: String,
+}
+
+fn records(n: usize) -> Doc<Record> {
+    let records = (0..n as u64)
+        .map(|id| Record {
+            id,
+            name: format!("user {}", id),
+            email: format!("user{}@example.com", id),
+            active: id % 2 == 0,
+            score: id as f64 / 3.0,
+            tags: vec!["a".to_string(), "b".to_string()],
+        })
+        .collect();
+    Doc { records }
+}
+
+fn bytes_records(n: usize) -> Doc<BytesRecord> {
+    let records = (0..n as u64)
+        .map(|id| BytesRecord {
+            id,
+            key: vec![id as u8; 32],
+            payload: (0..=255).collect(),
+        })
+        .collect();
+    Doc { records }
+}
+
+fn hex_records(n: usize) -> Doc<HexRecord> {
+    let records = bytes_records(n)
+        .records
+        .into_iter()
+        .map(|r| HexRecord {
+            id: r.id,
+            key: hex::encode(r.key),
+            payload: hex::encode(r.payload),
+        })
+        .collect();
+    Doc { records }
+}
+
+trait Format {
+    const NAME: &'static str;
+
+    fn to_string<T: Serialize>(value: &T) -> String;
+    fn to_string_hex<T: Serialize>(value: &T) -> String;
+    fn from_str<T: DeserializeOwned>(s: &str) -> T;
+    fn from_str_hex<T: DeserializeOwned>(s: &str) -> T;
+}
+
+struct Json;
+
+impl Format for Json {
+    const NAME: &'static str = "json";
+
+    fn to_string<T: Serialize>(value: &T) -> String {
+        serde_json::to_string(value).unwrap()
+    }
+
+    fn to_string_hex<T: Serialize>(value: &T) -> String {
+        let mut out = Vec::new();
+        let mut ser = serde_json::Serializer::new(&mut out);
+        value.serialize(ByteFmtSerializer::hex(&mut ser)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn from_str<T: DeserializeOwned>(s: &str) -> T {
+        serde_json::from_str(s).unwrap()
+    }
+
+    fn from_str_hex<T: DeserializeOwned>(s: &str) -> T {
+        let mut de = serde_json::Deserializer::from_str(s);
+        T::deserialize(ByteFmtDeserializer::new_hex(&mut de)).unwrap()
+    }
+}
+
+struct Toml;
+
+impl Format for Toml {
+    const NAME: &'static str = "toml";
+
+    fn to_string<T: Serialize>(value: &T) -> String {
+        toml::to_string(value).unwrap()
+    }
+
+    fn to_string_hex<T: Serialize>(value: &T) -> String {
+        serde_bytes_repr::toml::to_string_hex(value).unwrap()
+    }
+
+    fn from_str<T: DeserializeOwned>(s: &str) -> T {
+        toml::from_str(s).unwrap()
+    }
+
+    fn from_str_hex<T: DeserializeOwned>(s: &str) -> T {
+        serde_bytes_repr::toml::from_str_hex(s).unwrap()
+    }
+}
+
+struct Yaml;
+
+impl Format for Yaml {
+    const NAME: &'static str = "yaml";
+
+    fn to_string<T: Serialize>(value: &T) -> String {
+        serde_yaml::to_string(value).unwrap()
+    }
+
+    fn to_string_hex<T: Serialize>(value: &T) -> String {
+        serde_bytes_repr::yaml::to_string_hex(value).unwrap()
+    }
+
+    fn from_str<T: DeserializeOwned>(s: &str) -> T {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    fn from_str_hex<T: DeserializeOwned>(s: &str) -> T {
+        serde_bytes_repr::yaml::from_str_hex(s).unwrap()
+    }
+}
+
+fn no_bytes<F: Format>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{}/no_bytes", F::NAME));
+    for n in SIZES {
+        let doc = records(n);
+        let text = F::to_string(&doc);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new("serialize/plain", n), &doc, |b, doc| {
+            b.iter(|| F::to_string(black_box(doc)))
+        });
+        group.bench_with_input(BenchmarkId::new("serialize/adapter", n), &doc, |b, doc| {
+            b.iter(|| F::to_string_hex(black_box(doc)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("deserialize/plain", n),
+            &text,
+            |b, text| b.iter(|| F::from_str::<Doc<Record>>(black_box(text))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("deserialize/adapter", n),
+            &text,
+            |b, text| b.iter(|| F::from_str_hex::<Doc<Record>>(black_box(text))),
+        );
+    }
+    group.finish();
+}
+
+/// The Base64 adapter on documents without bytes, where its engine is shared by the adapters
+/// created for every nested value but never used.
+fn no_bytes_base64(c: &mut Criterion) {
+    let mut group = c.benchmark_group("json/no_bytes/base64");
+    let config = base64::engine::GeneralPurposeConfig::new();
+    for n in SIZES {
+        let doc = records(n);
+        let text = Json::to_string(&doc);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new("serialize/plain", n), &doc, |b, doc| {
+            b.iter(|| Json::to_string(black_box(doc)))
+        });
+        group.bench_with_input(BenchmarkId::new("serialize/adapter", n), &doc, |b, doc| {
+            b.iter(|| {
+                let mut out = Vec::new();
+                let mut ser = serde_json::Serializer::new(&mut out);
+                let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::STANDARD, config);
+                black_box(doc).serialize(ser).unwrap();
+                out
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("deserialize/plain", n),
+            &text,
+            |b, text| b.iter(|| Json::from_str::<Doc<Record>>(black_box(text))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("deserialize/adapter", n),
+            &text,
+            |b, text| {
+                b.iter(|| {
+                    let mut de = serde_json::Deserializer::from_str(black_box(text));
+                    let de = ByteFmtDeserializer::new_base64(
+                        &mut de,
+                        base64::alphabet::STANDARD,
+                        config,
+                    );
+                    Doc::<Record>::deserialize(de).unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn with_bytes<F: Format>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("{}/bytes", F::NAME));
+    for n in SIZES {
+        let doc = bytes_records(n);
+        let hex_doc = hex_records(n);
+        let text = F::to_string_hex(&doc);
+        assert_eq!(F::to_string(&hex_doc), text);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("serialize/plain", n),
+            &hex_doc,
+            |b, doc| b.iter(|| F::to_string(black_box(doc))),
+        );
+        group.bench_with_input(BenchmarkId::new("serialize/adapter", n), &doc, |b, doc| {
+            b.iter(|| F::to_string_hex(black_box(doc)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("deserialize/plain", n),
+            &text,
+            |b, text| b.iter(|| F::from_str::<Doc<HexRecord>>(black_box(text))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("deserialize/adapter", n),
+            &text,
+            |b, text| b.iter(|| F::from_str_hex::<Doc<BytesRecord>>(black_box(text))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    no_bytes<Json>,
+    no_bytes_base64,
+    with_bytes<Json>,
+    no_bytes<Toml>,
+    with_bytes<Toml>,
+    no_bytes<Yaml>,
+    with_bytes<Yaml>
+);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+//! Per-field cost of encoding the hashes and IDs most byte fields hold, with the default stack
+//! buffer and with every value going through a `String` (`stack_buffer::<0>`).
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::Serialize;
+use serde_bytes_repr::ByteFmtSerializer;
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [16, 32, 64];
+
+fn hex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hex");
+    let mut out = Vec::with_capacity(256);
+    for len in SIZES {
+        let bytes = vec![0xab; len];
+        let bytes = serde_bytes::Bytes::new(&bytes);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("stack", len), &bytes, |b, bytes| {
+            b.iter(|| {
+                out.clear();
+                let mut ser = serde_json::Serializer::new(&mut out);
+                let ser = ByteFmtSerializer::hex(&mut ser);
+                black_box(bytes).serialize(ser).unwrap();
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("string", len), &bytes, |b, bytes| {
+            b.iter(|| {
+                out.clear();
+                let mut ser = serde_json::Serializer::new(&mut out);
+                let ser = ByteFmtSerializer::hex(&mut ser).stack_buffer::<0>();
+                black_box(bytes).serialize(ser).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn base64(c: &mut Criterion) {
+    let mut group = c.benchmark_group("base64");
+    let mut out = Vec::with_capacity(256);
+    let config = base64::engine::GeneralPurposeConfig::new();
+    for len in SIZES {
+        let bytes = vec![0xab; len];
+        let bytes = serde_bytes::Bytes::new(&bytes);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("stack", len), &bytes, |b, bytes| {
+            b.iter(|| {
+                out.clear();
+                let mut ser = serde_json::Serializer::new(&mut out);
+                let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::STANDARD, config);
+                black_box(bytes).serialize(ser).unwrap();
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("string", len), &bytes, |b, bytes| {
+            b.iter(|| {
+                out.clear();
+                let mut ser = serde_json::Serializer::new(&mut out);
+                let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::STANDARD, config)
+                    .stack_buffer::<0>();
+                black_box(bytes).serialize(ser).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A struct with many byte fields, where the engine is only built once per document.
+fn document(c: &mut Criterion) {
+    #[derive(Serialize)]
+    struct Entry {
+        #[serde(with = "serde_bytes")]
+        id: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        hash: Vec<u8>,
+    }
+    let entries: Vec<Entry> = (0..100)
+        .map(|i| Entry {
+            id: vec![i; 16],
+            hash: vec![i; 32],
+        })
+        .collect();
+    let config = base64::engine::GeneralPurposeConfig::new();
+    let mut out = Vec::with_capacity(16 * 1024);
+    c.bench_function("base64/document/100_entries", |b| {
+        b.iter(|| {
+            out.clear();
+            let mut ser = serde_json::Serializer::new(&mut out);
+            let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::STANDARD, config);
+            black_box(&entries).serialize(ser).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, hex, base64, document);
+criterion_main!(benches);
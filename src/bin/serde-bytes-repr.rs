@@ -0,0 +1,283 @@
+//! Re-encodes byte fields in JSON, YAML and TOML documents.
+//!
+//! ```text
+//! serde-bytes-repr --from hex --to base64 --field '/users/*/key' config.json
+//! serde-bytes-repr --check --to base64 --field '/**/*_key' -i config.yaml
+//! ```
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurposeConfig},
+};
+use clap::{Parser, ValueEnum};
+use serde::{ser, Deserializer, Serialize, Serializer};
+use serde_bytes_repr::{
+    transcode::{self, transcode},
+    ByteFmtDeserializer, ByteFmtSerializer, Encoding,
+};
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Re-encodes byte fields in JSON, YAML and TOML documents.
+///
+/// Byte fields are selected with JSON Pointers extended with globs: `*` and `?` match within a
+/// key or array index and a `**` segment matches any number of levels, like `/users/*/key` or
+/// `/**/*_key`.
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Document format, inferred from the file extension when not given.
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+
+    /// Encoding of the byte fields in the input.
+    #[arg(long, value_enum, required_unless_present = "check")]
+    from: Option<Enc>,
+
+    /// Encoding to write the byte fields in, or to check them against with `--check`.
+    #[arg(long, value_enum)]
+    to: Enc,
+
+    /// Path pattern selecting byte fields, can be repeated.
+    #[arg(long = "field", short = 'f', value_name = "PATTERN", required = true)]
+    fields: Vec<String>,
+
+    /// Rewrite FILE instead of writing the result to stdout.
+    ///
+    /// The document is written anew: comments and formatting in YAML and TOML files are lost,
+    /// and JSON is pretty printed.
+    #[arg(long, short = 'i', requires = "file", conflicts_with = "check")]
+    in_place: bool,
+
+    /// Only check that every byte field is in the `--to` encoding, exits with 1 otherwise.
+    #[arg(long)]
+    check: bool,
+
+    /// Document to read, stdin when not given.
+    file: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Enc {
+    /// Lowercase HEX.
+    Hex,
+    /// Standard Base64 with padding.
+    Base64,
+    /// URL-safe Base64 with padding.
+    Base64url,
+    /// Standard Base64 without padding.
+    Base64Nopad,
+    /// URL-safe Base64 without padding.
+    Base64urlNopad,
+    /// Arrays of integers.
+    Array,
+}
+
+impl Enc {
+    fn encoding(self) -> Encoding {
+        // Padding is only enforced by `--check`, which compares against the re-encoded value.
+        let config =
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+        let nopad = config.with_encode_padding(false);
+        match self {
+            Enc::Hex => Encoding::hex(),
+            Enc::Base64 => Encoding::base64(alphabet::STANDARD, config),
+            Enc::Base64url => Encoding::base64(alphabet::URL_SAFE, config),
+            Enc::Base64Nopad => Encoding::base64(alphabet::STANDARD, nopad),
+            Enc::Base64urlNopad => Encoding::base64(alphabet::URL_SAFE, nopad),
+            Enc::Array => Encoding::array(),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<ExitCode, String> {
+    let format = match (args.format, &args.file) {
+        (Some(format), _) => format,
+        (None, Some(file)) => infer_format(file)?,
+        (None, None) => return Err("`--format` is required when reading stdin".into()),
+    };
+    let input = match &args.file {
+        Some(file) => fs::read_to_string(file)
+            .map_err(|err| format!("failed to read {}: {}", file.display(), err))?,
+        None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| format!("failed to read stdin: {}", err))?;
+            input
+        }
+    };
+    let fields: Vec<&str> = args.fields.iter().map(String::as_str).collect();
+
+    if args.check {
+        let value = to_value(format, &input)?;
+        return match serde_bytes_repr::json::check(&value, &fields, &args.to.encoding()) {
+            Ok(()) => Ok(ExitCode::SUCCESS),
+            Err(errors) => {
+                for err in errors {
+                    eprintln!("{}", err);
+                }
+                Ok(ExitCode::FAILURE)
+            }
+        };
+    }
+
+    // `from` is required unless `--check` is given.
+    let from = args.from.expect("--from").encoding();
+    let output = convert(format, &input, &fields, from, args.to.encoding())?;
+    match (&args.file, args.in_place) {
+        (Some(file), true) => fs::write(file, output)
+            .map_err(|err| format!("failed to write {}: {}", file.display(), err))?,
+        _ => io::stdout()
+            .write_all(output.as_bytes())
+            .map_err(|err| format!("failed to write stdout: {}", err))?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn infer_format(file: &Path) -> Result<Format, String> {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok(Format::Json),
+        Some("yaml") | Some("yml") => Ok(Format::Yaml),
+        Some("toml") => Ok(Format::Toml),
+        _ => Err(format!(
+            "can't infer the format of {}, use `--format`",
+            file.display()
+        )),
+    }
+}
+
+fn to_value(format: Format, input: &str) -> Result<serde_json::Value, String> {
+    match format {
+        Format::Json => serde_json::from_str(input).map_err(|err| err.to_string()),
+        Format::Yaml => serde_yaml::from_str(input).map_err(|err| err.to_string()),
+        Format::Toml => ::toml::from_str(input).map_err(|err| err.to_string()),
+    }
+}
+
+fn convert(
+    format: Format,
+    input: &str,
+    fields: &[&str],
+    from: Encoding,
+    to: Encoding,
+) -> Result<String, String> {
+    match format {
+        Format::Json => {
+            let mut de = serde_json::Deserializer::from_str(input);
+            let mut out = vec![];
+            let mut ser = serde_json::Serializer::pretty(&mut out);
+            transcode(
+                ByteFmtDeserializer::new(&mut de, from),
+                ByteFmtSerializer::new(&mut ser, to),
+                fields,
+            )
+            .map_err(|err| err.to_string())?;
+            de.end().map_err(|err| err.to_string())?;
+            out.push(b'\n');
+            // serde_json only writes UTF-8.
+            Ok(String::from_utf8(out).unwrap())
+        }
+        Format::Yaml => {
+            let de = serde_yaml::Deserializer::from_str(input);
+            let mut out = vec![];
+            let mut ser = serde_yaml::Serializer::new(&mut out);
+            transcode(
+                ByteFmtDeserializer::new(de, from),
+                ByteFmtSerializer::new(&mut ser, to),
+                fields,
+            )
+            .map_err(|err| err.to_string())?;
+            Ok(String::from_utf8(out).unwrap())
+        }
+        Format::Toml => {
+            // Transcoding into the TOML serializer directly would write datetimes as the tables
+            // `toml` uses to pass them through serde, so go through a `toml::Value` first.
+            let document = Transcode {
+                de: RefCell::new(Some(ByteFmtDeserializer::new(
+                    ::toml::Deserializer::new(input),
+                    from,
+                ))),
+                fields,
+                to,
+            };
+            let mut value = ::toml::Value::try_from(&document).map_err(|err| err.to_string())?;
+            restore_datetimes(&mut value)?;
+            ::toml::to_string(&value).map_err(|err| err.to_string())
+        }
+    }
+}
+
+/// Transcodes a document into whatever serializer it's serialized with.
+struct Transcode<'a, D> {
+    de: RefCell<Option<ByteFmtDeserializer<D>>>,
+    fields: &'a [&'a str],
+    to: Encoding,
+}
+
+impl<'de, D> Serialize for Transcode<'_, D>
+where
+    D: Deserializer<'de>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let de = self.de.borrow_mut().take().expect("serialized twice");
+        transcode(
+            de,
+            ByteFmtSerializer::new(serializer, self.to.clone()),
+            self.fields,
+        )
+        .map_err(|err| match err {
+            transcode::Error::De(err) => ser::Error::custom(err),
+            transcode::Error::Ser(err) => err,
+        })
+    }
+}
+
+/// Turns the `{ "$__toml_private_datetime" = "..." }` tables the TOML deserializer hands out
+/// for datetimes back into datetimes.
+fn restore_datetimes(value: &mut ::toml::Value) -> Result<(), String> {
+    match value {
+        ::toml::Value::Table(table) => {
+            if let (1, Some(::toml::Value::String(datetime))) =
+                (table.len(), table.get(TOML_DATETIME_FIELD))
+            {
+                *value = ::toml::Value::Datetime(
+                    datetime
+                        .parse()
+                        .map_err(|err| format!("invalid datetime `{}`: {}", datetime, err))?,
+                );
+                return Ok(());
+            }
+            table.iter_mut().try_for_each(|(_, v)| restore_datetimes(v))
+        }
+        ::toml::Value::Array(values) => values.iter_mut().try_for_each(restore_datetimes),
+        _ => Ok(()),
+    }
+}
+
+const TOML_DATETIME_FIELD: &str = "$__toml_private_datetime";
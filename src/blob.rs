@@ -0,0 +1,162 @@
+//! Keeping large values out of documents, in a content-addressed store.
+//!
+//! Bytes longer than a threshold are put into a [`BlobStore`] and written as a reference to
+//! their SHA-256, like `{"$blob":"sha256:2c26b46b…"}`. When deserializing, references are
+//! read back from the same store and checked against their hash. Both only happen in human
+//! readable formats, since references are read through `deserialize_any`.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{blob::FsBlobStore, ByteFmtDeserializer, ByteFmtSerializer};
+//! use std::sync::Arc;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Artifact {
+//!     #[serde(with = "serde_bytes")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! # let dir = std::env::temp_dir().join(format!("blob-doc-{}", std::process::id()));
+//! let store = Arc::new(FsBlobStore::new(&dir));
+//! let artifact = Artifact { data: b"foo".to_vec() };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! let ser = ByteFmtSerializer::hex(&mut ser).blob_store(store.clone(), 2);
+//! artifact.serialize(ser).unwrap();
+//! assert_eq!(
+//!     r#"{"data":{"$blob":"sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"}}"#,
+//!     String::from_utf8(out.clone()).unwrap()
+//! );
+//!
+//! let mut de = serde_json::Deserializer::from_slice(&out);
+//! let de = ByteFmtDeserializer::new_hex(&mut de).blob_store(store);
+//! assert_eq!(b"foo", &Artifact::deserialize(de).unwrap().data[..]);
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use crate::ByteFormat;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Where externalized bytes are kept.
+///
+/// Keys are `sha256:` followed by the lowercase HEX of the content's SHA-256, so a blob that
+/// is already stored doesn't need to be written again.
+pub trait BlobStore: Send + Sync {
+    /// Stores `bytes` under `key`.
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Reads the bytes stored under `key`.
+    fn get(&self, key: &str) -> io::Result<Vec<u8>>;
+}
+
+/// A [`BlobStore`] keeping each blob in a file of a directory, named after its key.
+#[derive(Clone, Debug)]
+pub struct FsBlobStore {
+    dir: PathBuf,
+}
+
+impl FsBlobStore {
+    /// Stores blobs in `dir`, which is created on the first write.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        FsBlobStore {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // `:` isn't allowed in file names on Windows.
+        self.dir.join(key.replace(':', "-"))
+    }
+}
+
+impl BlobStore for FsBlobStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key);
+        if path.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)?;
+        // Written aside and renamed, so readers never see a partial blob.
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &path)
+    }
+
+    fn get(&self, key: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path(key))
+    }
+}
+
+/// A serializer's encoding along with a [`BlobStore`].
+#[derive(Clone)]
+pub(crate) struct Externalizing {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) store: Arc<dyn BlobStore>,
+    /// Longest value kept inline, only used when serializing.
+    pub(crate) threshold: usize,
+}
+
+impl Externalizing {
+    /// Whether `v` is written inline.
+    pub(crate) fn fits(&self, v: &[u8]) -> bool {
+        v.len() <= self.threshold
+    }
+
+    /// Stores `v` and returns its key.
+    pub(crate) fn put(&self, v: &[u8]) -> Result<String, Failure> {
+        let key = key(&Sha256::digest(v));
+        match self.store.put(&key, v) {
+            Ok(()) => Ok(key),
+            Err(err) => Err(Failure::Store(key, err)),
+        }
+    }
+}
+
+/// Map key of blob references.
+pub(crate) const REFERENCE_KEY: &str = "$blob";
+
+const KEY_PREFIX: &str = "sha256:";
+
+fn key(digest: &[u8]) -> String {
+    let mut key = String::from(KEY_PREFIX);
+    key.push_str(&hex::encode(digest));
+    key
+}
+
+/// Reads the blob `key` from `store`, checking its hash.
+pub(crate) fn get(store: &dyn BlobStore, key: &str) -> Result<Vec<u8>, Failure> {
+    let valid = key.strip_prefix(KEY_PREFIX).is_some_and(|hash| {
+        hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    });
+    if !valid {
+        return Err(Failure::InvalidKey(key.into()));
+    }
+    let bytes = store
+        .get(key)
+        .map_err(|err| Failure::Store(key.into(), err))?;
+    if self::key(&Sha256::digest(&bytes)) != key {
+        return Err(Failure::Mismatch(key.into()));
+    }
+    Ok(bytes)
+}
+
+/// Why a blob couldn't be stored or read.
+pub(crate) enum Failure {
+    InvalidKey(String),
+    Store(String, io::Error),
+    Mismatch(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::InvalidKey(key) => write!(f, "invalid blob reference `{}`", key),
+            Failure::Store(key, err) => write!(f, "blob `{}`: {}", key, err),
+            Failure::Mismatch(key) => write!(f, "blob `{}` doesn't match its hash", key),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! `bytes::Bytes` and `BytesMut` fields, with `#[serde(with = "serde_bytes_repr::bytes")]`.
+//!
+//! [`ByteFmtDeserializer`] hands decoded values to the visitor as a `Vec<u8>`, which
+//! [`deserialize`] turns into `Bytes` without copying it. [`ByteFmtSerializer`] writes values
+//! from [`serialize`] while it encodes them, instead of encoding them into a `String` first,
+//! unless the `zeroize` feature is enabled: formats may buffer the pieces in a `String` of their
+//! own, which can't be wiped. Other serializers write them like any other bytes.
+//!
+//! With the `zeroize` feature the buffer the bytes were decoded in is handed over as well, so
+//! the `Bytes` owns the only copy. It isn't wiped when it's dropped.
+//!
+//! ```rust
+//! use bytes::Bytes;
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Packet {
+//!     #[serde(with = "serde_bytes_repr::bytes")]
+//!     payload: Bytes,
+//! }
+//!
+//! let packet = Packet { payload: Bytes::from_static(b"ping") };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! packet.serialize(ByteFmtSerializer::hex(&mut ser)).unwrap();
+//! assert_eq!(br#"{"payload":"70696e67"}"#, &out[..]);
+//!
+//! let mut de = serde_json::Deserializer::from_slice(&out);
+//! let packet = Packet::deserialize(ByteFmtDeserializer::new_hex(&mut de)).unwrap();
+//! assert_eq!(b"ping", &packet.payload[..]);
+//! ```
+//!
+//! [`ByteFmtDeserializer`]: crate::ByteFmtDeserializer
+//! [`ByteFmtSerializer`]: crate::ByteFmtSerializer
+use ::bytes::Bytes;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{de, Deserializer, Serialize, Serializer};
+
+/// The name of the newtype [`serialize`] wraps the bytes in, which tells the adapter to write
+/// them while it encodes them. Formats write newtypes as their content.
+pub(crate) const STREAMED: &str = "$serde_bytes_repr::bytes::Streamed";
+
+/// Serializes `Bytes`, `BytesMut` or anything else holding bytes as bytes.
+pub fn serialize<T, S>(v: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + AsRef<[u8]>,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(STREAMED, &Streamed(v.as_ref()))
+}
+
+struct Streamed<'a>(&'a [u8]);
+
+impl Serialize for Streamed<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Deserializes `Bytes` or `BytesMut`, taking over the buffer the bytes were decoded in.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<Bytes>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_byte_buf(BytesVisitor).map(T::from)
+}
+
+struct BytesVisitor;
+
+impl<'de> de::Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Bytes::copy_from_slice(v))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Bytes::from(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_string<E: de::Error>(self, v: alloc::string::String) -> Result<Self::Value, E> {
+        self.visit_byte_buf(v.into_bytes())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(Bytes::from(bytes))
+    }
+}
@@ -0,0 +1,176 @@
+//! Checksums appended to values, to catch values that were changed by hand.
+//!
+//! The checksum of the bytes is appended to them before they are encoded, and checked and
+//! removed by a deserializer set up with the same [`Checksum`].
+//!
+//! ```rust
+//! # #[cfg(feature = "crc32c")] {
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{checksum::Checksum, ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "serde_bytes")]
+//!     key: Vec<u8>,
+//! }
+//!
+//! let config = Config { key: vec![0x0a, 0x0b] };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! config
+//!     .serialize(ByteFmtSerializer::hex(&mut ser).checksum(Checksum::crc32c()))
+//!     .unwrap();
+//! assert_eq!(r#"{"key":"0a0bd2b92fbf"}"#, String::from_utf8(out).unwrap());
+//!
+//! let edited = r#"{"key":"0a0cd2b92fbf"}"#;
+//! let mut de = serde_json::Deserializer::from_str(edited);
+//! let de = ByteFmtDeserializer::new_hex(&mut de).checksum(Checksum::crc32c());
+//! let err = Config::deserialize(de).err().unwrap();
+//! assert_eq!(
+//!     "invalid value: checksum mismatch, expected bytes with a valid crc32c checksum in `/key` \
+//!      at line 1 column 21",
+//!     err.to_string()
+//! );
+//! # }
+//! ```
+
+// The `checksum` feature alone enables none of the algorithms.
+#![cfg_attr(
+    not(any(feature = "crc32c", feature = "xxhash", feature = "sha2")),
+    allow(dead_code, unreachable_code, unused_variables)
+)]
+
+use crate::{wipe::Wiped, ByteFormat};
+use alloc::vec::Vec;
+
+/// An algorithm and how many bytes of its output are appended, for
+/// [`ByteFmtSerializer::checksum`](crate::ByteFmtSerializer::checksum) and
+/// [`ByteFmtDeserializer::checksum`](crate::ByteFmtDeserializer::checksum).
+#[derive(Clone, Copy, Debug)]
+pub struct Checksum {
+    algorithm: Algorithm,
+    len: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Algorithm {
+    #[cfg(feature = "crc32c")]
+    Crc32c,
+    #[cfg(feature = "xxhash")]
+    Xxh3,
+    #[cfg(feature = "sha2")]
+    Sha256,
+}
+
+impl Checksum {
+    fn new(algorithm: Algorithm) -> Self {
+        Checksum {
+            algorithm,
+            len: algorithm.len(),
+        }
+    }
+
+    /// CRC-32C, 4 bytes.
+    #[cfg(feature = "crc32c")]
+    pub fn crc32c() -> Self {
+        Checksum::new(Algorithm::Crc32c)
+    }
+
+    /// 64 bit XXH3, 8 bytes.
+    #[cfg(feature = "xxhash")]
+    pub fn xxh3() -> Self {
+        Checksum::new(Algorithm::Xxh3)
+    }
+
+    /// SHA-256, 32 bytes. Usually shortened with [`len`](Self::len).
+    #[cfg(feature = "sha2")]
+    pub fn sha256() -> Self {
+        Checksum::new(Algorithm::Sha256)
+    }
+
+    /// Only appends the first `len` bytes of the checksum.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is 0 or longer than the algorithm's output.
+    pub fn len(mut self, len: usize) -> Self {
+        assert!(
+            (1..=self.algorithm.len()).contains(&len),
+            "{} checksums are 1 to {} bytes long",
+            self.algorithm.name(),
+            self.algorithm.len()
+        );
+        self.len = len;
+        self
+    }
+
+    /// `v` with its checksum appended.
+    pub(crate) fn append(&self, v: &[u8]) -> Vec<u8> {
+        let mut checksummed = Vec::with_capacity(v.len() + self.len);
+        checksummed.extend_from_slice(v);
+        checksummed.extend_from_slice(&self.algorithm.sum(v)[..self.len]);
+        checksummed
+    }
+
+    /// Checks the checksum at the end of `v` and removes it, `None` if it doesn't match.
+    pub(crate) fn strip(&self, v: Vec<u8>) -> Option<Vec<u8>> {
+        let mut v = Wiped(v);
+        let len = v.len().checked_sub(self.len)?;
+        if self.algorithm.sum(&v[..len])[..self.len] != v[len..] {
+            return None;
+        }
+        v.truncate(len);
+        Some(v.into_inner())
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.algorithm.name()
+    }
+}
+
+impl Algorithm {
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "crc32c")]
+            Algorithm::Crc32c => "crc32c",
+            #[cfg(feature = "xxhash")]
+            Algorithm::Xxh3 => "xxh3",
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => "sha256",
+        }
+    }
+
+    fn len(self) -> usize {
+        match self {
+            #[cfg(feature = "crc32c")]
+            Algorithm::Crc32c => 4,
+            #[cfg(feature = "xxhash")]
+            Algorithm::Xxh3 => 8,
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => 32,
+        }
+    }
+
+    /// The whole checksum of `v`, in big-endian for the integer ones.
+    fn sum(self, v: &[u8]) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "crc32c")]
+            Algorithm::Crc32c => crc32c::crc32c(v).to_be_bytes().to_vec(),
+            #[cfg(feature = "xxhash")]
+            Algorithm::Xxh3 => xxhash_rust::xxh3::xxh3_64(v).to_be_bytes().to_vec(),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => {
+                use sha2::Digest;
+
+                sha2::Sha256::digest(v).to_vec()
+            }
+        }
+    }
+}
+
+/// An encoding along with a [`Checksum`].
+#[derive(Clone)]
+pub(crate) struct Checksumming {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) checksum: Checksum,
+}
@@ -0,0 +1,239 @@
+//! Compressing values before encoding them.
+//!
+//! Compressed values are written with the name of the algorithm in front, like
+//! `gzip:H4sIAAAAAAAA…`. A deserializer set up with
+//! [`ByteFmtDeserializer::decompress`](crate::ByteFmtDeserializer::decompress) recognizes the
+//! prefix and decompresses the bytes after decoding them. Plain values are still read as
+//! before.
+//!
+//! ```rust
+//! # #[cfg(feature = "gzip")] {
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{compress::Compression, ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Log {
+//!     #[serde(with = "serde_bytes")]
+//!     text: Vec<u8>,
+//! }
+//!
+//! let log = Log { text: b"GET /index.html 200\n".repeat(100) };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! let base64_config = base64::engine::GeneralPurposeConfig::new();
+//! let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::STANDARD, base64_config)
+//!     .compress(Compression::gzip());
+//! log.serialize(ser).unwrap();
+//! assert!(out.starts_with(br#"{"text":"gzip:H4sI"#));
+//! assert!(out.len() < 200);
+//!
+//! let mut de = serde_json::Deserializer::from_slice(&out);
+//! let de = ByteFmtDeserializer::new_base64(&mut de, base64::alphabet::STANDARD, base64_config)
+//!     .decompress(1 << 20);
+//! assert_eq!(log.text, Log::deserialize(de).unwrap().text);
+//! # }
+//! ```
+
+// The `compress` feature alone enables none of the algorithms.
+#![cfg_attr(
+    not(any(feature = "gzip", feature = "deflate", feature = "zstd")),
+    allow(dead_code, unreachable_code, unused_variables)
+)]
+
+use crate::{
+    wipe::{self, Wiped},
+    ByteFormat,
+};
+use std::io::{self, Read};
+
+/// An algorithm and the length from which values are compressed, for
+/// [`ByteFmtSerializer::compress`](crate::ByteFmtSerializer::compress).
+///
+/// Values are written uncompressed if they are at most [`threshold`](Self::threshold) bytes
+/// long, or if compressing doesn't make them shorter.
+#[derive(Clone, Debug)]
+pub struct Compression {
+    algorithm: Algorithm,
+    threshold: usize,
+}
+
+/// Default for [`Compression::threshold`]. Shorter values rarely get smaller.
+pub const DEFAULT_THRESHOLD: usize = 64;
+
+impl Compression {
+    fn new(algorithm: Algorithm) -> Self {
+        Compression {
+            algorithm,
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    /// Gzip, written with a `gzip:` prefix.
+    #[cfg(feature = "gzip")]
+    pub fn gzip() -> Self {
+        Compression::new(Algorithm::Gzip)
+    }
+
+    /// Raw DEFLATE, written with a `deflate:` prefix.
+    #[cfg(feature = "deflate")]
+    pub fn deflate() -> Self {
+        Compression::new(Algorithm::Deflate)
+    }
+
+    /// Zstandard, written with a `zstd:` prefix.
+    #[cfg(feature = "zstd")]
+    pub fn zstd() -> Self {
+        Compression::new(Algorithm::Zstd)
+    }
+
+    /// Only compresses values longer than `len` bytes.
+    pub fn threshold(mut self, len: usize) -> Self {
+        self.threshold = len;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Algorithm {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "deflate")]
+    Deflate,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Algorithm {
+    const ALL: &'static [Algorithm] = &[
+        #[cfg(feature = "gzip")]
+        Algorithm::Gzip,
+        #[cfg(feature = "deflate")]
+        Algorithm::Deflate,
+        #[cfg(feature = "zstd")]
+        Algorithm::Zstd,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "gzip")]
+            Algorithm::Gzip => "gzip",
+            #[cfg(feature = "deflate")]
+            Algorithm::Deflate => "deflate",
+            #[cfg(feature = "zstd")]
+            Algorithm::Zstd => "zstd",
+        }
+    }
+
+    /// The algorithm named in front of `v`, and the rest of `v`.
+    pub(crate) fn detect(v: &[u8]) -> Option<(Algorithm, &[u8])> {
+        Algorithm::ALL.iter().find_map(|&algorithm| {
+            let rest = v.strip_prefix(algorithm.name().as_bytes())?;
+            Some((algorithm, rest.strip_prefix(b":")?))
+        })
+    }
+
+    fn compress(self, v: &[u8]) -> io::Result<Vec<u8>> {
+        #[cfg(any(feature = "gzip", feature = "deflate"))]
+        use std::io::Write;
+
+        match self {
+            #[cfg(feature = "gzip")]
+            Algorithm::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(v)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "deflate")]
+            Algorithm::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(v)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Algorithm::Zstd => zstd::encode_all(v, 0),
+        }
+    }
+
+    /// Decompresses `v`, failing if the result is longer than `limit` bytes.
+    pub(crate) fn decompress(self, v: &[u8], limit: usize) -> Result<Vec<u8>, Failure> {
+        let reader: Box<dyn Read + '_> = match self {
+            #[cfg(feature = "gzip")]
+            Algorithm::Gzip => Box::new(flate2::read::GzDecoder::new(v)),
+            #[cfg(feature = "deflate")]
+            Algorithm::Deflate => Box::new(flate2::read::DeflateDecoder::new(v)),
+            #[cfg(feature = "zstd")]
+            Algorithm::Zstd => {
+                Box::new(zstd::Decoder::with_buffer(v).map_err(|_| Failure::Invalid)?)
+            }
+        };
+        // One more byte than allowed tells a value of exactly `limit` bytes from a longer one.
+        let mut reader = reader.take(limit as u64 + 1);
+        let mut decompressed = Wiped(Vec::new());
+        let mut chunk = Wiped([0; DECOMPRESS_CHUNK_LEN]);
+        loop {
+            let len = match reader.read(&mut chunk[..]) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => return Err(Failure::Invalid),
+            };
+            wipe::extend(&mut decompressed, &chunk[..len]);
+        }
+        if decompressed.len() > limit {
+            return Err(Failure::TooLong);
+        }
+        Ok(decompressed.into_inner())
+    }
+}
+
+/// Bytes read from a decompressor at once.
+const DECOMPRESS_CHUNK_LEN: usize = 4096;
+
+/// Why a value couldn't be decompressed.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Failure {
+    Invalid,
+    TooLong,
+}
+
+/// A serializer's encoding along with a [`Compression`].
+#[derive(Clone)]
+pub(crate) struct Compressing {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) compression: Compression,
+}
+
+impl Compressing {
+    /// `v` compressed and encoded with its prefix, `None` if it is written as is.
+    pub(crate) fn encode(&self, v: &[u8]) -> io::Result<Option<String>> {
+        let Compression {
+            algorithm,
+            threshold,
+        } = self.compression;
+        if v.len() <= threshold {
+            return Ok(None);
+        }
+        let compressed = Wiped(algorithm.compress(v)?);
+        if compressed.len() >= v.len() {
+            return Ok(None);
+        }
+        let fmt = &self.fmt;
+        let name = algorithm.name();
+        let len = fmt.encoded_len(compressed.len()).unwrap_or(0);
+        let mut encoded = String::with_capacity(name.len() + 1 + len);
+        encoded.push_str(name);
+        encoded.push(':');
+        // Writing to a `String` can't fail.
+        fmt.encode_to(&compressed, &mut encoded).unwrap();
+        Ok(Some(encoded))
+    }
+}
+
+/// The decompression limit of a deserializer.
+#[derive(Clone)]
+pub(crate) struct Decompressing {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) limit: usize,
+}
@@ -0,0 +1,325 @@
+//! Decoding secrets in constant time, for private keys and other values whose characters
+//! shouldn't show in how long decoding takes.
+//!
+//! The `hex` and `base64` crates look characters up in tables and stop at the first invalid
+//! one. Values selected by [`ConstantTime`] are decoded with arithmetic on every character
+//! instead, and invalid ones are only reported once the whole value was read. Errors are the
+//! same as for the other values. It is a lot slower for Base64, so only select the secrets.
+//!
+//! Only the symbols are protected. Where YAML `!!binary` scalars break their lines and where
+//! padding starts can still show, as can the length of every value.
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use serde_bytes_repr::{constant_time::ConstantTime, ByteFmtDeserializer};
+//!
+//! #[derive(Deserialize)]
+//! struct Account {
+//!     #[serde(with = "serde_bytes")]
+//!     id: Vec<u8>,
+//!     #[serde(with = "serde_bytes")]
+//!     private_key: Vec<u8>,
+//! }
+//!
+//! let json = r#"{"id":"0102","private_key":"9f86d081884c7d65"}"#;
+//! let mut de = serde_json::Deserializer::from_str(json);
+//! let de = ByteFmtDeserializer::new_hex(&mut de)
+//!     .constant_time(ConstantTime::new().fields(["private_key"]));
+//! let account = Account::deserialize(de).unwrap();
+//! assert_eq!(
+//!     vec![0x9f, 0x86, 0xd0, 0x81, 0x88, 0x4c, 0x7d, 0x65],
+//!     account.private_key
+//! );
+//! ```
+use crate::{selection::Selection, wipe::Wiped, ByteFormat};
+use alloc::vec::Vec;
+use base64::{alphabet::Alphabet, engine::GeneralPurpose, DecodeError, Engine};
+
+/// Which bytes [`ByteFmtDeserializer::constant_time`] decodes in constant time.
+///
+/// Bytes below the selected struct fields and newtype structs are decoded in constant time,
+/// the others as usual. Without any field or newtype, all bytes are.
+///
+/// [`ByteFmtDeserializer::constant_time`]: crate::ByteFmtDeserializer::constant_time
+#[derive(Clone, Debug, Default)]
+pub struct ConstantTime {
+    selection: Selection,
+}
+
+impl ConstantTime {
+    /// Selects all bytes, until fields or newtypes are added.
+    pub fn new() -> Self {
+        ConstantTime::default()
+    }
+
+    /// Decodes the bytes below struct fields with these names in constant time.
+    pub fn fields<I>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.selection.add_fields(fields);
+        self
+    }
+
+    /// Decodes the bytes below newtype structs with these names in constant time, like
+    /// `PrivateKey` for `struct PrivateKey(#[serde(with = "serde_bytes")] Vec<u8>)`.
+    pub fn newtypes<I>(mut self, newtypes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.selection.add_newtypes(newtypes);
+        self
+    }
+}
+
+/// A deserializer's encoding along with a [`ConstantTime`].
+#[derive(Clone)]
+pub(crate) struct ConstantTimeDecoding {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) constant_time: ConstantTime,
+}
+
+impl ConstantTimeDecoding {
+    /// The values decoded in constant time.
+    pub(crate) fn selection(&self) -> &Selection {
+        &self.constant_time.selection
+    }
+}
+
+/// `0xff` if `lo <= c <= hi`, `0` otherwise.
+fn in_range(c: u8, lo: u8, hi: u8) -> u8 {
+    let c = i16::from(c);
+    // Both differences are negative only inside the range, which sets the high byte.
+    (((i16::from(lo) - 1 - c) & (c - i16::from(hi) - 1)) >> 8) as u8
+}
+
+/// `0xff` if `a == b`, `0` otherwise.
+fn eq(a: u8, b: u8) -> u8 {
+    in_range(a, b, b)
+}
+
+/// `a` if `mask` is `0xff`, `b` if it is `0`.
+fn select(mask: u8, a: u8, b: u8) -> u8 {
+    (a & mask) | (b & !mask)
+}
+
+/// The first invalid character of a value, picked without branching on where it is.
+struct FirstInvalid {
+    /// All ones once a character was recorded.
+    found: usize,
+    index: usize,
+    c: u8,
+}
+
+impl FirstInvalid {
+    fn new() -> Self {
+        FirstInvalid {
+            found: 0,
+            index: 0,
+            c: 0,
+        }
+    }
+
+    /// Records `c` at `index` if `invalid` is `0xff` and no character was recorded before.
+    fn record(&mut self, invalid: u8, index: usize, c: u8) {
+        let take = usize::from(invalid & 1).wrapping_neg() & !self.found;
+        self.index = (self.index & !take) | (index & take);
+        self.c = select(take as u8, c, self.c);
+        self.found |= take;
+    }
+
+    fn get(&self) -> Option<(usize, u8)> {
+        (self.found != 0).then_some((self.index, self.c))
+    }
+}
+
+/// The value of a HEX digit, along with `0xff` if `c` is one.
+fn hex_digit(c: u8) -> (u8, u8) {
+    let digit = in_range(c, b'0', b'9');
+    let upper = in_range(c, b'A', b'F');
+    let lower = in_range(c, b'a', b'f');
+    let value = (digit & c.wrapping_sub(b'0'))
+        | (upper & c.wrapping_sub(b'A' - 10))
+        | (lower & c.wrapping_sub(b'a' - 10));
+    (value, digit | upper | lower)
+}
+
+/// Decodes HEX into `out`, which has to fit the decoded bytes, like [`hex::decode_to_slice`].
+pub(crate) fn decode_hex(v: &[u8], out: &mut [u8]) -> Result<usize, hex::FromHexError> {
+    if !v.len().is_multiple_of(2) {
+        return Err(hex::FromHexError::OddLength);
+    }
+    let mut invalid = FirstInvalid::new();
+    for (i, pair) in v.chunks_exact(2).enumerate() {
+        let (high, high_valid) = hex_digit(pair[0]);
+        let (low, low_valid) = hex_digit(pair[1]);
+        invalid.record(!high_valid, 2 * i, pair[0]);
+        invalid.record(!low_valid, 2 * i + 1, pair[1]);
+        out[i] = high << 4 | low;
+    }
+    match invalid.get() {
+        Some((index, c)) => Err(hex::FromHexError::InvalidHexCharacter { c: c.into(), index }),
+        None => Ok(v.len() / 2),
+    }
+}
+
+/// Decodes Base64 into `out`, which has to fit the decoded bytes, accepting exactly what
+/// `engine` accepts.
+pub(crate) fn decode_base64(
+    alphabet: &Alphabet,
+    engine: &GeneralPurpose,
+    v: &[u8],
+    out: &mut [u8],
+) -> Result<usize, DecodeError> {
+    Base64Rules::new(alphabet, engine).decode(v, 0, out)
+}
+
+/// Decodes standard Base64 folded over several lines into `out` a quad at a time, like the
+/// deserializer does for `!!binary` scalars. Indices in errors leave out the line breaks.
+///
+/// This isn't strictly constant time: dropping the whitespace and checking each quad branch
+/// on where the line breaks and the padding are, so the timing shows the folding and the
+/// length of the value. It still doesn't depend on the values of the other symbols.
+pub(crate) fn decode_folded_base64(v: &[u8], out: &mut [u8]) -> Result<usize, DecodeError> {
+    let rules = Base64Rules::new(
+        &base64::alphabet::STANDARD,
+        &base64::engine::general_purpose::STANDARD,
+    );
+    // Where the lines break is formatting, not part of the value.
+    let symbols = Wiped(
+        v.iter()
+            .copied()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect::<Vec<u8>>(),
+    );
+    let mut first = None;
+    let mut written = 0;
+    for (i, quad) in symbols.chunks(4).enumerate() {
+        let start = 4 * i;
+        let result = rules.decode(quad, start, &mut out[written..]);
+        // Padding is only valid at the very end.
+        let padding = quad.iter().fold(0, |padding, &c| padding | eq(c, b'='));
+        let result = if start + quad.len() < symbols.len() && padding != 0 {
+            Err(DecodeError::InvalidPadding)
+        } else {
+            result
+        };
+        match result {
+            Ok(len) => written += len,
+            Err(err) => {
+                first.get_or_insert(err);
+            }
+        }
+    }
+    first.map_or(Ok(written), Err)
+}
+
+/// What a Base64 engine accepts.
+struct Base64Rules<'a> {
+    symbols: &'a [u8],
+    padded: bool,
+    unpadded: bool,
+    trailing_bits: bool,
+}
+
+impl<'a> Base64Rules<'a> {
+    fn new(alphabet: &'a Alphabet, engine: &GeneralPurpose) -> Self {
+        let symbols = alphabet.as_str().as_bytes();
+        // The engine's decoding options aren't public, so find them out by trying. The second
+        // symbol leaves non-zero trailing bits.
+        let accepts = |s: &[u8]| engine.decode_slice(s, &mut [0; 3]).is_ok();
+        let (a, b) = (symbols[0], symbols[1]);
+        Base64Rules {
+            symbols,
+            padded: accepts(&[a, a, b'=', b'=']),
+            unpadded: accepts(&[a, a]),
+            trailing_bits: accepts(&[a, b, b'=', b'=']) || accepts(&[a, b]),
+        }
+    }
+
+    /// The value of a symbol, along with `0xff` if `c` is one. Compares `c` with the whole
+    /// alphabet, which can be any permutation.
+    fn symbol(&self, c: u8) -> (u8, u8) {
+        let mut value = 0;
+        let mut valid = 0;
+        for (i, &symbol) in self.symbols.iter().enumerate() {
+            let eq = eq(c, symbol);
+            value |= eq & i as u8;
+            valid |= eq;
+        }
+        (value, valid)
+    }
+
+    /// Decodes `v` with the checks of the `base64` engine in the same order, so the first
+    /// one failing is reported. `start` is added to the indices in errors.
+    fn decode(&self, v: &[u8], start: usize, out: &mut [u8]) -> Result<usize, DecodeError> {
+        let rem = v.len() % 4;
+        // The last quad may be partial or padded.
+        let body = v.len().saturating_sub(if rem == 0 { 4 } else { rem });
+        let mut invalid = FirstInvalid::new();
+
+        // A lone invalid character at the end is reported first.
+        if rem == 1 {
+            let c = v[v.len() - 1];
+            let (_, valid) = self.symbol(c);
+            invalid.record(!valid & !eq(c, b'='), start + v.len() - 1, c);
+        }
+
+        for (i, quad) in v[..body].chunks_exact(4).enumerate() {
+            let mut bits = 0;
+            for (j, &c) in quad.iter().enumerate() {
+                let (value, valid) = self.symbol(c);
+                invalid.record(!valid, start + 4 * i + j, c);
+                bits = bits << 6 | u32::from(value);
+            }
+            out[3 * i..3 * i + 3].copy_from_slice(&bits.to_be_bytes()[1..]);
+        }
+
+        let mut bits = 0;
+        let mut symbols = 0;
+        let mut padding = 0;
+        let mut first_padding = 0;
+        let mut last = 0;
+        for (k, &c) in v[body..].iter().enumerate() {
+            let pad = eq(c, b'=');
+            let (value, valid) = self.symbol(c);
+            if k < 2 {
+                invalid.record(pad, start + body + k, c);
+            }
+            // A symbol after padding is reported at the padding.
+            let seen = select(eq(padding, 0), 0, 0xff);
+            invalid.record(!pad & seen, start + body + first_padding, b'=');
+            invalid.record(!pad & !valid, start + body + k, c);
+            let first = usize::from(pad & !seen & 1).wrapping_neg();
+            first_padding = (first_padding & !first) | (k & first);
+            padding += pad & 1;
+            symbols += usize::from(!pad & 1);
+            bits |= u32::from(value & !pad) << (26 - 6 * k);
+            last = select(pad, last, c);
+        }
+
+        if let Some((index, c)) = invalid.get() {
+            return Err(DecodeError::InvalidByte(index, c));
+        }
+        if !v.is_empty() && symbols < 2 {
+            return Err(DecodeError::InvalidLength(start + body + symbols));
+        }
+        if (!self.unpadded && (usize::from(padding) + symbols) % 4 != 0)
+            || (!self.padded && padding > 0)
+        {
+            return Err(DecodeError::InvalidPadding);
+        }
+        let len = symbols * 6 / 8;
+        if !self.trailing_bits && bits & (!0 >> (len * 8)) != 0 {
+            return Err(DecodeError::InvalidLastSymbol(
+                start + body + symbols - 1,
+                last,
+            ));
+        }
+        let written = body / 4 * 3;
+        out[written..written + len].copy_from_slice(&bits.to_be_bytes()[..len]);
+        Ok(written + len)
+    }
+}
@@ -1,7 +1,41 @@
-use crate::{ByteFmtDeserializer, ByteFormat};
+#[cfg(feature = "blob")]
+use crate::blob::{self, BlobStore};
+#[cfg(feature = "checksum")]
+use crate::checksum::Checksum;
+#[cfg(feature = "compress")]
+use crate::compress;
+#[cfg(feature = "alloc")]
+use crate::constant_time::{self, ConstantTimeDecoding};
+#[cfg(feature = "encrypt")]
+use crate::encrypt::{self, Encrypting};
+#[cfg(feature = "alloc")]
+use crate::envelope::{self, Envelope, Enveloping};
+#[cfg(feature = "alloc")]
+use crate::error::{DecodeError, Error};
+#[cfg(feature = "alloc")]
+use crate::path::Segment;
+#[cfg(feature = "alloc")]
+use crate::selection::Selection;
+#[cfg(feature = "hmac")]
+use crate::sign::Hmac;
+#[cfg(feature = "simd")]
+use crate::simd;
+use crate::{wipe::Wiped, Base64Codec, ByteFmtDeserializer, ByteFormat, PREVIEW_MARKER};
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use base64::Engine;
+#[cfg(feature = "alloc")]
+use core::cell::RefCell;
+use core::fmt::{self, Write};
+#[cfg(feature = "alloc")]
+use core::marker::PhantomData;
 use serde::de;
-use std::fmt;
 
 impl<'de, D> de::Deserializer<'de> for ByteFmtDeserializer<D>
 where
@@ -13,112 +47,919 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_any(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_any(visitor)
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_bool(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_bool(visitor)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_u8(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_u8(visitor)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_u16(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_u16(visitor)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_u32(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_u32(visitor)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_u64(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_u64(visitor)
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_u128(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_u128(visitor)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_i8(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_i8(visitor)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_i16(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_i16(visitor)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_i32(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_i32(visitor)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_i64(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_i64(visitor)
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_i128(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_i128(visitor)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_f32(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_f32(visitor)
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_f64(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_f64(visitor)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_char(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_char(visitor)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_str(Visitor::new(visitor, self.fmt))
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_str(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_string(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_bytes(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_option(visitor)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_unit(visitor)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_map(visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_ignored_any(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ctx = Context::new(self.fmt, self.plain_scalars);
+        Deserializer::new(self.inner, &ctx).deserialize_identifier(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Deserializes `T`, keeping the details of the value that failed to decode.
+#[cfg(feature = "alloc")]
+pub(crate) fn deserialize<'de, T, D>(
+    deserializer: ByteFmtDeserializer<D>,
+) -> Result<T, Error<D::Error>>
+where
+    T: de::Deserialize<'de>,
+    D: de::Deserializer<'de>,
+{
+    let ctx = Context::new(deserializer.fmt, deserializer.plain_scalars);
+    T::deserialize(Deserializer::new(deserializer.inner, &ctx))
+        .map_err(|err| Error::new(err, ctx.errors.into_inner().pop()))
+}
+
+/// Deserializes `T`, replacing values that fail to decode with empty bytes.
+#[cfg(feature = "alloc")]
+pub(crate) fn deserialize_recovering<'de, T, D>(
+    deserializer: ByteFmtDeserializer<D>,
+) -> Result<(T, Vec<DecodeError>), Error<D::Error>>
+where
+    T: de::Deserialize<'de>,
+    D: de::Deserializer<'de>,
+{
+    let mut ctx = Context::new(deserializer.fmt, deserializer.plain_scalars);
+    ctx.recover = true;
+    let result = T::deserialize(Deserializer::new(deserializer.inner, &ctx));
+    match result {
+        Ok(value) => Ok((value, ctx.errors.into_inner())),
+        // Either something else, or a target the empty bytes don't fit.
+        Err(err) => Err(Error::recovering(err, ctx.errors.into_inner())),
+    }
+}
+
+/// Shared by everything below one [`ByteFmtDeserializer`], which lives on its stack.
+pub(crate) struct Context {
+    fmt: ByteFormat,
+    /// Ask for strings where bytes are expected, for plain YAML scalars like `1234`.
+    plain_scalars: bool,
+    /// Values that failed to decode. Only the last one is kept unless recovering.
+    #[cfg(feature = "alloc")]
+    errors: RefCell<Vec<DecodeError>>,
+    /// Replace values that fail to decode with empty bytes instead of failing.
+    #[cfg(feature = "alloc")]
+    recover: bool,
+    /// Where blob references are read from.
+    #[cfg(feature = "blob")]
+    blobs: Option<Arc<dyn BlobStore>>,
+    /// The keys of envelopes, if values may be in one.
+    #[cfg(feature = "alloc")]
+    envelope: Option<Arc<Enveloping>>,
+    /// Longest value compressed ones may decompress to, if they are decompressed at all.
+    #[cfg(feature = "compress")]
+    decompress_limit: Option<usize>,
+    /// Checksum at the end of every value.
+    #[cfg(feature = "checksum")]
+    checksum: Option<Checksum>,
+    /// Keys for the signature at the end of every value, checked after the checksum.
+    #[cfg(feature = "hmac")]
+    hmac: Option<Hmac>,
+    /// Keys for the selected values, which must be encrypted.
+    #[cfg(feature = "encrypt")]
+    encryption: Option<Arc<Encrypting>>,
+    /// Which values are decoded in constant time.
+    #[cfg(feature = "alloc")]
+    constant_time: Option<Arc<ConstantTimeDecoding>>,
+    /// Decode strings where a sequence or tuple is expected, for sequences of `u8`.
+    #[cfg(feature = "alloc")]
+    u8_sequences: bool,
+}
+
+impl Context {
+    fn new(fmt: ByteFormat, plain_scalars: bool) -> Self {
+        #[cfg_attr(not(feature = "alloc"), allow(unused_mut))]
+        let mut ctx = Context {
+            fmt,
+            plain_scalars,
+            #[cfg(feature = "alloc")]
+            errors: RefCell::new(Vec::new()),
+            #[cfg(feature = "alloc")]
+            recover: false,
+            #[cfg(feature = "blob")]
+            blobs: None,
+            #[cfg(feature = "alloc")]
+            envelope: None,
+            #[cfg(feature = "compress")]
+            decompress_limit: None,
+            #[cfg(feature = "checksum")]
+            checksum: None,
+            #[cfg(feature = "hmac")]
+            hmac: None,
+            #[cfg(feature = "encrypt")]
+            encryption: None,
+            #[cfg(feature = "alloc")]
+            constant_time: None,
+            #[cfg(feature = "alloc")]
+            u8_sequences: false,
+        };
+        #[cfg(feature = "alloc")]
+        ctx.take_options();
+        ctx
+    }
+
+    /// Whether values may be objects: blob references or envelopes.
+    pub(crate) fn reads_objects(&self) -> bool {
+        #[cfg(feature = "blob")]
+        if self.blobs.is_some() {
+            return true;
+        }
+        #[cfg(feature = "alloc")]
+        if self.envelope.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// Moves the options of the deserializer, which wrap its format, to the context.
+    #[cfg(feature = "alloc")]
+    fn take_options(&mut self) {
+        loop {
+            let fmt = match &self.fmt {
+                #[cfg(feature = "blob")]
+                ByteFormat::Blob(externalizing) => {
+                    self.blobs = Some(externalizing.store.clone());
+                    externalizing.fmt.clone()
+                }
+                ByteFormat::Envelope(enveloping) => {
+                    self.envelope = Some(enveloping.clone());
+                    enveloping.fmt.clone()
+                }
+                #[cfg(feature = "compress")]
+                ByteFormat::Decompressing(decompressing) => {
+                    self.decompress_limit = Some(decompressing.limit);
+                    decompressing.fmt.clone()
+                }
+                #[cfg(feature = "hmac")]
+                ByteFormat::Signing(signing) => {
+                    self.hmac = Some(signing.hmac.clone());
+                    signing.fmt.clone()
+                }
+                #[cfg(feature = "checksum")]
+                ByteFormat::Checksumming(checksumming) => {
+                    self.checksum = Some(checksumming.checksum);
+                    checksumming.fmt.clone()
+                }
+                #[cfg(feature = "encrypt")]
+                ByteFormat::Encrypting(encrypting) => {
+                    self.encryption = Some(encrypting.clone());
+                    encrypting.fmt.clone()
+                }
+                ByteFormat::ConstantTime(decoding) => {
+                    self.constant_time = Some(decoding.clone());
+                    decoding.fmt.clone()
+                }
+                ByteFormat::Sequences(fmt) => {
+                    self.u8_sequences = true;
+                    (**fmt).clone()
+                }
+                _ => return,
+            };
+            self.fmt = fmt;
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Context {
+    /// Decodes `v`, in constant time if asked to, decompresses it if it starts with the name of
+    /// an algorithm and checks its checksum and signature.
+    fn decode(&self, v: &[u8], constant_time: bool) -> Result<Vec<u8>, Failure> {
+        let decoded = self.decode_compressed(&self.fmt, v, constant_time)?;
+        self.verify(decoded)
+    }
+
+    /// Like [`decode`](Self::decode) for the data of an envelope, which is in `fmt` and must
+    /// be `length` bytes long. Envelopes can't hold checksums or signatures.
+    fn decode_enveloped(
+        &self,
+        fmt: &ByteFormat,
+        v: &[u8],
+        length: usize,
+        constant_time: bool,
+    ) -> Result<Vec<u8>, Failure> {
+        #[cfg(feature = "checksum")]
+        if self.appends() {
+            return Err(Failure::Custom(envelope::APPENDED));
+        }
+        let decoded = Wiped(self.decode_compressed(fmt, v, constant_time)?);
+        if decoded.len() != length {
+            return Err(Failure::DeclaredLength {
+                len: decoded.len(),
+                declared: length,
+            });
+        }
+        self.verify(decoded.into_inner())
+    }
+
+    fn decode_compressed(
+        &self,
+        fmt: &ByteFormat,
+        v: &[u8],
+        constant_time: bool,
+    ) -> Result<Vec<u8>, Failure> {
+        let decode = |v: &[u8]| match constant_time {
+            true => fmt.decode_constant_time(v),
+            false => fmt.decode_to_vec(v),
+        };
+        #[cfg(feature = "compress")]
+        if let Some(limit) = self.decompress_limit {
+            if let Some((algorithm, compressed)) = compress::Algorithm::detect(v) {
+                let prefix_len = v.len() - compressed.len();
+                let compressed =
+                    Wiped(decode(compressed).map_err(|failure| failure.shift(prefix_len))?);
+                return algorithm.decompress(&compressed, limit).map_err(|failure| {
+                    Failure::Decompress {
+                        algorithm: algorithm.name(),
+                        failure,
+                        limit,
+                    }
+                });
+            }
+        }
+        decode(v)
+    }
+
+    /// Checks and removes the checksum and signature of `v`, if there are any.
+    fn verify(&self, v: Vec<u8>) -> Result<Vec<u8>, Failure> {
+        #[cfg(feature = "checksum")]
+        let v = match &self.checksum {
+            Some(checksum) => checksum.strip(v).ok_or(Failure::Checksum {
+                algorithm: checksum.name(),
+            })?,
+            None => v,
+        };
+        #[cfg(feature = "hmac")]
+        let v = match &self.hmac {
+            Some(hmac) => hmac.strip(v).ok_or(Failure::Signature)?,
+            None => v,
+        };
+        Ok(v)
+    }
+
+    /// Whether something is appended to the bytes, so that `Array` values have to be collected
+    /// before handing them over.
+    #[cfg(feature = "checksum")]
+    fn appends(&self) -> bool {
+        #[cfg(feature = "hmac")]
+        if self.hmac.is_some() {
+            return true;
+        }
+        self.checksum.is_some()
+    }
+}
+
+/// Where a wrapper sits in the document.
+struct Position<'a> {
+    ctx: &'a Context,
+    path: Path<'a>,
+    /// Set while deserializing a map key or variant name, which is written here.
+    key: Option<&'a mut Key>,
+}
+
+impl<'a> Position<'a> {
+    fn new(ctx: &'a Context, path: Path<'a>) -> Self {
+        Position {
+            ctx,
+            path,
+            key: None,
+        }
+    }
+
+    /// The keys of the value here, if it must be encrypted.
+    #[cfg(feature = "encrypt")]
+    fn encryption(&self) -> Option<&'a Encrypting> {
+        let encrypting = self.ctx.encryption.as_deref()?;
+        let selection = encrypting.selection();
+        (selection.everything() || self.path.selected_by(selection)).then_some(encrypting)
+    }
+
+    /// Whether the value here is decoded in constant time.
+    #[cfg(feature = "alloc")]
+    fn constant_time(&self) -> bool {
+        self.ctx
+            .constant_time
+            .as_deref()
+            .map(ConstantTimeDecoding::selection)
+            .is_some_and(|selection| selection.everything() || self.path.selected_by(selection))
+    }
+}
+
+/// Decoding for [`transcode`](crate::transcode), which doesn't know the types of the values,
+/// so they are only selected by the keys on their path.
+#[cfg(feature = "alloc")]
+impl Context {
+    /// Resolves the options of a [`ByteFmtDeserializer`] with the format `fmt`.
+    pub(crate) fn for_transcoding(fmt: ByteFormat) -> Self {
+        Context::new(fmt, false)
+    }
+
+    /// The encoding below the options.
+    pub(crate) fn encoding(&self) -> &ByteFormat {
+        &self.fmt
+    }
+
+    /// Decodes the string `v` at `path` like a value of the deserializer.
+    pub(crate) fn decode_at<E: de::Error>(&self, v: &[u8], path: &[Segment]) -> Result<Vec<u8>, E> {
+        #[cfg(feature = "encrypt")]
+        if let Some(encrypting) = self.encryption.as_deref() {
+            if selected_at(encrypting.selection(), path) {
+                return encrypting
+                    .open(v)
+                    .map_err(Failure::Decrypt)
+                    .and_then(|opened| self.verify(opened))
+                    .map_err(|failure| failure.to_error(&Path::Root));
+            }
+        }
+        self.decode(v, self.constant_time_at(path))
+            .map_err(|failure| failure.to_error(&Path::Root))
+    }
+
+    /// Checks the checksum and signature of the integers read at a path in place of bytes.
+    pub(crate) fn verify_array<E: de::Error>(&self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+        self.verify(bytes)
+            .map_err(|failure| failure.to_error(&Path::Root))
+    }
+
+    /// Reads the blob reference or envelope `map` at `path`, if
+    /// [`reads_objects`](Self::reads_objects).
+    pub(crate) fn decode_object<'de, A>(
+        &self,
+        map: A,
+        path: &[Segment],
+    ) -> Result<Vec<u8>, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        #[cfg(feature = "blob")]
+        if let Some(store) = &self.blobs {
+            let bytes = read_blob(map, &**store, &Path::Root)?;
+            return self
+                .verify(bytes)
+                .map_err(|failure| failure.to_error(&Path::Root));
+        }
+        let enveloping = match &self.envelope {
+            Some(enveloping) => enveloping,
+            None => return Err(de::Error::invalid_type(de::Unexpected::Map, &"bytes")),
+        };
+        let (fmt, length, data) = read_envelope(map, &enveloping.envelope, &Path::Root)?;
+        self.decode_enveloped(&fmt, data.as_bytes(), length, self.constant_time_at(path))
+            .map_err(|failure| failure.to_error(&Path::Root))
+    }
+
+    fn constant_time_at(&self, path: &[Segment]) -> bool {
+        self.constant_time
+            .as_deref()
+            .is_some_and(|decoding| selected_at(decoding.selection(), path))
+    }
+}
+
+/// Whether a key on `path` is selected by `selection`, or every value is.
+#[cfg(feature = "alloc")]
+fn selected_at(selection: &Selection, path: &[Segment]) -> bool {
+    selection.everything()
+        || path.iter().any(|segment| match segment {
+            Segment::Key(key) => selection.is_field(key),
+            Segment::Index(_) => false,
+        })
+}
+
+/// Path to a value, linked through the stack of the wrappers containing it.
+#[derive(Clone, Copy)]
+enum Path<'a> {
+    Root,
+    Index(&'a Path<'a>, usize),
+    Key(&'a Path<'a>, &'a Key),
+    /// Inside a newtype struct, which doesn't show in the path.
+    #[cfg(feature = "alloc")]
+    Newtype(&'a Path<'a>, &'static str),
+}
+
+impl Path<'_> {
+    fn is_root(&self) -> bool {
+        match self {
+            Path::Root => true,
+            #[cfg(feature = "alloc")]
+            Path::Newtype(parent, _) => parent.is_root(),
+            _ => false,
+        }
+    }
+
+    /// Whether a struct field or newtype on the path is selected by `selection`.
+    #[cfg(feature = "alloc")]
+    fn selected_by(&self, selection: &Selection) -> bool {
+        match self {
+            Path::Root => false,
+            Path::Index(parent, _) => parent.selected_by(selection),
+            Path::Key(parent, key) => {
+                selection.is_field(key.as_str()) || parent.selected_by(selection)
+            }
+            Path::Newtype(parent, name) => {
+                selection.is_newtype(name) || parent.selected_by(selection)
+            }
+        }
+    }
+}
+
+/// Formats the path as a JSON Pointer.
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Path::Root => Ok(()),
+            Path::Index(parent, index) => write!(f, "{}/{}", parent, index),
+            Path::Key(parent, key) => {
+                write!(f, "{}/", parent)?;
+                for c in key.as_str().chars() {
+                    match c {
+                        '~' => f.write_str("~0")?,
+                        '/' => f.write_str("~1")?,
+                        c => f.write_char(c)?,
+                    }
+                }
+                Ok(())
+            }
+            #[cfg(feature = "alloc")]
+            Path::Newtype(parent, _) => parent.fmt(f),
+        }
+    }
+}
+
+/// A map key or variant name, as far as it is a string or an integer.
+struct Key {
+    #[cfg(feature = "alloc")]
+    name: String,
+    /// Longer keys are cut short without an allocator.
+    #[cfg(not(feature = "alloc"))]
+    name: ([u8; KEY_BUFFER_LEN], usize),
+}
+
+#[cfg(not(feature = "alloc"))]
+const KEY_BUFFER_LEN: usize = 32;
+
+impl Key {
+    fn new() -> Self {
+        Key {
+            #[cfg(feature = "alloc")]
+            name: String::new(),
+            #[cfg(not(feature = "alloc"))]
+            name: ([0; KEY_BUFFER_LEN], 0),
+        }
+    }
+
+    fn set(&mut self, name: impl fmt::Display) {
+        self.clear();
+        // Writing to a `Key` can't fail.
+        let _ = write!(self, "{}", name);
+    }
+
+    #[cfg(feature = "alloc")]
+    fn clear(&mut self) {
+        self.name.clear();
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn clear(&mut self) {
+        self.name.1 = 0;
+    }
+
+    #[cfg(feature = "alloc")]
+    fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn as_str(&self) -> &str {
+        // Only whole characters are written.
+        core::str::from_utf8(&self.name.0[..self.name.1]).unwrap()
+    }
+}
+
+impl Write for Key {
+    #[cfg(feature = "alloc")]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.name.push_str(s);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let (buf, len) = &mut self.name;
+        for c in s.chars() {
+            let end = *len + c.len_utf8();
+            if end > buf.len() {
+                break;
+            }
+            c.encode_utf8(&mut buf[*len..end]);
+            *len = end;
+        }
+        Ok(())
+    }
+}
+
+/// [`ByteFmtDeserializer`] below the top level, where it knows its path.
+struct Deserializer<'a, D> {
+    inner: D,
+    pos: Position<'a>,
+}
+
+impl<'a, D> Deserializer<'a, D> {
+    fn new(inner: D, ctx: &'a Context) -> Self {
+        Deserializer {
+            inner,
+            pos: Position::new(ctx, Path::Root),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, D: de::Deserializer<'de>> Deserializer<'_, D> {
+    /// Whether sequences may be encoded bytes. Formats that aren't human readable keep them
+    /// as they are, and so does an encoding that reads bytes from sequences anyway.
+    fn u8_sequences(&self) -> bool {
+        let ctx = self.pos.ctx;
+        ctx.u8_sequences && !matches!(ctx.fmt, ByteFormat::Array) && self.inner.is_human_readable()
+    }
+}
+
+impl<'de, D> de::Deserializer<'de> for Deserializer<'_, D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_any(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_bool(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_u8(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_u16(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_u32(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_u64(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_u128(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_i8(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_i16(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_i32(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_i64(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_i128(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_f32(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_f64(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_char(Visitor::new(visitor, self.pos))
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.inner.deserialize_str(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, D::Error>
@@ -126,23 +967,95 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_string(Visitor::new(visitor, self.fmt))
+            .deserialize_string(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner
-            .deserialize_bytes(Visitor::new(visitor, self.fmt))
+        // The encoded bytes are a string in human readable formats, but sequences of integers
+        // are still accepted. Some of them (YAML) don't support `deserialize_bytes` at all, so
+        // ask for whatever is there. Plain YAML scalars like `1234` would be read as integers
+        // though, so the `yaml` helpers ask for a string. `!!binary` scalars need
+        // `deserialize_any` so that tags other than `!!binary` are reported.
+        #[cfg(feature = "encrypt")]
+        if self.pos.encryption().is_some() {
+            // Encrypted values are strings whatever the format, and nothing else is accepted.
+            return if self.inner.is_human_readable() {
+                self.inner
+                    .deserialize_str(Visitor::bytes(visitor, self.pos))
+            } else {
+                self.inner
+                    .deserialize_bytes(Visitor::bytes(visitor, self.pos))
+            };
+        }
+        if let ByteFormat::Array = self.pos.ctx.fmt {
+            #[cfg(feature = "checksum")]
+            if self.pos.ctx.appends() {
+                // Collected to check the end.
+                return self
+                    .inner
+                    .deserialize_seq(Visitor::bytes(visitor, self.pos));
+            }
+            self.inner.deserialize_seq(Visitor::new(visitor, self.pos))
+        } else if let ByteFormat::YamlBinary { .. } = self.pos.ctx.fmt {
+            self.inner
+                .deserialize_any(Visitor::bytes(visitor, self.pos))
+        } else if self.inner.is_human_readable() {
+            if self.pos.ctx.plain_scalars && !self.pos.ctx.reads_objects() {
+                return self
+                    .inner
+                    .deserialize_str(Visitor::bytes(visitor, self.pos));
+            }
+            // A string, a sequence of integers, a blob reference or an envelope.
+            self.inner
+                .deserialize_any(Visitor::bytes(visitor, self.pos))
+        } else {
+            self.inner
+                .deserialize_bytes(Visitor::bytes(visitor, self.pos))
+        }
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner
-            .deserialize_byte_buf(Visitor::new(visitor, self.fmt))
+        #[cfg(feature = "encrypt")]
+        if self.pos.encryption().is_some() {
+            return if self.inner.is_human_readable() {
+                self.inner
+                    .deserialize_string(Visitor::bytes(visitor, self.pos))
+            } else {
+                self.inner
+                    .deserialize_byte_buf(Visitor::bytes(visitor, self.pos))
+            };
+        }
+        if let ByteFormat::Array = self.pos.ctx.fmt {
+            #[cfg(feature = "checksum")]
+            if self.pos.ctx.appends() {
+                // Collected to check the end.
+                return self
+                    .inner
+                    .deserialize_seq(Visitor::bytes(visitor, self.pos));
+            }
+            self.inner.deserialize_seq(Visitor::new(visitor, self.pos))
+        } else if let ByteFormat::YamlBinary { .. } = self.pos.ctx.fmt {
+            self.inner
+                .deserialize_any(Visitor::bytes(visitor, self.pos))
+        } else if self.inner.is_human_readable() {
+            if self.pos.ctx.plain_scalars && !self.pos.ctx.reads_objects() {
+                return self
+                    .inner
+                    .deserialize_string(Visitor::bytes(visitor, self.pos));
+            }
+            // A string, a sequence of integers, a blob reference or an envelope.
+            self.inner
+                .deserialize_any(Visitor::bytes(visitor, self.pos))
+        } else {
+            self.inner
+                .deserialize_byte_buf(Visitor::bytes(visitor, self.pos))
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, D::Error>
@@ -150,14 +1063,14 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_option(Visitor::new(visitor, self.fmt))
+            .deserialize_option(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_unit(Visitor::new(visitor, self.fmt))
+        self.inner.deserialize_unit(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_unit_struct<V>(
@@ -169,7 +1082,7 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_unit_struct(name, Visitor::new(visitor, self.fmt))
+            .deserialize_unit_struct(name, Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_newtype_struct<V>(
@@ -180,23 +1093,43 @@ where
     where
         V: de::Visitor<'de>,
     {
+        #[cfg(feature = "alloc")]
+        let path = Path::Newtype(&self.pos.path, name);
+        #[cfg(feature = "alloc")]
+        let pos = Position {
+            ctx: self.pos.ctx,
+            path,
+            key: self.pos.key,
+        };
+        #[cfg(not(feature = "alloc"))]
+        let pos = self.pos;
         self.inner
-            .deserialize_newtype_struct(name, Visitor::new(visitor, self.fmt))
+            .deserialize_newtype_struct(name, Visitor::new(visitor, pos))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_seq(Visitor::new(visitor, self.fmt))
+        #[cfg(feature = "alloc")]
+        if self.u8_sequences() {
+            let visitor = Visitor::bytes(U8Sequence(visitor, self.pos.path), self.pos);
+            return self.inner.deserialize_any(visitor);
+        }
+        self.inner.deserialize_seq(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
+        #[cfg(feature = "alloc")]
+        if self.u8_sequences() {
+            let visitor = Visitor::bytes(U8Sequence(visitor, self.pos.path), self.pos);
+            return self.inner.deserialize_any(visitor);
+        }
         self.inner
-            .deserialize_tuple(len, Visitor::new(visitor, self.fmt))
+            .deserialize_tuple(len, Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -209,14 +1142,14 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_tuple_struct(name, len, Visitor::new(visitor, self.fmt))
+            .deserialize_tuple_struct(name, len, Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.inner.deserialize_map(Visitor::new(visitor, self.fmt))
+        self.inner.deserialize_map(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_struct<V>(
@@ -229,7 +1162,7 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_struct(name, fields, Visitor::new(visitor, self.fmt))
+            .deserialize_struct(name, fields, Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_enum<V>(
@@ -242,7 +1175,7 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_enum(name, variants, Visitor::new(visitor, self.fmt))
+            .deserialize_enum(name, variants, Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
@@ -250,7 +1183,7 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_ignored_any(Visitor::new(visitor, self.fmt))
+            .deserialize_ignored_any(Visitor::new(visitor, self.pos))
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, D::Error>
@@ -258,7 +1191,7 @@ where
         V: de::Visitor<'de>,
     {
         self.inner
-            .deserialize_identifier(Visitor::new(visitor, self.fmt))
+            .deserialize_identifier(Visitor::new(visitor, self.pos))
     }
 
     fn is_human_readable(&self) -> bool {
@@ -266,62 +1199,777 @@ where
     }
 }
 
-struct Visitor<V> {
+struct Visitor<'a, V> {
     delegate: V,
-    fmt: ByteFormat,
+    pos: Position<'a>,
+    /// Set when the visitor was asked for bytes. Formats like TOML and YAML have no
+    /// bytes type and report the encoded value through `visit_str`, so in that case
+    /// strings are decoded as well.
+    decode_str: bool,
 }
 
-impl<V> Visitor<V> {
-    fn new(delegate: V, format: ByteFormat) -> Self {
+impl<'a, V> Visitor<'a, V> {
+    fn new(delegate: V, pos: Position<'a>) -> Self {
+        Visitor {
+            delegate,
+            pos,
+            decode_str: false,
+        }
+    }
+
+    fn bytes(delegate: V, pos: Position<'a>) -> Self {
         Visitor {
             delegate,
-            fmt: format,
+            pos,
+            decode_str: true,
+        }
+    }
+
+    /// Records `v` as the key being deserialized, if there is one.
+    fn capture(&mut self, v: impl fmt::Display) {
+        if let Some(key) = self.pos.key.take() {
+            key.set(v);
+        }
+    }
+
+    /// Keeps the details of `failure` for [`ByteFmtDeserializer::deserialize`] and
+    /// [`ByteFmtDeserializer::deserialize_recovering`].
+    #[cfg(feature = "alloc")]
+    fn record(&self, failure: &Failure, v: &[u8]) {
+        let error = DecodeError::new(
+            self.pos.path.to_string(),
+            self.pos.ctx.fmt.name(),
+            failure.offset(),
+            excerpt(v, failure.offset()),
+            failure
+                .to_error::<de::value::Error>(&Path::Root)
+                .to_string(),
+        );
+        let mut errors = self.pos.ctx.errors.borrow_mut();
+        if !self.pos.ctx.recover {
+            errors.clear();
         }
+        errors.push(error);
     }
 
-    fn decode<E>(&self, v: &[u8]) -> Result<Vec<u8>, E>
+    /// Decodes `v` and hands the bytes to the delegate.
+    #[cfg(feature = "alloc")]
+    fn visit_decoded<'de, E>(self, v: &[u8]) -> Result<V::Value, E>
     where
+        V: de::Visitor<'de>,
         E: de::Error,
     {
-        match self.fmt {
-            ByteFormat::Base64(ref alphabet, config) => {
-                match base64::engine::GeneralPurpose::new(alphabet, config).decode(v) {
-                    Ok(bytes) => Ok(bytes),
-                    Err(base64::DecodeError::InvalidByte(index, b)) => Err(E::invalid_value(
-                        de::Unexpected::Char(b.into()),
-                        &format!("valid base64 character at index {}", index).as_str(),
-                    )),
-                    Err(base64::DecodeError::InvalidLength(_)) => {
-                        Err(E::invalid_length(v.len(), &"valid base64 length"))
-                    }
-                    Err(base64::DecodeError::InvalidLastSymbol(_, b)) => Err(E::invalid_value(
-                        de::Unexpected::Char(b.into()),
-                        &"valid character ending base64 string",
-                    )),
-                    Err(base64::DecodeError::InvalidPadding) => Err(E::invalid_value(
-                        de::Unexpected::Other("invalid padding"),
-                        &"valid padding",
-                    )),
+        #[cfg(feature = "encrypt")]
+        if let Some(encrypting) = self.pos.encryption() {
+            let opened = encrypting
+                .open(v)
+                .map_err(Failure::Decrypt)
+                .and_then(|opened| self.pos.ctx.verify(opened));
+            return self.visit_result(opened, v);
+        }
+        let decoded = self.pos.ctx.decode(v, self.pos.constant_time());
+        self.visit_result(decoded, v)
+    }
+
+    /// Hands the bytes to the delegate, or fails unless recovering. `v` is the encoded value.
+    #[cfg(feature = "alloc")]
+    fn visit_result<'de, E>(self, result: Result<Vec<u8>, Failure>, v: &[u8]) -> Result<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+        E: de::Error,
+    {
+        let bytes = match result {
+            Ok(bytes) => bytes,
+            Err(failure) => {
+                self.record(&failure, v);
+                if !self.pos.ctx.recover {
+                    return Err(failure.to_error(&self.pos.path));
                 }
+                Vec::new()
+            }
+        };
+        // With `zeroize`, the visitor owns the buffer from here on and has to wipe it.
+        self.delegate.visit_byte_buf(bytes)
+    }
+
+    /// Decodes `v` and hands the bytes to the delegate.
+    #[cfg(not(feature = "alloc"))]
+    fn visit_decoded<'de, E>(self, v: &[u8]) -> Result<V::Value, E>
+    where
+        V: de::Visitor<'de>,
+        E: de::Error,
+    {
+        let mut buf = Wiped([0; crate::DECODE_BUFFER_LEN]);
+        match self.pos.ctx.fmt.decode_to_slice(v, &mut buf[..]) {
+            Ok(len) => self.delegate.visit_bytes(&buf[..len]),
+            Err(failure) => Err(failure.to_error(&self.pos.path)),
+        }
+    }
+}
+
+/// Reads a sequence of `u8` from bytes as well as from a sequence.
+#[cfg(feature = "alloc")]
+struct U8Sequence<'a, V>(V, Path<'a>);
+
+#[cfg(feature = "alloc")]
+impl<'de, V: de::Visitor<'de>> de::Visitor<'de> for U8Sequence<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let path = self.1;
+        let elements = v.iter().map(|&byte| U8Element(byte, path, PhantomData));
+        let mut seq = de::value::SeqDeserializer::new(elements);
+        let value = self.0.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        self.visit_bytes(&Wiped(v))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        self.0.visit_seq(seq)
+    }
+}
+
+/// A decoded byte as an element of a sequence, which can only be read as a `u8`. Other
+/// integers would accept it as well, but the string didn't encode them.
+#[cfg(feature = "alloc")]
+struct U8Element<'a, E>(u8, Path<'a>, PhantomData<E>);
+
+#[cfg(feature = "alloc")]
+impl<'de, E: de::Error> de::IntoDeserializer<'de, E> for U8Element<'_, E> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, E: de::Error> de::Deserializer<'de> for U8Element<'_, E> {
+    type Error = E;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, E> {
+        Err(custom(
+            "encoded bytes can only be read as a sequence of `u8`",
+            &self.1,
+        ))
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, E> {
+        visitor.visit_u8(self.0)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+/// Reads the blob referenced by the map `map`, written in place of the bytes at `path`.
+#[cfg(feature = "blob")]
+fn read_blob<'de, A>(mut map: A, store: &dyn BlobStore, path: &Path) -> Result<Vec<u8>, A::Error>
+where
+    A: de::MapAccess<'de>,
+{
+    let expected = InPath(&"encoded bytes or a blob reference", path);
+    let key = match map.next_key::<String>()? {
+        Some(key) if key == blob::REFERENCE_KEY => map.next_value::<String>()?,
+        _ => return Err(de::Error::invalid_type(de::Unexpected::Map, &expected)),
+    };
+    if map.next_key::<de::IgnoredAny>()?.is_some() {
+        return Err(de::Error::invalid_type(de::Unexpected::Map, &expected));
+    }
+    blob::get(store, &key).map_err(|failure| custom(failure, path))
+}
+
+/// Reads the envelope `map`, written in place of the bytes at `path`. Returns the format it
+/// names, the length it declares and the encoded bytes.
+#[cfg(feature = "alloc")]
+fn read_envelope<'de, A>(
+    mut map: A,
+    envelope: &Envelope,
+    path: &Path,
+) -> Result<(ByteFormat, usize, Wiped<String>), A::Error>
+where
+    A: de::MapAccess<'de>,
+{
+    let (mut fmt, mut length, mut data) = (None, None, None);
+    while let Some(key) = map.next_key::<String>()? {
+        if key == envelope.encoding_key {
+            let name = map.next_value::<String>()?;
+            let decoder = envelope::decoder(&name).ok_or_else(|| {
+                de::Error::invalid_value(
+                    de::Unexpected::Str(&name),
+                    &InPath(&envelope::ENCODINGS, path),
+                )
+            })?;
+            fmt = Some(decoder);
+        } else if key == envelope.length_key {
+            length = Some(map.next_value::<usize>()?);
+        } else if key == envelope.data_key {
+            data = Some(Wiped(map.next_value::<String>()?));
+        } else {
+            map.next_value::<de::IgnoredAny>()?;
+        }
+    }
+    let missing = |key: &str| custom(format_args!("envelope without `{}`", key), path);
+    Ok((
+        fmt.ok_or_else(|| missing(&envelope.encoding_key))?,
+        length.ok_or_else(|| missing(&envelope.length_key))?,
+        data.ok_or_else(|| missing(&envelope.data_key))?,
+    ))
+}
+
+/// Bytes of the encoded value shown around an error.
+#[cfg(feature = "alloc")]
+const EXCERPT_LEN: usize = 16;
+
+/// Up to [`EXCERPT_LEN`] bytes of `v` centered on `offset`.
+#[cfg(feature = "alloc")]
+fn excerpt(v: &[u8], offset: Option<usize>) -> String {
+    let start = offset.map_or(0, |offset| offset.saturating_sub(EXCERPT_LEN / 2));
+    let end = v.len().min(start + EXCERPT_LEN);
+    String::from_utf8_lossy(&v[start.min(end)..end]).into_owned()
+}
+
+impl ByteFormat {
+    #[cfg(feature = "json")]
+    pub(crate) fn decode<E>(&self, v: &[u8]) -> Result<Vec<u8>, E>
+    where
+        E: de::Error,
+    {
+        self.decode_to_vec(v)
+            .map_err(|failure| failure.to_error(&Path::Root))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn decode_to_vec(&self, v: &[u8]) -> Result<Vec<u8>, Failure> {
+        let mut decoded = Wiped(vec![0; self.decoded_len_estimate(v.len())]);
+        let len = self.decode_to_slice(v, &mut decoded)?;
+        decoded.truncate(len);
+        Ok(decoded.into_inner())
+    }
+
+    /// Name of the encoding in errors.
+    #[cfg(feature = "alloc")]
+    fn name(&self) -> &'static str {
+        match self {
+            ByteFormat::Base64(..) => "base64",
+            ByteFormat::Hex => "hex",
+            ByteFormat::YamlBinary { .. } => "!!binary",
+            ByteFormat::Array => "array",
+            ByteFormat::Redacting(_) | ByteFormat::Redacted(_) => "redacted",
+            ByteFormat::Preview(_) => "preview",
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "compress")]
+            ByteFormat::Decompressing(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "compress")]
+            ByteFormat::Compressing(_) => unreachable!("only used for serializing"),
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Sealed(_) => unreachable!("only used for serializing"),
+            #[cfg(feature = "alloc")]
+            ByteFormat::ConstantTime(decoding) => decoding.fmt.name(),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Sequences(fmt) => fmt.name(),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Envelope(_) => unreachable!("resolved when deserializing starts"),
+        }
+    }
+
+    /// Upper bound of the decoded length of `len` encoded bytes.
+    fn decoded_len_estimate(&self, len: usize) -> usize {
+        match self {
+            ByteFormat::Base64(..) | ByteFormat::YamlBinary { .. } => {
+                base64::decoded_len_estimate(len)
+            }
+            ByteFormat::Hex => len / 2,
+            ByteFormat::Array => len,
+            #[cfg(feature = "alloc")]
+            ByteFormat::Redacting(_) | ByteFormat::Redacted(_) | ByteFormat::Preview(_) => {
+                unreachable!("redaction and previews are only used for serializing")
+            }
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "compress")]
+            ByteFormat::Decompressing(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "compress")]
+            ByteFormat::Compressing(_) => unreachable!("only used for serializing"),
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Sealed(_) => unreachable!("only used for serializing"),
+            #[cfg(feature = "alloc")]
+            ByteFormat::ConstantTime(decoding) => decoding.fmt.decoded_len_estimate(len),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Sequences(fmt) => fmt.decoded_len_estimate(len),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Envelope(_) => unreachable!("resolved when deserializing starts"),
+        }
+    }
+
+    /// Decodes `v` into `out`, returning the length of the decoded bytes.
+    fn decode_to_slice(&self, v: &[u8], out: &mut [u8]) -> Result<usize, Failure> {
+        self.decode_encoded(v, out)
+            .map_err(|failure| preview_failure(v, failure))
+    }
+
+    /// Like [`decode_to_vec`](Self::decode_to_vec), without branches or table lookups that
+    /// depend on the characters of `v`.
+    #[cfg(feature = "alloc")]
+    fn decode_constant_time(&self, v: &[u8]) -> Result<Vec<u8>, Failure> {
+        let mut decoded = Wiped(vec![0; self.decoded_len_estimate(v.len())]);
+        let len = self
+            .decode_constant_time_to(v, &mut decoded)
+            .map_err(|failure| preview_failure(v, failure))?;
+        decoded.truncate(len);
+        Ok(decoded.into_inner())
+    }
+
+    /// Like [`decode_encoded`](Self::decode_encoded), in constant time.
+    #[cfg(feature = "alloc")]
+    fn decode_constant_time_to(&self, v: &[u8], out: &mut [u8]) -> Result<usize, Failure> {
+        if self.decoded_len(v) > out.len() {
+            return Err(Failure::TooLong {
+                len: v.len(),
+                max: out.len(),
+            });
+        }
+        // Whole quads are written, so the padding needs room as well.
+        let estimate = self.decoded_len_estimate(v.len());
+        if out.len() < estimate {
+            let decoded = Wiped(self.decode_constant_time(v)?);
+            out[..decoded.len()].copy_from_slice(&decoded);
+            return Ok(decoded.len());
+        }
+        match self {
+            ByteFormat::Base64(codec) => {
+                constant_time::decode_base64(&codec.alphabet, &codec.engine, v, out)
+                    .map_err(|err| base64_failure(err, v.len()))
+            }
+            ByteFormat::Hex => {
+                constant_time::decode_hex(v, out).map_err(|err| hex_failure(err, v.len()))
+            }
+            ByteFormat::YamlBinary { .. } => {
+                constant_time::decode_folded_base64(v, out).map_err(|err| {
+                    let len = v.iter().filter(|b| !b.is_ascii_whitespace()).count();
+                    base64_failure(err, len)
+                })
             }
-            ByteFormat::Hex => match hex::decode(v) {
-                Ok(bytes) => Ok(bytes),
-                Err(hex::FromHexError::OddLength) => {
-                    Err(E::invalid_length(v.len(), &"even length"))
+            // Nothing else is decoded from text.
+            _ => self.decode_encoded(v, out),
+        }
+    }
+
+    /// Decoded length of `v` when it is well formed, leaving out padding and the whitespace
+    /// `!!binary` scalars are folded with.
+    fn decoded_len(&self, v: &[u8]) -> usize {
+        match self {
+            ByteFormat::Base64(..) => {
+                let padding = v.iter().rev().take_while(|&&b| b == b'=').count();
+                (v.len() - padding) * 3 / 4
+            }
+            ByteFormat::YamlBinary { .. } => {
+                let symbols = v.iter().filter(|&&b| b != b'=' && !b.is_ascii_whitespace());
+                symbols.count() * 3 / 4
+            }
+            #[cfg(feature = "alloc")]
+            ByteFormat::ConstantTime(decoding) => decoding.fmt.decoded_len(v),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Sequences(fmt) => fmt.decoded_len(v),
+            _ => self.decoded_len_estimate(v.len()),
+        }
+    }
+
+    fn decode_encoded(&self, v: &[u8], out: &mut [u8]) -> Result<usize, Failure> {
+        if self.decoded_len(v) > out.len() {
+            return Err(Failure::TooLong {
+                len: v.len(),
+                max: out.len(),
+            });
+        }
+        match self {
+            ByteFormat::Base64(codec) => decode_base64(codec, v, out),
+            ByteFormat::Hex => {
+                #[cfg(feature = "simd")]
+                if let Some(len) = simd::decode_hex(v, out) {
+                    return Ok(len);
                 }
-                Err(hex::FromHexError::InvalidHexCharacter { c, index }) => Err(E::invalid_value(
-                    de::Unexpected::Char(c),
-                    &format!("valid hex character at index {}", index).as_str(),
-                )),
-                Err(hex::FromHexError::InvalidStringLength) => Err(E::custom(
-                    "Imposible to reach due to unrestricted return length",
-                )),
+                let decoded = &mut out[..v.len() / 2];
+                hex::decode_to_slice(v, decoded)
+                    .map(|()| decoded.len())
+                    .map_err(|err| hex_failure(err, v.len()))
+            }
+            // Only reached for formats with a bytes type, which are already raw.
+            ByteFormat::Array => {
+                out[..v.len()].copy_from_slice(v);
+                Ok(v.len())
+            }
+            ByteFormat::YamlBinary { .. } => decode_folded_base64(v, out),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Redacting(_) | ByteFormat::Redacted(_) | ByteFormat::Preview(_) => {
+                unreachable!("redaction and previews are only used for serializing")
+            }
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "compress")]
+            ByteFormat::Decompressing(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "compress")]
+            ByteFormat::Compressing(_) => unreachable!("only used for serializing"),
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(_) => unreachable!("resolved when deserializing starts"),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Sealed(_) => unreachable!("only used for serializing"),
+            #[cfg(feature = "alloc")]
+            ByteFormat::ConstantTime(decoding) => decoding.fmt.decode_constant_time_to(v, out),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Sequences(fmt) => fmt.decode_encoded(v, out),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Envelope(_) => unreachable!("resolved when deserializing starts"),
+        }
+    }
+}
+
+/// Why a value failed to decode.
+enum Failure {
+    InvalidCharacter {
+        encoding: &'static str,
+        c: char,
+        index: usize,
+    },
+    InvalidLastSymbol {
+        c: char,
+        /// Only reported in [`DecodeError`].
+        #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+        index: usize,
+    },
+    InvalidLength {
+        len: usize,
+        expected: &'static str,
+    },
+    InvalidPadding,
+    /// Written by [`ByteFmtSerializer::preview`](crate::ByteFmtSerializer::preview).
+    Preview {
+        #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+        index: usize,
+    },
+    #[cfg(feature = "compress")]
+    Decompress {
+        algorithm: &'static str,
+        failure: compress::Failure,
+        limit: usize,
+    },
+    #[cfg(feature = "checksum")]
+    Checksum {
+        algorithm: &'static str,
+    },
+    #[cfg(feature = "encrypt")]
+    Decrypt(encrypt::Failure),
+    #[cfg(feature = "hmac")]
+    Signature,
+    /// Not as long as its envelope says.
+    #[cfg(feature = "alloc")]
+    DeclaredLength {
+        len: usize,
+        declared: usize,
+    },
+    /// Doesn't fit in the decode buffer.
+    TooLong {
+        len: usize,
+        max: usize,
+    },
+    Custom(&'static str),
+}
+
+impl Failure {
+    /// Index of the offending character in the encoded value.
+    #[cfg(feature = "alloc")]
+    fn offset(&self) -> Option<usize> {
+        match self {
+            Failure::InvalidCharacter { index, .. }
+            | Failure::InvalidLastSymbol { index, .. }
+            | Failure::Preview { index } => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Moves the offset by `len`, for a failure in the part of a value after `len` bytes.
+    #[cfg(feature = "compress")]
+    fn shift(self, len: usize) -> Self {
+        match self {
+            Failure::InvalidCharacter { encoding, c, index } => Failure::InvalidCharacter {
+                encoding,
+                c,
+                index: index + len,
             },
+            Failure::InvalidLastSymbol { c, index } => Failure::InvalidLastSymbol {
+                c,
+                index: index + len,
+            },
+            Failure::Preview { index } => Failure::Preview { index: index + len },
+            failure => failure,
+        }
+    }
+
+    fn to_error<E>(&self, path: &Path) -> E
+    where
+        E: de::Error,
+    {
+        match *self {
+            Failure::InvalidCharacter { encoding, c, index } => E::invalid_value(
+                de::Unexpected::Char(c),
+                &InPath(&ValidCharacter(encoding, index), path),
+            ),
+            Failure::InvalidLastSymbol { c, .. } => E::invalid_value(
+                de::Unexpected::Char(c),
+                &InPath(&"valid character ending base64 string", path),
+            ),
+            Failure::InvalidLength { len, expected } => {
+                E::invalid_length(len, &InPath(&expected, path))
+            }
+            Failure::InvalidPadding => E::invalid_value(
+                de::Unexpected::Other("invalid padding"),
+                &InPath(&"valid padding", path),
+            ),
+            Failure::Preview { .. } => E::invalid_value(
+                de::Unexpected::Other("truncated preview"),
+                &InPath(&"complete encoded bytes", path),
+            ),
+            Failure::TooLong { len, max } => E::invalid_length(len, &InPath(&TooLong(max), path)),
+            #[cfg(feature = "alloc")]
+            Failure::DeclaredLength { len, declared } => {
+                E::invalid_length(len, &InPath(&DeclaredLength(declared), path))
+            }
+            #[cfg(feature = "compress")]
+            Failure::Decompress {
+                algorithm,
+                failure: compress::Failure::Invalid,
+                ..
+            } => E::invalid_value(
+                de::Unexpected::Other(&alloc::format!("invalid {} data", algorithm)),
+                &InPath(&"compressed bytes", path),
+            ),
+            #[cfg(feature = "compress")]
+            Failure::Decompress {
+                failure: compress::Failure::TooLong,
+                limit,
+                ..
+            } => custom(
+                format_args!("decompresses to more than {} bytes", limit),
+                path,
+            ),
+            #[cfg(feature = "checksum")]
+            Failure::Checksum { algorithm } => E::invalid_value(
+                de::Unexpected::Other("checksum mismatch"),
+                &InPath(&ValidChecksum(algorithm), path),
+            ),
+            #[cfg(feature = "hmac")]
+            Failure::Signature => E::invalid_value(
+                de::Unexpected::Other("bytes with an invalid signature"),
+                &InPath(&"bytes signed with a known key", path),
+            ),
+            #[cfg(feature = "encrypt")]
+            Failure::Decrypt(failure) => E::invalid_value(
+                de::Unexpected::Other(&failure.to_string()),
+                &InPath(&"bytes encrypted with a known key", path),
+            ),
+            Failure::Custom(msg) => custom(msg, path),
+        }
+    }
+}
+
+/// A failure to decode `v`, unless `v` is a preview. The marker is invalid in every text
+/// encoding, so only failures need checking.
+fn preview_failure(v: &[u8], failure: Failure) -> Failure {
+    match find(v, PREVIEW_MARKER.as_bytes()) {
+        Some(index) => Failure::Preview { index },
+        None => failure,
+    }
+}
+
+/// Index of the first occurrence of `needle` in `v`.
+fn find(v: &[u8], needle: &[u8]) -> Option<usize> {
+    v.windows(needle.len()).position(|window| window == needle)
+}
+
+fn decode_base64(codec: &Base64Codec, v: &[u8], out: &mut [u8]) -> Result<usize, Failure> {
+    match codec.decode_slice(v, out) {
+        Ok(len) => Ok(len),
+        Err(base64::DecodeSliceError::DecodeError(err)) => Err(base64_failure(err, v.len())),
+        Err(base64::DecodeSliceError::OutputSliceTooSmall) => Err(Failure::TooLong {
+            len: v.len(),
+            max: out.len(),
+        }),
+    }
+}
+
+/// Decodes standard Base64 folded over several lines, like in `!!binary` scalars, by
+/// decoding it 4 characters at a time.
+fn decode_folded_base64(v: &[u8], out: &mut [u8]) -> Result<usize, Failure> {
+    let engine = &base64::engine::general_purpose::STANDARD;
+    let symbols = v.iter().copied().filter(|b| !b.is_ascii_whitespace());
+    let len = symbols.clone().count();
+    let mut quad = [0; 4];
+    let mut written = 0;
+    for (i, symbol) in symbols.enumerate() {
+        quad[i % 4] = symbol;
+        if i % 4 < 3 && i + 1 < len {
+            continue;
+        }
+        // Padding is only valid at the very end.
+        if i + 1 < len && quad.contains(&b'=') {
+            return Err(Failure::InvalidPadding);
+        }
+        let start = i - i % 4;
+        written += match engine.decode_slice(&quad[..=i % 4], &mut out[written..]) {
+            Ok(len) => len,
+            Err(base64::DecodeSliceError::DecodeError(err)) => {
+                return Err(base64_failure(offset(err, start), len))
+            }
+            Err(base64::DecodeSliceError::OutputSliceTooSmall) => {
+                return Err(Failure::TooLong {
+                    len,
+                    max: out.len(),
+                })
+            }
+        };
+    }
+    Ok(written)
+}
+
+fn offset(err: base64::DecodeError, start: usize) -> base64::DecodeError {
+    match err {
+        base64::DecodeError::InvalidByte(index, b) => {
+            base64::DecodeError::InvalidByte(start + index, b)
+        }
+        base64::DecodeError::InvalidLastSymbol(index, b) => {
+            base64::DecodeError::InvalidLastSymbol(start + index, b)
+        }
+        err => err,
+    }
+}
+
+fn hex_failure(err: hex::FromHexError, len: usize) -> Failure {
+    match err {
+        hex::FromHexError::OddLength => Failure::InvalidLength {
+            len,
+            expected: "even length",
+        },
+        hex::FromHexError::InvalidHexCharacter { c, index } => Failure::InvalidCharacter {
+            encoding: "hex",
+            c,
+            index,
+        },
+        hex::FromHexError::InvalidStringLength => {
+            Failure::Custom("Imposible to reach due to unrestricted return length")
+        }
+    }
+}
+
+fn base64_failure(err: base64::DecodeError, len: usize) -> Failure {
+    match err {
+        base64::DecodeError::InvalidByte(index, b) => Failure::InvalidCharacter {
+            encoding: "base64",
+            c: b.into(),
+            index,
+        },
+        base64::DecodeError::InvalidLength(_) => Failure::InvalidLength {
+            len,
+            expected: "valid base64 length",
+        },
+        base64::DecodeError::InvalidLastSymbol(index, b) => {
+            Failure::InvalidLastSymbol { c: b.into(), index }
+        }
+        base64::DecodeError::InvalidPadding => Failure::InvalidPadding,
+    }
+}
+
+/// Expected a valid character of an encoding at an index.
+struct ValidCharacter(&'static str, usize);
+
+impl de::Expected for ValidCharacter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "valid {} character at index {}", self.0, self.1)
+    }
+}
+
+/// Expected a value that fits in a decode buffer.
+struct TooLong(usize);
+
+impl de::Expected for TooLong {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at most {} bytes once decoded", self.0)
+    }
+}
+
+#[cfg(feature = "alloc")]
+struct DeclaredLength(usize);
+
+#[cfg(feature = "alloc")]
+impl de::Expected for DeclaredLength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes as declared by the envelope", self.0)
+    }
+}
+
+/// An error with `msg`, naming `path` unless it is the top level.
+fn custom<E: de::Error>(msg: impl fmt::Display, path: &Path) -> E {
+    if path.is_root() {
+        E::custom(msg)
+    } else {
+        E::custom(format_args!("{} in `{}`", msg, path))
+    }
+}
+
+#[cfg(feature = "checksum")]
+struct ValidChecksum(&'static str);
+
+#[cfg(feature = "checksum")]
+impl de::Expected for ValidChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bytes with a valid {} checksum", self.0)
+    }
+}
+
+/// Names the path of the value after what was expected, unless it is the top level.
+struct InPath<'a>(&'a dyn de::Expected, &'a Path<'a>);
+
+impl de::Expected for InPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)?;
+        if self.1.is_root() {
+            return Ok(());
         }
+        write!(f, " in `{}`", self.1)
     }
 }
 
-impl<'de, V> de::Visitor<'de> for Visitor<V>
+impl<'de, V> de::Visitor<'de> for Visitor<'_, V>
 where
     V: de::Visitor<'de>,
 {
@@ -338,31 +1986,35 @@ where
         self.delegate.visit_bool(v)
     }
 
-    fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
+    fn visit_i8<E>(mut self, v: i8) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_i8(v)
     }
 
-    fn visit_i16<E>(self, v: i16) -> Result<Self::Value, E>
+    fn visit_i16<E>(mut self, v: i16) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_i16(v)
     }
 
-    fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
+    fn visit_i32<E>(mut self, v: i32) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_i32(v)
     }
 
-    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    fn visit_i64<E>(mut self, v: i64) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_i64(v)
     }
 
@@ -373,31 +2025,35 @@ where
         self.delegate.visit_i128(v)
     }
 
-    fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
+    fn visit_u8<E>(mut self, v: u8) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_u8(v)
     }
 
-    fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E>
+    fn visit_u16<E>(mut self, v: u16) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_u16(v)
     }
 
-    fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E>
+    fn visit_u32<E>(mut self, v: u32) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_u32(v)
     }
 
-    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    fn visit_u64<E>(mut self, v: u64) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
         self.delegate.visit_u64(v)
     }
 
@@ -429,24 +2085,37 @@ where
         self.delegate.visit_char(v)
     }
 
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    fn visit_str<E>(mut self, v: &str) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.capture(v);
+        if self.decode_str {
+            return self.visit_decoded(v.as_bytes());
+        }
         self.delegate.visit_str(v)
     }
 
-    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    fn visit_borrowed_str<E>(mut self, v: &'de str) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        if self.decode_str {
+            return self.visit_str(v);
+        }
+        self.capture(v);
         self.delegate.visit_borrowed_str(v)
     }
 
-    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    #[cfg(feature = "alloc")]
+    fn visit_string<E>(mut self, v: String) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        if self.decode_str {
+            return self.visit_str(&Wiped(v));
+        }
+        self.capture(&v);
         self.delegate.visit_string(v)
     }
 
@@ -468,9 +2137,9 @@ where
     where
         D: de::Deserializer<'de>,
     {
-        self.delegate.visit_some(ByteFmtDeserializer {
+        self.delegate.visit_some(Deserializer {
             inner: deserializer,
-            fmt: self.fmt,
+            pos: self.pos,
         })
     }
 
@@ -478,9 +2147,9 @@ where
     where
         D: de::Deserializer<'de>,
     {
-        self.delegate.visit_newtype_struct(ByteFmtDeserializer {
+        self.delegate.visit_newtype_struct(Deserializer {
             inner: deserializer,
-            fmt: self.fmt,
+            pos: self.pos,
         })
     }
 
@@ -488,29 +2157,74 @@ where
     where
         A: de::SeqAccess<'de>,
     {
-        self.delegate.visit_seq(SeqAccess::new(visitor, self.fmt))
+        #[cfg(feature = "checksum")]
+        if let (true, ByteFormat::Array, true) =
+            (self.decode_str, &self.pos.ctx.fmt, self.pos.ctx.appends())
+        {
+            // The checksum or signature is part of the sequence.
+            let mut visitor = visitor;
+            let mut bytes = Wiped(Vec::with_capacity(visitor.size_hint().unwrap_or(0)));
+            while let Some(b) = visitor.next_element()? {
+                crate::wipe::extend(&mut bytes, &[b]);
+            }
+            let verified = self.pos.ctx.verify(bytes.into_inner());
+            return self.visit_result(verified, &[]);
+        }
+        self.delegate
+            .visit_seq(SeqAccess::new(visitor, self.pos.ctx, self.pos.path))
     }
 
     fn visit_map<A>(self, visitor: A) -> Result<Self::Value, A::Error>
     where
         A: de::MapAccess<'de>,
     {
-        self.delegate.visit_map(MapAccess::new(visitor, self.fmt))
+        #[cfg(feature = "blob")]
+        if let (true, Some(store)) = (self.decode_str, &self.pos.ctx.blobs) {
+            let bytes = read_blob(visitor, &**store, &self.pos.path)?;
+            let verified = self.pos.ctx.verify(bytes);
+            return self.visit_result(verified, &[]);
+        }
+        #[cfg(feature = "alloc")]
+        if let (true, Some(enveloping)) = (self.decode_str, &self.pos.ctx.envelope) {
+            let (fmt, length, data) = read_envelope(visitor, &enveloping.envelope, &self.pos.path)?;
+            let decoded = self.pos.ctx.decode_enveloped(
+                &fmt,
+                data.as_bytes(),
+                length,
+                self.pos.constant_time(),
+            );
+            return self.visit_result(decoded, data.as_bytes());
+        }
+        self.delegate
+            .visit_map(MapAccess::new(visitor, self.pos.ctx, self.pos.path))
     }
 
     fn visit_enum<A>(self, visitor: A) -> Result<Self::Value, A::Error>
     where
         A: de::EnumAccess<'de>,
     {
-        self.delegate.visit_enum(EnumAccess::new(visitor, self.fmt))
+        #[cfg(feature = "alloc")]
+        if let (true, ByteFormat::YamlBinary { .. }) = (self.decode_str, &self.pos.ctx.fmt) {
+            // `serde_yaml` reports local tags (`!tag value`) as enums.
+            let (tag, variant) = visitor.variant::<String>()?;
+            if tag != "binary" {
+                return Err(de::Error::invalid_value(
+                    de::Unexpected::Other(&alloc::format!("tag `!{}`", tag)),
+                    &InPath(&"`!!binary` tagged or untagged base64", &self.pos.path),
+                ));
+            }
+            let encoded: String = de::VariantAccess::newtype_variant(variant)?;
+            return self.visit_str(&encoded);
+        }
+        self.delegate
+            .visit_enum(EnumAccess::new(visitor, self.pos.ctx, self.pos.path))
     }
 
     fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        let decoded = self.decode(v)?;
-        self.delegate.visit_bytes(&decoded)
+        self.visit_decoded(v)
     }
 
     fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
@@ -521,56 +2235,77 @@ where
         self.visit_bytes(v)
     }
 
+    #[cfg(feature = "alloc")]
     fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        let decoded = self.decode(&v)?;
-        self.delegate.visit_byte_buf(decoded)
+        self.visit_decoded(&Wiped(v))
     }
 }
 
-struct EnumAccess<D> {
+struct EnumAccess<'a, D> {
     delegate: D,
-    fmt: ByteFormat,
+    ctx: &'a Context,
+    path: Path<'a>,
 }
 
-impl<D> EnumAccess<D> {
-    fn new(delegate: D, fmt: ByteFormat) -> Self {
-        EnumAccess { delegate, fmt }
+impl<'a, D> EnumAccess<'a, D> {
+    fn new(delegate: D, ctx: &'a Context, path: Path<'a>) -> Self {
+        EnumAccess {
+            delegate,
+            ctx,
+            path,
+        }
     }
 }
 
-impl<'de, D> de::EnumAccess<'de> for EnumAccess<D>
+impl<'a, 'de, D> de::EnumAccess<'de> for EnumAccess<'a, D>
 where
     D: de::EnumAccess<'de>,
 {
     type Error = D::Error;
-    type Variant = VariantAccess<D::Variant>;
+    type Variant = VariantAccess<'a, D::Variant>;
 
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), D::Error>
     where
         V: de::DeserializeSeed<'de>,
     {
-        let fmt = self.fmt;
-        self.delegate
-            .variant_seed(DeserializeSeed::new(seed, fmt.clone()))
-            .map(|(v, vis)| (v, VariantAccess::new(vis, fmt)))
+        let mut name = Key::new();
+        let pos = Position {
+            ctx: self.ctx,
+            path: self.path,
+            key: Some(&mut name),
+        };
+        let (value, variant) = self
+            .delegate
+            .variant_seed(DeserializeSeed::new(seed, pos))?;
+        Ok((
+            value,
+            VariantAccess::new(variant, self.ctx, self.path, name),
+        ))
     }
 }
 
-struct VariantAccess<D> {
+struct VariantAccess<'a, D> {
     delegate: D,
-    fmt: ByteFormat,
+    ctx: &'a Context,
+    path: Path<'a>,
+    name: Key,
 }
 
-impl<D> VariantAccess<D> {
-    fn new(delegate: D, fmt: ByteFormat) -> Self {
-        VariantAccess { delegate, fmt }
+impl<'a, D> VariantAccess<'a, D> {
+    fn new(delegate: D, ctx: &'a Context, path: Path<'a>, name: Key) -> Self {
+        VariantAccess {
+            delegate,
+            ctx,
+            path,
+            name,
+        }
     }
 }
 
-impl<'de, D> de::VariantAccess<'de> for VariantAccess<D>
+impl<'de, D> de::VariantAccess<'de> for VariantAccess<'_, D>
 where
     D: de::VariantAccess<'de>,
 {
@@ -584,16 +2319,18 @@ where
     where
         T: de::DeserializeSeed<'de>,
     {
+        let path = Path::Key(&self.path, &self.name);
         self.delegate
-            .newtype_variant_seed(DeserializeSeed::new(seed, self.fmt))
+            .newtype_variant_seed(DeserializeSeed::new(seed, Position::new(self.ctx, path)))
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
+        let path = Path::Key(&self.path, &self.name);
         self.delegate
-            .tuple_variant(len, Visitor::new(visitor, self.fmt))
+            .tuple_variant(len, Visitor::new(visitor, Position::new(self.ctx, path)))
     }
 
     fn struct_variant<V>(
@@ -604,23 +2341,24 @@ where
     where
         V: de::Visitor<'de>,
     {
+        let path = Path::Key(&self.path, &self.name);
         self.delegate
-            .struct_variant(fields, Visitor::new(visitor, self.fmt))
+            .struct_variant(fields, Visitor::new(visitor, Position::new(self.ctx, path)))
     }
 }
 
-struct DeserializeSeed<S> {
+struct DeserializeSeed<'a, S> {
     delegate: S,
-    fmt: ByteFormat,
+    pos: Position<'a>,
 }
 
-impl<S> DeserializeSeed<S> {
-    fn new(delegate: S, fmt: ByteFormat) -> Self {
-        DeserializeSeed { delegate, fmt }
+impl<'a, S> DeserializeSeed<'a, S> {
+    fn new(delegate: S, pos: Position<'a>) -> Self {
+        DeserializeSeed { delegate, pos }
     }
 }
 
-impl<'de, S> de::DeserializeSeed<'de> for DeserializeSeed<S>
+impl<'de, S> de::DeserializeSeed<'de> for DeserializeSeed<'_, S>
 where
     S: de::DeserializeSeed<'de>,
 {
@@ -630,25 +2368,32 @@ where
     where
         D: de::Deserializer<'de>,
     {
-        self.delegate.deserialize(ByteFmtDeserializer {
+        self.delegate.deserialize(Deserializer {
             inner: deserializer,
-            fmt: self.fmt,
+            pos: self.pos,
         })
     }
 }
 
-struct SeqAccess<D> {
+struct SeqAccess<'a, D> {
     delegate: D,
-    fmt: ByteFormat,
+    ctx: &'a Context,
+    path: Path<'a>,
+    index: usize,
 }
 
-impl<D> SeqAccess<D> {
-    fn new(delegate: D, fmt: ByteFormat) -> Self {
-        SeqAccess { delegate, fmt }
+impl<'a, D> SeqAccess<'a, D> {
+    fn new(delegate: D, ctx: &'a Context, path: Path<'a>) -> Self {
+        SeqAccess {
+            delegate,
+            ctx,
+            path,
+            index: 0,
+        }
     }
 }
 
-impl<'de, D> de::SeqAccess<'de> for SeqAccess<D>
+impl<'de, D> de::SeqAccess<'de> for SeqAccess<'_, D>
 where
     D: de::SeqAccess<'de>,
 {
@@ -658,8 +2403,10 @@ where
     where
         T: de::DeserializeSeed<'de>,
     {
+        let path = Path::Index(&self.path, self.index);
+        self.index += 1;
         self.delegate
-            .next_element_seed(DeserializeSeed::new(seed, self.fmt.clone()))
+            .next_element_seed(DeserializeSeed::new(seed, Position::new(self.ctx, path)))
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -667,18 +2414,26 @@ where
     }
 }
 
-struct MapAccess<D> {
+struct MapAccess<'a, D> {
     delegate: D,
-    fmt: ByteFormat,
+    ctx: &'a Context,
+    path: Path<'a>,
+    /// The last key, reused for every entry.
+    key: Key,
 }
 
-impl<D> MapAccess<D> {
-    fn new(delegate: D, fmt: ByteFormat) -> Self {
-        MapAccess { delegate, fmt }
+impl<'a, D> MapAccess<'a, D> {
+    fn new(delegate: D, ctx: &'a Context, path: Path<'a>) -> Self {
+        MapAccess {
+            delegate,
+            ctx,
+            path,
+            key: Key::new(),
+        }
     }
 }
 
-impl<'de, D> de::MapAccess<'de> for MapAccess<D>
+impl<'de, D> de::MapAccess<'de> for MapAccess<'_, D>
 where
     D: de::MapAccess<'de>,
 {
@@ -688,16 +2443,23 @@ where
     where
         K: de::DeserializeSeed<'de>,
     {
-        self.delegate
-            .next_key_seed(DeserializeSeed::new(seed, self.fmt.clone()))
+        // Keys that aren't strings or integers show up empty in paths.
+        self.key.clear();
+        let pos = Position {
+            ctx: self.ctx,
+            path: self.path,
+            key: Some(&mut self.key),
+        };
+        self.delegate.next_key_seed(DeserializeSeed::new(seed, pos))
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, D::Error>
     where
         V: de::DeserializeSeed<'de>,
     {
+        let path = Path::Key(&self.path, &self.key);
         self.delegate
-            .next_value_seed(DeserializeSeed::new(seed, self.fmt.clone()))
+            .next_value_seed(DeserializeSeed::new(seed, Position::new(self.ctx, path)))
     }
 
     fn size_hint(&self) -> Option<usize> {
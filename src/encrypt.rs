@@ -0,0 +1,310 @@
+//! Encrypting bytes with a key, for keeping secrets in documents that are checked in.
+//!
+//! Selected values are sealed with an AEAD cipher and written as the ID of the key followed by
+//! the standard Base64 of the nonce, the ciphertext and the authentication tag, like
+//! `2024-01:sAoW9…`. A deserializer set up with the same keys opens them, and fails on values
+//! that are selected but not sealed, sealed with an unknown key or changed in any way.
+//!
+//! ```rust
+//! # #[cfg(feature = "aes-gcm")] {
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{
+//!     encrypt::{Encryption, Key},
+//!     ByteFmtDeserializer, ByteFmtSerializer,
+//! };
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "serde_bytes")]
+//!     id: Vec<u8>,
+//!     #[serde(with = "serde_bytes")]
+//!     password: Vec<u8>,
+//! }
+//!
+//! let encryption = Encryption::new(Key::aes256_gcm("2024-01", &[7; 32])).fields(["password"]);
+//! let config = Config { id: vec![1, 2], password: b"hunter2".to_vec() };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! config
+//!     .serialize(ByteFmtSerializer::hex(&mut ser).encrypt(encryption.clone()))
+//!     .unwrap();
+//! let json = String::from_utf8(out).unwrap();
+//! assert!(json.starts_with(r#"{"id":"0102","password":"2024-01:"#));
+//!
+//! let mut de = serde_json::Deserializer::from_str(&json);
+//! let de = ByteFmtDeserializer::new_hex(&mut de).decrypt(encryption);
+//! assert_eq!(b"hunter2", &Config::deserialize(de).unwrap().password[..]);
+//! # }
+//! ```
+
+// The `encrypt` feature alone enables none of the ciphers.
+#![cfg_attr(
+    not(any(feature = "aes-gcm", feature = "chacha20poly1305")),
+    allow(dead_code, unreachable_code, unused_variables)
+)]
+
+use crate::{selection::Selection, ByteFormat};
+#[cfg(feature = "aes-gcm")]
+use alloc::boxed::Box;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use base64::Engine;
+use core::fmt;
+
+/// A key for [`Encryption`], along with its cipher and the ID written in front of the values
+/// it seals.
+#[derive(Clone)]
+pub struct Key {
+    id: String,
+    cipher: Cipher,
+}
+
+#[derive(Clone)]
+enum Cipher {
+    #[cfg(feature = "aes-gcm")]
+    // Boxed for the expanded key schedule.
+    Aes256Gcm(Box<aes_gcm::Aes256Gcm>),
+    #[cfg(feature = "chacha20poly1305")]
+    ChaCha20Poly1305(chacha20poly1305::ChaCha20Poly1305),
+}
+
+/// Bytes of the nonce in front of the ciphertext, the same for both ciphers.
+const NONCE_LEN: usize = 12;
+
+/// Bytes of the authentication tag after the ciphertext.
+const TAG_LEN: usize = 16;
+
+impl Key {
+    fn new(id: &str, cipher: Cipher) -> Self {
+        assert!(
+            !id.is_empty() && !id.contains(':'),
+            "key IDs must be non-empty and can't contain `:`"
+        );
+        Key {
+            id: id.to_string(),
+            cipher,
+        }
+    }
+
+    /// AES-256 in Galois/Counter Mode.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is empty or contains a `:`.
+    #[cfg(feature = "aes-gcm")]
+    pub fn aes256_gcm(id: &str, key: &[u8; 32]) -> Self {
+        use aes_gcm::KeyInit;
+
+        Key::new(
+            id,
+            Cipher::Aes256Gcm(Box::new(aes_gcm::Aes256Gcm::new(key.into()))),
+        )
+    }
+
+    /// ChaCha20-Poly1305.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is empty or contains a `:`.
+    #[cfg(feature = "chacha20poly1305")]
+    pub fn chacha20_poly1305(id: &str, key: &[u8; 32]) -> Self {
+        use chacha20poly1305::KeyInit;
+
+        Key::new(
+            id,
+            Cipher::ChaCha20Poly1305(chacha20poly1305::ChaCha20Poly1305::new(key.into())),
+        )
+    }
+
+    /// The ID of the key is authenticated along with the bytes.
+    fn seal(&self, v: &[u8]) -> Result<Vec<u8>, Failure> {
+        let aad = self.id.as_bytes();
+        match self.cipher {
+            #[cfg(feature = "aes-gcm")]
+            Cipher::Aes256Gcm(ref cipher) => {
+                use aes_gcm::aead::{Aead, AeadCore, OsRng, Payload};
+
+                let nonce = aes_gcm::Aes256Gcm::generate_nonce(&mut OsRng);
+                let sealed = cipher
+                    .encrypt(&nonce, Payload { msg: v, aad })
+                    .map_err(|_| Failure::Seal)?;
+                Ok([&nonce[..], &sealed].concat())
+            }
+            #[cfg(feature = "chacha20poly1305")]
+            Cipher::ChaCha20Poly1305(ref cipher) => {
+                use chacha20poly1305::aead::{Aead, AeadCore, OsRng, Payload};
+
+                let nonce = chacha20poly1305::ChaCha20Poly1305::generate_nonce(&mut OsRng);
+                let sealed = cipher
+                    .encrypt(&nonce, Payload { msg: v, aad })
+                    .map_err(|_| Failure::Seal)?;
+                Ok([&nonce[..], &sealed].concat())
+            }
+        }
+    }
+
+    /// Opens the nonce, ciphertext and tag in `v`.
+    fn open(&self, v: &[u8]) -> Result<Vec<u8>, Failure> {
+        let aad = self.id.as_bytes();
+        let (nonce, sealed) = v.split_at(NONCE_LEN);
+        match self.cipher {
+            #[cfg(feature = "aes-gcm")]
+            Cipher::Aes256Gcm(ref cipher) => {
+                use aes_gcm::aead::{Aead, Payload};
+
+                cipher
+                    .decrypt(nonce.into(), Payload { msg: sealed, aad })
+                    .map_err(|_| Failure::Forged)
+            }
+            #[cfg(feature = "chacha20poly1305")]
+            Cipher::ChaCha20Poly1305(ref cipher) => {
+                use chacha20poly1305::aead::{Aead, Payload};
+
+                cipher
+                    .decrypt(nonce.into(), Payload { msg: sealed, aad })
+                    .map_err(|_| Failure::Forged)
+            }
+        }
+    }
+
+    fn algorithm(&self) -> &'static str {
+        match self.cipher {
+            #[cfg(feature = "aes-gcm")]
+            Cipher::Aes256Gcm(_) => "aes256-gcm",
+            #[cfg(feature = "chacha20poly1305")]
+            Cipher::ChaCha20Poly1305(_) => "chacha20-poly1305",
+        }
+    }
+}
+
+/// Only shows the ID and the cipher.
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("id", &self.id)
+            .field("algorithm", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The keys and which bytes are encrypted, for
+/// [`ByteFmtSerializer::encrypt`](crate::ByteFmtSerializer::encrypt) and
+/// [`ByteFmtDeserializer::decrypt`](crate::ByteFmtDeserializer::decrypt).
+///
+/// Bytes below the selected struct fields and newtype structs are encrypted, the others are
+/// written with the serializer's encoding. Without any field or newtype, all bytes are
+/// encrypted.
+#[derive(Clone, Debug)]
+pub struct Encryption {
+    /// The key values are sealed with, followed by those that are only used for opening.
+    keys: Vec<Key>,
+    selection: Selection,
+}
+
+impl Encryption {
+    /// Seals values with `key`, and opens values sealed with it.
+    pub fn new(key: Key) -> Self {
+        Encryption {
+            keys: alloc::vec![key],
+            selection: Selection::default(),
+        }
+    }
+
+    /// Also opens values sealed with `key`, like one that was replaced by the current key.
+    pub fn old_key(mut self, key: Key) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Encrypts the bytes below struct fields with these names.
+    pub fn fields<I>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.selection.add_fields(fields);
+        self
+    }
+
+    /// Encrypts the bytes below newtype structs with these names, like `Password` for
+    /// `struct Password(#[serde(with = "serde_bytes")] Vec<u8>)`.
+    pub fn newtypes<I>(mut self, newtypes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.selection.add_newtypes(newtypes);
+        self
+    }
+}
+
+/// An encoding along with an [`Encryption`].
+#[derive(Clone)]
+pub(crate) struct Encrypting {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) encryption: Encryption,
+}
+
+impl Encrypting {
+    /// The values that are encrypted.
+    pub(crate) fn selection(&self) -> &Selection {
+        &self.encryption.selection
+    }
+
+    /// `v` sealed with the current key, with the ID in front.
+    pub(crate) fn seal(&self, v: &[u8]) -> Result<String, Failure> {
+        let key = &self.encryption.keys[0];
+        let sealed = key.seal(v)?;
+        let mut out = String::with_capacity(
+            key.id.len() + 1 + base64::encoded_len(sealed.len(), true).unwrap_or(0),
+        );
+        out.push_str(&key.id);
+        out.push(':');
+        base64::engine::general_purpose::STANDARD.encode_string(sealed, &mut out);
+        Ok(out)
+    }
+
+    /// Opens a value written by [`seal`](Self::seal) with the key named in front of it.
+    pub(crate) fn open(&self, v: &[u8]) -> Result<Vec<u8>, Failure> {
+        let colon = v
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or(Failure::NotSealed)?;
+        let (id, sealed) = (&v[..colon], &v[colon + 1..]);
+        let sealed = base64::engine::general_purpose::STANDARD
+            .decode(sealed)
+            .map_err(|_| Failure::NotSealed)?;
+        if sealed.len() < NONCE_LEN + TAG_LEN {
+            return Err(Failure::NotSealed);
+        }
+        let key = self
+            .encryption
+            .keys
+            .iter()
+            .find(|key| key.id.as_bytes() == id)
+            .ok_or(Failure::UnknownKey)?;
+        key.open(&sealed)
+    }
+}
+
+/// Why a value couldn't be sealed or opened.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Failure {
+    Seal,
+    NotSealed,
+    UnknownKey,
+    Forged,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Failure::Seal => "bytes too long to encrypt",
+            Failure::NotSealed => "unencrypted bytes",
+            Failure::UnknownKey => "bytes encrypted with an unknown key",
+            Failure::Forged => "bytes failing authentication",
+        })
+    }
+}
@@ -0,0 +1,145 @@
+//! Writing bytes as objects that name their encoding and length, for services that expect
+//! them, like `{"encoding":"base64","length":7,"data":"aGVsbG8gIQ=="}`.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{envelope::Envelope, ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Upload {
+//!     #[serde(with = "serde_bytes")]
+//!     content: Vec<u8>,
+//! }
+//!
+//! let upload = Upload { content: vec![0xca, 0xfe] };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! let ser = ByteFmtSerializer::hex(&mut ser).envelope(Envelope::new().data_key("value"));
+//! upload.serialize(ser).unwrap();
+//! assert_eq!(
+//!     r#"{"content":{"encoding":"hex","length":2,"value":"cafe"}}"#,
+//!     String::from_utf8(out.clone()).unwrap()
+//! );
+//!
+//! // The decoder is picked by the envelope, whatever the adapter's encoding.
+//! let mut de = serde_json::Deserializer::from_slice(&out);
+//! let de = ByteFmtDeserializer::new_base64(
+//!     &mut de,
+//!     base64::alphabet::STANDARD,
+//!     base64::engine::general_purpose::PAD,
+//! );
+//! let de = de.envelope(Envelope::new().data_key("value"));
+//! assert_eq!(vec![0xca, 0xfe], Upload::deserialize(de).unwrap().content);
+//! ```
+use crate::ByteFormat;
+use alloc::string::{String, ToString};
+use base64::{
+    alphabet::{self, Alphabet},
+    engine::{DecodePaddingMode, GeneralPurposeConfig},
+};
+
+/// The keys of the objects bytes are written as, for [`ByteFmtSerializer::envelope`] and
+/// [`ByteFmtDeserializer::envelope`].
+///
+/// The encoding is named `hex`, `base64` or `base64url`, so only those encodings can be
+/// written. Base64 is read with or without padding, and other keys are ignored. Envelopes
+/// are only used in human readable formats, the others keep their bytes.
+///
+/// Values with a checksum or signature, or compressed ones, can't be written in envelopes or
+/// read from them.
+///
+/// [`ByteFmtSerializer::envelope`]: crate::ByteFmtSerializer::envelope
+/// [`ByteFmtDeserializer::envelope`]: crate::ByteFmtDeserializer::envelope
+#[derive(Clone, Debug)]
+pub struct Envelope {
+    pub(crate) encoding_key: String,
+    pub(crate) length_key: String,
+    pub(crate) data_key: String,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Envelope::new()
+    }
+}
+
+impl Envelope {
+    /// Envelopes with the keys `encoding`, `length` and `data`.
+    pub fn new() -> Self {
+        Envelope {
+            encoding_key: "encoding".to_string(),
+            length_key: "length".to_string(),
+            data_key: "data".to_string(),
+        }
+    }
+
+    /// Names the encoding under `key`.
+    pub fn encoding_key(mut self, key: impl Into<String>) -> Self {
+        self.encoding_key = key.into();
+        self
+    }
+
+    /// Writes the number of bytes under `key`. Deserializing fails if it doesn't match.
+    pub fn length_key(mut self, key: impl Into<String>) -> Self {
+        self.length_key = key.into();
+        self
+    }
+
+    /// Writes the encoded bytes under `key`.
+    pub fn data_key(mut self, key: impl Into<String>) -> Self {
+        self.data_key = key.into();
+        self
+    }
+}
+
+/// A format along with the [`Envelope`] its values are written in.
+#[derive(Clone)]
+pub(crate) struct Enveloping {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) envelope: Envelope,
+}
+
+impl Enveloping {
+    /// Name of the encoding in envelopes, if it has one.
+    pub(crate) fn encoding(&self) -> Option<&'static str> {
+        match &self.fmt {
+            ByteFormat::Hex => Some("hex"),
+            ByteFormat::Base64(codec) => base64_name(&codec.alphabet),
+            _ => None,
+        }
+    }
+}
+
+/// What envelopes can hold, in errors.
+pub(crate) const ENCODINGS: &str = "hex, base64 or base64url";
+
+/// The length in an envelope is that of the bytes, so nothing can be appended to them.
+#[cfg(feature = "checksum")]
+pub(crate) const APPENDED: &str = "bytes in an envelope can't have a checksum or signature";
+
+/// An envelope names the encoding of the bytes, which compressed values don't have.
+#[cfg(feature = "compress")]
+pub(crate) const COMPRESSED: &str = "bytes in an envelope can't be compressed";
+
+/// The format for the encoding `name` in an envelope.
+pub(crate) fn decoder(name: &str) -> Option<ByteFormat> {
+    let alphabet = match name {
+        "hex" => return Some(ByteFormat::Hex),
+        "base64" => alphabet::STANDARD,
+        "base64url" => alphabet::URL_SAFE,
+        _ => return None,
+    };
+    let config =
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    Some(ByteFormat::base64(&alphabet, config))
+}
+
+fn base64_name(alphabet: &Alphabet) -> Option<&'static str> {
+    if alphabet.as_str() == alphabet::STANDARD.as_str() {
+        Some("base64")
+    } else if alphabet.as_str() == alphabet::URL_SAFE.as_str() {
+        Some("base64url")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,159 @@
+//! Details of values that failed to decode.
+//!
+//! Errors are returned as the wrapped deserializer's error type, whose message names the path
+//! of the value. [`ByteFmtDeserializer::deserialize`](crate::ByteFmtDeserializer::deserialize)
+//! also hands out a [`DecodeError`] with the details.
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use serde_bytes_repr::ByteFmtDeserializer;
+//!
+//! #[derive(Deserialize, Debug)]
+//! struct Demo {
+//!     keys: Vec<serde_bytes::ByteBuf>,
+//! }
+//!
+//! let json = r#"{"keys":["0a0b","0a!b"]}"#;
+//! let mut json_de = serde_json::Deserializer::from_str(json);
+//! let err = ByteFmtDeserializer::new_hex(&mut json_de)
+//!     .deserialize::<Demo>()
+//!     .unwrap_err();
+//! let decode = err.decode_error().unwrap();
+//! assert_eq!("/keys/1", decode.path());
+//! assert_eq!(Some(2), decode.offset());
+//! assert_eq!("0a!b", decode.excerpt());
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// A value that failed to decode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    path: String,
+    encoding: &'static str,
+    offset: Option<usize>,
+    excerpt: String,
+    message: String,
+}
+
+impl DecodeError {
+    pub(crate) fn new(
+        path: String,
+        encoding: &'static str,
+        offset: Option<usize>,
+        excerpt: String,
+        message: String,
+    ) -> Self {
+        DecodeError {
+            path,
+            encoding,
+            offset,
+            excerpt,
+            message,
+        }
+    }
+
+    /// Path of the value as a JSON Pointer, empty for the top level.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Name of the encoding, like `base64` or `hex`.
+    pub fn encoding(&self) -> &'static str {
+        self.encoding
+    }
+
+    /// Index of the offending character in the encoded value, if the error is about one.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Up to 16 bytes of the encoded value around the offset, or from its start.
+    pub fn excerpt(&self) -> &str {
+        &self.excerpt
+    }
+
+    /// What went wrong, without the path.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// Error of [`ByteFmtDeserializer::deserialize`](crate::ByteFmtDeserializer::deserialize).
+#[derive(Debug)]
+pub struct Error<E> {
+    inner: E,
+    /// The value that failed to decode, or those replaced when recovering.
+    decode: Vec<DecodeError>,
+    recovering: bool,
+}
+
+impl<E> Error<E> {
+    pub(crate) fn new(inner: E, decode: Option<DecodeError>) -> Self {
+        Error {
+            inner,
+            decode: decode.into_iter().collect(),
+            recovering: false,
+        }
+    }
+
+    pub(crate) fn recovering(inner: E, recovered: Vec<DecodeError>) -> Self {
+        Error {
+            inner,
+            decode: recovered,
+            recovering: true,
+        }
+    }
+
+    /// The error of the wrapped deserializer.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Turns this into the error of the wrapped deserializer, dropping the decoding details.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    /// The last value that failed to decode, `None` if the error has another cause.
+    pub fn decode_error(&self) -> Option<&DecodeError> {
+        self.decode.last().filter(|_| !self.recovering)
+    }
+
+    /// The values
+    /// [`deserialize_recovering`](crate::ByteFmtDeserializer::deserialize_recovering) replaced
+    /// with empty bytes before failing.
+    pub fn recovered(&self) -> &[DecodeError] {
+        if self.recovering {
+            &self.decode
+        } else {
+            &[]
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.inner)
+    }
+}
@@ -0,0 +1,230 @@
+//! Helpers for working with [`serde_json::Value`] trees with a chosen byte representation.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::Encoding;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Demo {
+//!     #[serde(with = "serde_bytes")]
+//!     bytes: Vec<u8>,
+//! }
+//!
+//! let demo = Demo { bytes: b"testing".to_vec() };
+//! let mut value = serde_bytes_repr::json::to_value_hex(&demo).unwrap();
+//! assert_eq!(serde_json::json!({ "bytes": "74657374696e67" }), value);
+//!
+//! let base64 = Encoding::base64(
+//!     base64::alphabet::STANDARD,
+//!     base64::engine::GeneralPurposeConfig::new(),
+//! );
+//! serde_bytes_repr::json::reencode(&mut value, &["/bytes"], &Encoding::hex(), &base64).unwrap();
+//! assert_eq!(serde_json::json!({ "bytes": "dGVzdGluZw==" }), value);
+//! ```
+use crate::{
+    path::{DisplayPath, Pointer, Segment},
+    ByteFmtDeserializer, ByteFmtSerializer, ByteFormat, Encoding,
+};
+use base64::{alphabet::Alphabet, engine::GeneralPurposeConfig};
+use serde::{de, de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Error, Value};
+
+/// Converts `value` to a [`Value`] with bytes encoded as Base64 strings.
+pub fn to_value_base64<T>(
+    value: &T,
+    alphabet: Alphabet,
+    config: GeneralPurposeConfig,
+) -> Result<Value, Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_value(value, ByteFormat::base64(&alphabet, config))
+}
+
+/// Converts `value` to a [`Value`] with bytes encoded as HEX strings.
+pub fn to_value_hex<T>(value: &T) -> Result<Value, Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_value(value, ByteFormat::Hex)
+}
+
+/// Interprets a [`Value`] as an instance of `T`, decoding bytes from Base64 strings.
+pub fn from_value_base64<T>(
+    value: Value,
+    alphabet: Alphabet,
+    config: GeneralPurposeConfig,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_value(value, ByteFormat::base64(&alphabet, config))
+}
+
+/// Interprets a [`Value`] as an instance of `T`, decoding bytes from HEX strings.
+pub fn from_value_hex<T>(value: Value) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_value(value, ByteFormat::Hex)
+}
+
+/// Re-encodes the byte fields of an existing [`Value`] from one encoding to another.
+///
+/// The byte fields are given as JSON Pointers extended with globs: `*` and `?` match within a
+/// key or array index, and a `**` segment matches any number of levels, like `/users/*/key`.
+/// Matched `null` values are left alone, any other value that isn't a string in the `from`
+/// encoding is an error. Nothing is changed if an error is returned.
+pub fn reencode(
+    value: &mut Value,
+    pointers: &[&str],
+    from: &Encoding,
+    to: &Encoding,
+) -> Result<(), Error> {
+    let pointers = parse_pointers(pointers)?;
+
+    let mut updates = vec![];
+    visit_byte_fields(value, &mut vec![], &pointers, &mut |path, field| {
+        let decoded = decode_value(&from.0, field).map_err(|err| prefix(path, err))?;
+        updates.push((path.to_vec(), encode_value(&to.0, &decoded)));
+        Ok(())
+    })?;
+    for (path, encoded) in updates {
+        if let Some(target) = value.pointer_mut(&DisplayPath(&path).to_string()) {
+            *target = encoded;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the byte fields of a [`Value`] are in the canonical form of `encoding`, so that
+/// decoding and encoding them again gives back the same value.
+///
+/// Fields are selected like in [`reencode`]. Every field that doesn't decode or isn't
+/// canonical, like uppercase HEX or Base64 with missing padding, is reported.
+pub fn check(value: &Value, pointers: &[&str], encoding: &Encoding) -> Result<(), Vec<Error>> {
+    let pointers = parse_pointers(pointers).map_err(|err| vec![err])?;
+
+    let mut errors = vec![];
+    let _ = visit_byte_fields(value, &mut vec![], &pointers, &mut |path, field| {
+        match decode_value(&encoding.0, field) {
+            Ok(decoded) if encode_value(&encoding.0, &decoded) == *field => {}
+            Ok(_) => errors.push(de::Error::custom(format_args!(
+                "{}: not in canonical form",
+                DisplayPath(path)
+            ))),
+            Err(err) => errors.push(prefix(path, err)),
+        }
+        Ok(())
+    });
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_pointers(pointers: &[&str]) -> Result<Vec<Pointer>, Error> {
+    pointers
+        .iter()
+        .map(|pointer| Pointer::parse(pointer).map_err(de::Error::custom))
+        .collect()
+}
+
+fn prefix(path: &[Segment], err: Error) -> Error {
+    de::Error::custom(format_args!("{}: {}", DisplayPath(path), err))
+}
+
+/// Calls `f` with every non-`null` value matched by `pointers`.
+fn visit_byte_fields<F>(
+    value: &Value,
+    path: &mut Vec<Segment>,
+    pointers: &[Pointer],
+    f: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(&[Segment], &Value) -> Result<(), Error>,
+{
+    if pointers.iter().any(|pointer| pointer.matches(path)) {
+        return match value {
+            Value::Null => Ok(()),
+            value => f(path, value),
+        };
+    }
+
+    match value {
+        Value::Array(values) => {
+            for (i, v) in values.iter().enumerate() {
+                path.push(Segment::Index(i));
+                visit_byte_fields(v, path, pointers, f)?;
+                path.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                path.push(Segment::Key(k.clone()));
+                visit_byte_fields(v, path, pointers, f)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn decode_value(fmt: &ByteFormat, value: &Value) -> Result<Vec<u8>, Error> {
+    match (fmt, value) {
+        (ByteFormat::Array, Value::Array(_)) => Vec::<u8>::deserialize(value),
+        (ByteFormat::Array, other) => Err(de::Error::invalid_type(
+            unexpected(other),
+            &"an array of integers",
+        )),
+        (fmt, Value::String(s)) => fmt.decode(s.as_bytes()),
+        (_, other) => Err(de::Error::invalid_type(
+            unexpected(other),
+            &"an encoded string",
+        )),
+    }
+}
+
+fn encode_value(fmt: &ByteFormat, v: &[u8]) -> Value {
+    match fmt {
+        ByteFormat::Array => Value::Array(v.iter().map(|&b| Value::from(b)).collect()),
+        fmt => Value::String(fmt.encode(v)),
+    }
+}
+
+fn unexpected(value: &Value) -> de::Unexpected<'_> {
+    match value {
+        Value::Null => de::Unexpected::Unit,
+        Value::Bool(b) => de::Unexpected::Bool(*b),
+        Value::Number(_) => de::Unexpected::Other("number"),
+        Value::String(s) => de::Unexpected::Str(s),
+        Value::Array(_) => de::Unexpected::Seq,
+        Value::Object(_) => de::Unexpected::Map,
+    }
+}
+
+fn to_value<T>(value: &T, fmt: ByteFormat) -> Result<Value, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let ser: ByteFmtSerializer<_> = ByteFmtSerializer {
+        inner: serde_json::value::Serializer,
+        encode_kind: fmt,
+        yaml_tag: true,
+    };
+    value.serialize(ser)
+}
+
+fn from_value<T>(value: Value, fmt: ByteFormat) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let de = ByteFmtDeserializer {
+        inner: value,
+        fmt,
+        plain_scalars: false,
+    };
+    T::deserialize(de)
+}
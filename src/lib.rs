@@ -4,6 +4,7 @@
 //!
 //! - Base64
 //! - Hexidecimal
+//! - YAML `!!binary` scalars
 //!
 //! Human readable formats tend not to include a universally agreed way to represent arbitrary binary
 //! data, which means those serde libraries can end up using a representation for serde's "bytes" type
@@ -65,32 +66,782 @@
 //!     assert_eq!("testing", deserialized.as_str());
 //! # }
 //! ```
+//!
+//! ## Converting documents
+//!
+//! [`transcode::transcode`] streams a document from a [`ByteFmtDeserializer`] into a
+//! [`ByteFmtSerializer`] with a different encoding, without knowing its Rust types. The byte
+//! fields are selected by path.
+//!
+//! ## Redacting secrets
+//!
+//! [`ByteFmtSerializer::redact`] replaces all bytes, or those below some struct fields and
+//! newtype structs, with a placeholder described in [`redact`](crate::redact).
+//!
+//! ## Previewing large values
+//!
+//! [`ByteFmtSerializer::preview`] writes only the start and end of long values, optionally with
+//! a digest, as described in [`preview`](crate::preview). The deserializer rejects such
+//! previews.
+//!
+//! ## Blob store
+//!
+//! With the `blob` feature, long values can be kept out of documents in a [`blob::BlobStore`]
+//! and written as references to their SHA-256, see [`blob`].
+//!
+//! ## Envelopes
+//!
+//! [`ByteFmtSerializer::envelope`] writes bytes as maps naming their encoding and length, like
+//! `{"encoding":"base64","length":7,"data":"…"}`, and [`ByteFmtDeserializer::envelope`] decodes
+//! them as they say, see [`envelope`].
+//!
+//! ## Compression
+//!
+//! [`ByteFmtSerializer::compress`] compresses long values before encoding them, and
+//! [`ByteFmtDeserializer::decompress`] reverses it up to a size limit, see
+//! [`compress`](crate::compress).
+//!
+//! ## Checksums
+//!
+//! [`ByteFmtSerializer::checksum`] appends a checksum to the bytes before encoding them, and
+//! [`ByteFmtDeserializer::checksum`] checks and removes it, see [`checksum`](crate::checksum).
+//!
+//! ## Signatures
+//!
+//! [`ByteFmtSerializer::sign`] appends an HMAC-SHA256 to the bytes before encoding them, and
+//! [`ByteFmtDeserializer::verify`] checks and removes it, see [`sign`](crate::sign).
+//!
+//! ## Encryption
+//!
+//! [`ByteFmtSerializer::encrypt`] encrypts the bytes below some fields and newtypes with an
+//! AEAD cipher, and [`ByteFmtDeserializer::decrypt`] opens them, see
+//! [`encrypt`](crate::encrypt).
+//!
+//! ## Constant-time decoding
+//!
+//! [`ByteFmtDeserializer::constant_time`] decodes the bytes below some fields and newtypes,
+//! like private keys, without timing that depends on their characters, see
+//! [`constant_time`](crate::constant_time).
+//!
+//! ## Zeroizing buffers
+//!
+//! With the `zeroize` feature, the buffers the adapters encode and decode values in are wiped
+//! before they are freed, including the ones for checksums, signatures and compression. The
+//! buffers the compression libraries use internally are not. The `secrecy` feature adds
+//! [`secret`](crate::secret), for deserializing into a `secrecy::SecretSlice<u8>` without
+//! copies of the bytes left behind.
+//!
+//! The buffer holding a decoded value is handed to the visitor with `visit_byte_buf` instead,
+//! and wiping it is up to the type. Visitors that only implement `visit_bytes` drop it unwiped.
+//!
+//! ## JSON Schema
+//!
+//! With the `schemars` feature, [`schema`](crate::schema) describes bytes fields as the strings
+//! the serializer writes, with their `contentEncoding`, `pattern` and lengths.
+//!
+//! ## `serde_with`
+//!
+//! With the `serde_with` feature, [`serde_as`](crate::serde_as) has adapters for
+//! `#[serde_as(as = "...")]` annotations, like `Option<Hex>` or `HashMap<Hex, Base64>`.
+//!
+//! ## `bytes`
+//!
+//! With the `bytes` feature, [`bytes`](crate::bytes) serializes `bytes::Bytes` and `BytesMut`
+//! fields, writing long values while they are encoded unless the `zeroize` feature is enabled
+//! anywhere in the build, and deserializes them without copying the decoded value.
+//!
+//! ## Sequences of `u8`
+//!
+//! Fields of types from other crates can't be annotated. [`ByteFmtSerializer::u8_sequences`]
+//! also encodes sequences and tuples whose elements are all `u8`, like `Vec<u8>` and
+//! `[u8; 32]`, and [`ByteFmtDeserializer::u8_sequences`] reads them back.
+//!
+//! ## Cargo features
+//!
+//! - `std` (default): implies `alloc`.
+//! - `alloc`: decoded bytes are handed out as `Vec<u8>` and [`transcode`](crate::transcode) is
+//!   available. Without it the crate works without an allocator: encoded values are written
+//!   piece by piece through a buffer on the stack, and decoding happens in a 256 byte stack
+//!   buffer, so values that decode to more than 256 bytes fail to deserialize.
+//! - `json`: helpers in [`json`](crate::json) for `serde_json::Value` trees.
+//! - `toml`: helpers in [`toml`](crate::toml) for reading and writing TOML documents.
+//! - `yaml`: helpers in [`yaml`](crate::yaml) for reading and writing YAML documents, including
+//!   binary scalars.
+//! - `simd`: standard and URL-safe Base64 goes through `base64-simd` and HEX through
+//!   `faster-hex`, which use SSE4.1, AVX2 or NEON when the CPU has them. Decoding falls back to
+//!   the scalar code for lenient Base64 configurations and for invalid input, so results and
+//!   errors don't change.
+//! - `sha2`: [`Redaction::fingerprint`](crate::redact::Redaction::fingerprint), a salted SHA-256
+//!   of redacted values, [`Preview::sha256`](crate::preview::Preview::sha256) and
+//!   [`Checksum::sha256`](crate::checksum::Checksum::sha256).
+//! - `blake3`: [`Preview::blake3`](crate::preview::Preview::blake3).
+//! - `blob`: [`blob`](crate::blob), a content-addressed store for large values.
+//! - `gzip`, `deflate`, `zstd`: compression algorithms for [`compress`](crate::compress), each
+//!   of which enables the `compress` feature.
+//! - `crc32c`, `xxhash`: checksum algorithms for [`checksum`](crate::checksum), each of which
+//!   enables the `checksum` feature.
+//! - `aes-gcm`, `chacha20poly1305`: ciphers for [`encrypt`](crate::encrypt), each of which
+//!   enables the `encrypt` feature.
+//! - `hmac`: [`sign`](crate::sign), HMAC-SHA256 signatures. Enables `sha2`.
+//! - `zeroize`: wipes the buffers values are encoded and decoded in before freeing them. The
+//!   buffer holding a decoded value is handed to the visitor, which has to wipe it.
+//! - `secrecy`: [`secret`](crate::secret), for `secrecy::SecretSlice<u8>` fields. Enables
+//!   `zeroize`.
+//! - `schemars`: [`schema`](crate::schema), JSON Schemas for bytes fields.
+//! - `serde_with`: [`serde_as`](crate::serde_as), adapters for `serde_with`.
+//! - `bytes`: [`bytes`](crate::bytes), for `bytes::Bytes` and `BytesMut` fields.
+//! - `cli`: the `serde-bytes-repr` binary, which re-encodes byte fields in JSON, YAML and TOML
+//!   files.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-use base64::{alphabet::Alphabet, engine::GeneralPurposeConfig, Engine};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, sync::Arc, vec::Vec};
+use base64::{
+    alphabet::Alphabet,
+    engine::{Config, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use core::fmt;
+use wipe::Wiped;
 
+#[cfg(feature = "blob")]
+pub mod blob;
+#[cfg(feature = "bytes")]
+pub mod bytes;
+#[cfg(feature = "checksum")]
+pub mod checksum;
+#[cfg(feature = "compress")]
+pub mod compress;
+#[cfg(feature = "alloc")]
+pub mod constant_time;
 mod deserializer;
+#[cfg(feature = "encrypt")]
+pub mod encrypt;
+#[cfg(feature = "alloc")]
+pub mod envelope;
+#[cfg(feature = "alloc")]
+pub mod error;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "alloc")]
+mod path;
+#[cfg(feature = "alloc")]
+pub mod preview;
+#[cfg(feature = "alloc")]
+pub mod redact;
+#[cfg(feature = "schemars")]
+pub mod schema;
+#[cfg(feature = "secrecy")]
+pub mod secret;
+#[cfg(feature = "alloc")]
+mod selection;
+#[cfg(feature = "serde_with")]
+pub mod serde_as;
 mod serializer;
+#[cfg(feature = "hmac")]
+pub mod sign;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "alloc")]
+pub mod transcode;
+mod wipe;
+#[cfg(feature = "yaml")]
+pub mod yaml;
 
 #[derive(Clone)]
 enum ByteFormat {
-    Base64(Alphabet, GeneralPurposeConfig),
+    #[cfg(feature = "alloc")]
+    Base64(Arc<Base64Codec>),
+    #[cfg(not(feature = "alloc"))]
+    Base64(Base64Codec),
     Hex,
+    /// Standard Base64 written as a YAML `!!binary` scalar. Only the `yaml` helpers set
+    /// `tagged`, since other serializers can't write tags.
+    YamlBinary {
+        #[cfg_attr(not(feature = "yaml"), allow(dead_code))]
+        tagged: bool,
+    },
+    /// Sequences of integers, which is how most formats write bytes by default. Only useful
+    /// for converting documents from or to that representation.
+    Array,
+    /// Another format, with the bytes below some fields and newtypes redacted.
+    #[cfg(feature = "alloc")]
+    Redacting(Arc<redact::Redacting>),
+    /// A placeholder instead of the bytes, for serializing only.
+    #[cfg(feature = "alloc")]
+    Redacted(redact::Mode),
+    /// Another format, abbreviating long values. For serializing only.
+    #[cfg(feature = "alloc")]
+    Preview(Arc<preview::Previewing>),
+    /// Another format, with long values kept in a blob store.
+    #[cfg(feature = "blob")]
+    Blob(Arc<blob::Externalizing>),
+    /// Another format, with values written in envelopes naming their encoding and length.
+    #[cfg(feature = "alloc")]
+    Envelope(Arc<envelope::Enveloping>),
+    /// Another format, compressing long values. For serializing only.
+    #[cfg(feature = "compress")]
+    Compressing(Arc<compress::Compressing>),
+    /// Another format, decompressing values. For deserializing only.
+    #[cfg(feature = "compress")]
+    Decompressing(Arc<compress::Decompressing>),
+    /// Another format, with a signature appended to the bytes.
+    #[cfg(feature = "hmac")]
+    Signing(Arc<sign::Signing>),
+    /// Another format, with a checksum appended to the bytes.
+    #[cfg(feature = "checksum")]
+    Checksumming(Arc<checksum::Checksumming>),
+    /// Another format, with the bytes below some fields and newtypes encrypted.
+    #[cfg(feature = "encrypt")]
+    Encrypting(Arc<encrypt::Encrypting>),
+    /// Bytes encrypted with the keys of the wrapper, for serializing only.
+    #[cfg(feature = "encrypt")]
+    Sealed(Arc<encrypt::Encrypting>),
+    /// Another format, decoding some values in constant time. For deserializing only.
+    #[cfg(feature = "alloc")]
+    ConstantTime(Arc<constant_time::ConstantTimeDecoding>),
+    /// Another format, also used for sequences and tuples of `u8`. The outermost wrapper when
+    /// serializing.
+    #[cfg(feature = "alloc")]
+    Sequences(Arc<ByteFormat>),
+}
+
+impl ByteFormat {
+    fn base64(alphabet: &Alphabet, config: GeneralPurposeConfig) -> Self {
+        #[cfg(feature = "alloc")]
+        {
+            let engine = GeneralPurpose::new(alphabet, config);
+            ByteFormat::Base64(Arc::new(Base64Codec {
+                #[cfg(feature = "simd")]
+                simd: simd::Base64Simd::new(alphabet, &config),
+                engine,
+                alphabet: alphabet.clone(),
+            }))
+        }
+        #[cfg(not(feature = "alloc"))]
+        ByteFormat::Base64(Base64Codec {
+            alphabet: alphabet.clone(),
+            config,
+        })
+    }
+
+    #[cfg(feature = "alloc")]
+    fn previewing(fmt: ByteFormat, preview: preview::Preview) -> Self {
+        let fmt = match fmt {
+            ByteFormat::Preview(previewing) => previewing.fmt.clone(),
+            fmt => fmt,
+        };
+        ByteFormat::Preview(Arc::new(preview::Previewing { fmt, preview }))
+    }
+
+    /// The encoding below previews, blob stores, envelopes and compression.
+    #[cfg(feature = "compress")]
+    fn encoding(&self) -> &ByteFormat {
+        match self {
+            ByteFormat::Preview(previewing) => previewing.fmt.encoding(),
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(externalizing) => externalizing.fmt.encoding(),
+            ByteFormat::Envelope(enveloping) => enveloping.fmt.encoding(),
+            ByteFormat::Compressing(compressing) => compressing.fmt.encoding(),
+            fmt => fmt,
+        }
+    }
+
+    /// Whether values are written in envelopes by a format below this one.
+    #[cfg(any(feature = "checksum", feature = "compress"))]
+    fn envelops(&self) -> bool {
+        match self {
+            ByteFormat::Envelope(_) => true,
+            ByteFormat::Preview(previewing) => previewing.fmt.envelops(),
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(externalizing) => externalizing.fmt.envelops(),
+            #[cfg(feature = "compress")]
+            ByteFormat::Compressing(compressing) => compressing.fmt.envelops(),
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(signing) => signing.fmt.envelops(),
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(checksumming) => checksumming.fmt.envelops(),
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(encrypting) => encrypting.fmt.envelops(),
+            _ => false,
+        }
+    }
+
+    /// Applies `wrap` to the format inside [`ByteFormat::Sequences`], which stays outermost so
+    /// the serializer finds it.
+    #[cfg(feature = "alloc")]
+    fn within_sequences(self, wrap: impl FnOnce(ByteFormat) -> ByteFormat) -> Self {
+        match self {
+            ByteFormat::Sequences(fmt) => ByteFormat::Sequences(Arc::new(wrap((*fmt).clone()))),
+            fmt => wrap(fmt),
+        }
+    }
+
+    /// Wraps the format with `wrap`. Redaction stays outermost, so that only the unredacted
+    /// bytes reach the wrapper, followed by the signature and the checksum, which cover the
+    /// bytes as they are, and encryption, so that the wrapper never sees selected values.
+    #[cfg(feature = "alloc")]
+    fn wrap(self, wrap: impl FnOnce(ByteFormat) -> ByteFormat) -> Self {
+        match self {
+            ByteFormat::Redacting(mut redacting) => {
+                let redacting_mut = Arc::make_mut(&mut redacting);
+                redacting_mut.fmt = redacting_mut.fmt.clone().wrap(wrap);
+                ByteFormat::Redacting(redacting)
+            }
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(mut signing) => {
+                let signing_mut = Arc::make_mut(&mut signing);
+                signing_mut.fmt = signing_mut.fmt.clone().wrap(wrap);
+                ByteFormat::Signing(signing)
+            }
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(mut checksumming) => {
+                let checksumming_mut = Arc::make_mut(&mut checksumming);
+                checksumming_mut.fmt = checksumming_mut.fmt.clone().wrap(wrap);
+                ByteFormat::Checksumming(checksumming)
+            }
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(mut encrypting) => {
+                let encrypting_mut = Arc::make_mut(&mut encrypting);
+                encrypting_mut.fmt = encrypting_mut.fmt.clone().wrap(wrap);
+                ByteFormat::Encrypting(encrypting)
+            }
+            fmt => wrap(fmt),
+        }
+    }
+
+    #[cfg(feature = "hmac")]
+    fn signing(self, hmac: sign::Hmac) -> Self {
+        match self {
+            ByteFormat::Redacting(mut redacting) => {
+                let redacting_mut = Arc::make_mut(&mut redacting);
+                redacting_mut.fmt = redacting_mut.fmt.clone().signing(hmac);
+                ByteFormat::Redacting(redacting)
+            }
+            ByteFormat::Signing(signing) => signing.fmt.clone().signing(hmac),
+            fmt => ByteFormat::Signing(Arc::new(sign::Signing { fmt, hmac })),
+        }
+    }
+
+    #[cfg(feature = "checksum")]
+    fn checksumming(self, checksum: checksum::Checksum) -> Self {
+        match self {
+            ByteFormat::Redacting(mut redacting) => {
+                let redacting_mut = Arc::make_mut(&mut redacting);
+                redacting_mut.fmt = redacting_mut.fmt.clone().checksumming(checksum);
+                ByteFormat::Redacting(redacting)
+            }
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(mut signing) => {
+                let signing_mut = Arc::make_mut(&mut signing);
+                signing_mut.fmt = signing_mut.fmt.clone().checksumming(checksum);
+                ByteFormat::Signing(signing)
+            }
+            ByteFormat::Checksumming(checksumming) => {
+                checksumming.fmt.clone().checksumming(checksum)
+            }
+            fmt => ByteFormat::Checksumming(Arc::new(checksum::Checksumming { fmt, checksum })),
+        }
+    }
+
+    #[cfg(feature = "encrypt")]
+    fn encrypting(self, encryption: encrypt::Encryption) -> Self {
+        match self {
+            ByteFormat::Redacting(mut redacting) => {
+                let redacting_mut = Arc::make_mut(&mut redacting);
+                redacting_mut.fmt = redacting_mut.fmt.clone().encrypting(encryption);
+                ByteFormat::Redacting(redacting)
+            }
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(mut signing) => {
+                let signing_mut = Arc::make_mut(&mut signing);
+                signing_mut.fmt = signing_mut.fmt.clone().encrypting(encryption);
+                ByteFormat::Signing(signing)
+            }
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(mut checksumming) => {
+                let checksumming_mut = Arc::make_mut(&mut checksumming);
+                checksumming_mut.fmt = checksumming_mut.fmt.clone().encrypting(encryption);
+                ByteFormat::Checksumming(checksumming)
+            }
+            ByteFormat::Encrypting(encrypting) => encrypting.fmt.clone().encrypting(encryption),
+            fmt => ByteFormat::Encrypting(Arc::new(encrypt::Encrypting { fmt, encryption })),
+        }
+    }
+
+    /// The format with the outermost wrapper for which `selected` returns a format replaced
+    /// by it, `None` if there is no such wrapper.
+    #[cfg(feature = "alloc")]
+    fn select(&self, selected: &dyn Fn(&ByteFormat) -> Option<ByteFormat>) -> Option<Self> {
+        if let Some(fmt) = selected(self) {
+            return Some(fmt);
+        }
+        match self {
+            ByteFormat::Redacting(redacting) => {
+                let fmt = redacting.fmt.select(selected)?;
+                let mut redacting = redacting.clone();
+                Arc::make_mut(&mut redacting).fmt = fmt;
+                Some(ByteFormat::Redacting(redacting))
+            }
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(signing) => {
+                let fmt = signing.fmt.select(selected)?;
+                let mut signing = signing.clone();
+                Arc::make_mut(&mut signing).fmt = fmt;
+                Some(ByteFormat::Signing(signing))
+            }
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(checksumming) => {
+                let fmt = checksumming.fmt.select(selected)?;
+                let mut checksumming = checksumming.clone();
+                Arc::make_mut(&mut checksumming).fmt = fmt;
+                Some(ByteFormat::Checksumming(checksumming))
+            }
+            ByteFormat::Sequences(fmt) => {
+                Some(ByteFormat::Sequences(Arc::new(fmt.select(selected)?)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Format of the value of the struct field `key`.
+    #[cfg_attr(not(feature = "alloc"), allow(unused_variables))]
+    fn field(&self, key: &str) -> Self {
+        #[cfg(feature = "alloc")]
+        if let Some(fmt) = self.select(&|fmt| match fmt {
+            ByteFormat::Redacting(redacting) if redacting.selection().is_field(key) => {
+                Some(ByteFormat::Redacted(redacting.mode()))
+            }
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(encrypting) if encrypting.selection().is_field(key) => {
+                Some(ByteFormat::Sealed(encrypting.clone()))
+            }
+            _ => None,
+        }) {
+            return fmt;
+        }
+        self.clone()
+    }
+
+    /// Format of the content of the newtype struct `name`.
+    #[cfg_attr(not(feature = "alloc"), allow(unused_variables))]
+    fn newtype(&self, name: &str) -> Self {
+        #[cfg(feature = "alloc")]
+        if let Some(fmt) = self.select(&|fmt| match fmt {
+            ByteFormat::Redacting(redacting) if redacting.selection().is_newtype(name) => {
+                Some(ByteFormat::Redacted(redacting.mode()))
+            }
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(encrypting) if encrypting.selection().is_newtype(name) => {
+                Some(ByteFormat::Sealed(encrypting.clone()))
+            }
+            _ => None,
+        }) {
+            return fmt;
+        }
+        self.clone()
+    }
+
+    #[cfg(feature = "alloc")]
+    fn encode(&self, v: &[u8]) -> String {
+        let mut encoded = String::with_capacity(self.encoded_len(v.len()).unwrap_or(0));
+        // Writing to a `String` can't fail.
+        self.encode_to(v, &mut encoded).unwrap();
+        encoded
+    }
+
+    /// Encodes `v` piece by piece through a buffer on the stack, so no allocator is needed.
+    fn encode_to<W: fmt::Write>(&self, v: &[u8], out: &mut W) -> fmt::Result {
+        let mut buf = Wiped([0; ENCODE_BUFFER_LEN]);
+        match self {
+            ByteFormat::Base64(codec) => {
+                // Padding is only written after the last chunk since the others are a
+                // multiple of 3 bytes long.
+                for chunk in v.chunks(ENCODE_BUFFER_LEN / 4 * 3) {
+                    let len = codec.encode_slice(chunk, &mut buf[..]);
+                    out.write_str(ascii(&buf[..len]))?;
+                }
+            }
+            ByteFormat::Hex => {
+                for chunk in v.chunks(ENCODE_BUFFER_LEN / 2) {
+                    let encoded = &mut buf[..chunk.len() * 2];
+                    encode_hex(chunk, encoded);
+                    out.write_str(ascii(encoded))?;
+                }
+            }
+            ByteFormat::YamlBinary { .. } => {
+                let engine = &base64::engine::general_purpose::STANDARD;
+                for (i, line) in v.chunks(YAML_BINARY_LINE_LEN / 4 * 3).enumerate() {
+                    if i > 0 {
+                        out.write_char('\n')?;
+                    }
+                    let len = engine.encode_slice(line, &mut buf[..]).unwrap();
+                    out.write_str(ascii(&buf[..len]))?;
+                }
+            }
+            // Only reached for text written in place of the bytes, like the head and tail of
+            // previews.
+            ByteFormat::Array => ByteFormat::Hex.encode_to(v, out)?,
+            // The serializer resolves the options that change the bytes before encoding them.
+            // Anything else holding them still has to apply them, and to redact and encrypt
+            // every value rather than leak any.
+            #[cfg(feature = "alloc")]
+            ByteFormat::Redacting(redacting) => redact::write(&redacting.mode(), v, out)?,
+            #[cfg(feature = "alloc")]
+            ByteFormat::Redacted(mode) => redact::write(mode, v, out)?,
+            #[cfg(feature = "alloc")]
+            ByteFormat::Preview(previewing) => previewing.write(v, out)?,
+            // Only reached for the head and tail of previews.
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(externalizing) => externalizing.fmt.encode_to(v, out)?,
+            #[cfg(feature = "alloc")]
+            ByteFormat::Envelope(enveloping) => enveloping.fmt.encode_to(v, out)?,
+            #[cfg(feature = "compress")]
+            ByteFormat::Compressing(compressing) => compressing.fmt.encode_to(v, out)?,
+            // Options for deserializing, which don't change how bytes are encoded.
+            #[cfg(feature = "compress")]
+            ByteFormat::Decompressing(decompressing) => decompressing.fmt.encode_to(v, out)?,
+            #[cfg(feature = "alloc")]
+            ByteFormat::ConstantTime(decoding) => decoding.fmt.encode_to(v, out)?,
+            #[cfg(feature = "alloc")]
+            ByteFormat::Sequences(fmt) => fmt.encode_to(v, out)?,
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(signing) => {
+                signing.fmt.encode_to(&Wiped(signing.hmac.sign(v)), out)?
+            }
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(checksumming) => {
+                let appended = Wiped(checksumming.checksum.append(v));
+                checksumming.fmt.encode_to(&appended, out)?
+            }
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(encrypting) | ByteFormat::Sealed(encrypting) => {
+                let sealed = Wiped(encrypting.seal(v).map_err(|_| fmt::Error)?);
+                out.write_str(&sealed)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Length of `len` bytes once encoded, `None` if that doesn't fit in a `usize`.
+    fn encoded_len(&self, len: usize) -> Option<usize> {
+        match self {
+            ByteFormat::Base64(codec) => base64::encoded_len(len, codec.padding()),
+            ByteFormat::Hex => len.checked_mul(2),
+            ByteFormat::YamlBinary { .. } => {
+                let lines = len.div_ceil(YAML_BINARY_LINE_LEN / 4 * 3);
+                base64::encoded_len(len, true)?.checked_add(lines.saturating_sub(1))
+            }
+            ByteFormat::Array => ByteFormat::Hex.encoded_len(len),
+            // Not worth computing, redacted values and previews are short.
+            #[cfg(feature = "alloc")]
+            ByteFormat::Redacting(_) | ByteFormat::Redacted(_) | ByteFormat::Preview(_) => None,
+            #[cfg(feature = "blob")]
+            ByteFormat::Blob(externalizing) => externalizing.fmt.encoded_len(len),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Envelope(enveloping) => enveloping.fmt.encoded_len(len),
+            #[cfg(feature = "compress")]
+            ByteFormat::Compressing(compressing) => compressing.fmt.encoded_len(len),
+            #[cfg(feature = "compress")]
+            ByteFormat::Decompressing(decompressing) => decompressing.fmt.encoded_len(len),
+            #[cfg(feature = "alloc")]
+            ByteFormat::ConstantTime(decoding) => decoding.fmt.encoded_len(len),
+            #[cfg(feature = "alloc")]
+            ByteFormat::Sequences(fmt) => fmt.encoded_len(len),
+            // Longer by what is appended or by the ciphertext, which isn't worth computing.
+            #[cfg(feature = "hmac")]
+            ByteFormat::Signing(_) => None,
+            #[cfg(feature = "checksum")]
+            ByteFormat::Checksumming(_) => None,
+            #[cfg(feature = "encrypt")]
+            ByteFormat::Encrypting(_) | ByteFormat::Sealed(_) => None,
+        }
+    }
+
+    /// Encodes `v` into `buf`, or returns `None` if it doesn't fit.
+    fn encode_to_slice<'a>(&self, v: &[u8], buf: &'a mut [u8]) -> Option<&'a str> {
+        let len = self.encoded_len(v.len())?;
+        let buf = buf.get_mut(..len)?;
+        match self {
+            ByteFormat::Base64(codec) => {
+                codec.encode_slice(v, buf);
+            }
+            ByteFormat::Hex => encode_hex(v, buf),
+            _ => {
+                let mut writer = SliceWriter { buf, len: 0 };
+                self.encode_to(v, &mut writer).ok()?;
+                return Some(ascii(&writer.buf[..writer.len]));
+            }
+        }
+        Some(ascii(buf))
+    }
+}
+
+/// A Base64 engine, built once when the adapter is created and shared by its copies. Without
+/// an allocator only the alphabet and configuration are kept, and the engine is built when a
+/// value is encoded or decoded.
+#[derive(Clone)]
+struct Base64Codec {
+    #[cfg(feature = "alloc")]
+    engine: GeneralPurpose,
+    #[cfg(not(feature = "alloc"))]
+    config: GeneralPurposeConfig,
+    /// Also for decoding in constant time, which can't use the engine's tables.
+    alphabet: Alphabet,
+    #[cfg(feature = "simd")]
+    simd: Option<simd::Base64Simd>,
+}
+
+impl Base64Codec {
+    #[cfg(feature = "alloc")]
+    fn with_engine<R>(&self, f: impl FnOnce(&GeneralPurpose) -> R) -> R {
+        f(&self.engine)
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn with_engine<R>(&self, f: impl FnOnce(&GeneralPurpose) -> R) -> R {
+        f(&GeneralPurpose::new(&self.alphabet, self.config))
+    }
+
+    fn padding(&self) -> bool {
+        #[cfg(feature = "alloc")]
+        return self.engine.config().encode_padding();
+        #[cfg(not(feature = "alloc"))]
+        return self.config.encode_padding();
+    }
+
+    /// Encodes `v` into `buf`, which has to fit the encoded value.
+    fn encode_slice(&self, v: &[u8], buf: &mut [u8]) -> usize {
+        #[cfg(feature = "simd")]
+        if let Some(simd) = &self.simd {
+            return simd.encode(v, buf);
+        }
+        self.with_engine(|engine| engine.encode_slice(v, buf).unwrap())
+    }
+
+    fn decode_slice(&self, v: &[u8], out: &mut [u8]) -> Result<usize, base64::DecodeSliceError> {
+        #[cfg(feature = "simd")]
+        if let Some(len) = self.simd.as_ref().and_then(|simd| simd.decode(v, out)) {
+            return Ok(len);
+        }
+        self.with_engine(|engine| engine.decode_slice(v, out))
+    }
+}
+
+/// Encodes `v` as lowercase HEX into `buf`, which has to be exactly twice as long.
+fn encode_hex(v: &[u8], buf: &mut [u8]) {
+    #[cfg(feature = "simd")]
+    return simd::encode_hex(v, buf);
+    #[cfg(not(feature = "simd"))]
+    hex::encode_to_slice(v, buf).unwrap();
+}
+
+/// Writes into a fixed slice, failing once it is full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
 }
 
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Base64 and HEX output is always ASCII.
+fn ascii(encoded: &[u8]) -> &str {
+    core::str::from_utf8(encoded).unwrap()
+}
+
+/// A byte representation chosen at runtime, for [`ByteFmtSerializer::new`],
+/// [`ByteFmtDeserializer::new`] and the helpers that convert already serialized documents.
+#[derive(Clone)]
+pub struct Encoding(ByteFormat);
+
+impl Encoding {
+    /// Base64 with the given alphabet and configuration from the `base64` crate.
+    pub fn base64(alphabet: Alphabet, config: GeneralPurposeConfig) -> Self {
+        Encoding(ByteFormat::base64(&alphabet, config))
+    }
+
+    /// HEX, lowercase when encoding.
+    pub fn hex() -> Self {
+        Encoding(ByteFormat::Hex)
+    }
+
+    /// Standard Base64 folded like YAML `!!binary` scalars, see [`ByteFmtSerializer::yaml_binary`].
+    pub fn yaml_binary() -> Self {
+        Encoding(ByteFormat::YamlBinary { tagged: false })
+    }
+
+    /// Sequences of integers, the representation most formats use for bytes by default. Meant
+    /// for converting documents from or to that representation.
+    pub fn array() -> Self {
+        Encoding(ByteFormat::Array)
+    }
+}
+
+/// Separates the head of a preview from the count of left out bytes. Never part of an encoded
+/// value, so the deserializer can tell previews apart.
+const PREVIEW_MARKER: &str = "…(+";
+
+/// Line length used when folding `!!binary` scalars, same as MIME.
+const YAML_BINARY_LINE_LEN: usize = 76;
+
+/// Size of the stack buffer values are encoded through, a multiple of 4 that fits a folded
+/// `!!binary` line.
+const ENCODE_BUFFER_LEN: usize = 128;
+
+/// Longest value that can be decoded without the `alloc` feature, decoding happens in a buffer
+/// of this size on the stack.
+#[cfg(not(feature = "alloc"))]
+const DECODE_BUFFER_LEN: usize = 256;
+
+/// Default for the `N` parameter of [`ByteFmtSerializer`], fits a 64 byte value in HEX.
+pub const DEFAULT_STACK_BUFFER_LEN: usize = 128;
+
 /// Serializer-adapter which encodes bytes to using the specified encoding. The format is
 /// serialized to the data formats string representation.
-pub struct ByteFmtSerializer<S> {
+///
+/// Values that are at most `N` bytes long once encoded are encoded in a buffer on the stack,
+/// see [`ByteFmtSerializer::stack_buffer`].
+pub struct ByteFmtSerializer<S, const N: usize = DEFAULT_STACK_BUFFER_LEN> {
     inner: S,
     encode_kind: ByteFormat,
+    /// Whether a `!!binary` scalar gets its tag.
+    yaml_tag: bool,
 }
+
 impl<S> ByteFmtSerializer<S> {
+    /// Creates an adapter which serializes to the given [`Encoding`].
+    pub fn new(ser: S, encoding: Encoding) -> Self {
+        Self {
+            inner: ser,
+            encode_kind: encoding.0,
+            yaml_tag: true,
+        }
+    }
+
     /// Crates an adapter which serializes to and from a Base64 representation.
     /// Provide a configuration from the `base64` crate specifying the specifics
     /// on how you want the bytes encoded.
     pub fn base64(ser: S, alphabet: Alphabet, config: GeneralPurposeConfig) -> Self {
         Self {
             inner: ser,
-            encode_kind: ByteFormat::Base64(alphabet, config),
+            encode_kind: ByteFormat::base64(&alphabet, config),
+            yaml_tag: true,
         }
     }
 
@@ -99,26 +850,180 @@ impl<S> ByteFmtSerializer<S> {
         Self {
             inner: ser,
             encode_kind: ByteFormat::Hex,
+            yaml_tag: true,
         }
     }
 
-    fn encode(&self, v: &[u8]) -> String {
-        match self.encode_kind {
-            ByteFormat::Base64(ref alphabet, config) => {
-                base64::engine::GeneralPurpose::new(alphabet, config).encode(v)
-            }
-            ByteFormat::Hex => hex::encode(v),
+    /// Creates an adapter which serializes bytes the way YAML `!!binary` scalars hold them:
+    /// standard Base64 folded into lines of 76 characters.
+    ///
+    /// The strings are untagged, whatever the inner serializer. Use
+    /// [`yaml::to_string_binary`](crate::yaml::to_string_binary) to write tagged scalars.
+    pub fn yaml_binary(ser: S) -> Self {
+        Self {
+            inner: ser,
+            encode_kind: ByteFormat::YamlBinary { tagged: false },
+            yaml_tag: true,
+        }
+    }
+}
+
+impl<S, const N: usize> ByteFmtSerializer<S, N> {
+    /// Changes the size of the stack buffer used for encoding to `M` bytes. Longer values are
+    /// encoded into a `String`, or without the `alloc` feature written piece by piece, which
+    /// is slower for formats that escape strings.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use serde_bytes_repr::ByteFmtSerializer;
+    ///
+    /// let mut out = vec![];
+    /// let mut ser = serde_json::Serializer::new(&mut out);
+    /// let ser = ByteFmtSerializer::hex(&mut ser).stack_buffer::<256>();
+    /// serde_bytes::Bytes::new(&[0xab; 128]).serialize(ser).unwrap();
+    /// ```
+    pub fn stack_buffer<const M: usize>(self) -> ByteFmtSerializer<S, M> {
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind: self.encode_kind,
+            yaml_tag: self.yaml_tag,
+        }
+    }
+
+    /// Writes bytes as configured by `redaction` instead of encoding them, see [`redact`].
+    #[cfg(feature = "alloc")]
+    pub fn redact(self, redaction: redact::Redaction) -> Self {
+        let encode_kind = self.encode_kind.within_sequences(|fmt| {
+            ByteFormat::Redacting(Arc::new(redact::Redacting { fmt, redaction }))
+        });
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind,
+            yaml_tag: self.yaml_tag,
+        }
+    }
+
+    /// Writes values longer than `preview` allows in abbreviated form, see [`preview`].
+    #[cfg(feature = "alloc")]
+    pub fn preview(self, preview: preview::Preview) -> Self {
+        self.wrap(|fmt| ByteFormat::previewing(fmt, preview))
+    }
+
+    /// Puts values longer than `threshold` bytes into `store` and writes a reference to them
+    /// instead, see [`blob`].
+    #[cfg(feature = "blob")]
+    pub fn blob_store(self, store: Arc<dyn blob::BlobStore>, threshold: usize) -> Self {
+        self.wrap(|fmt| {
+            ByteFormat::Blob(Arc::new(blob::Externalizing {
+                fmt,
+                store,
+                threshold,
+            }))
+        })
+    }
+
+    /// Writes bytes as maps with the name of the encoding, their length and the encoded bytes
+    /// under the keys of `envelope`, see [`envelope`]. Fails for encodings an envelope can't
+    /// name, and along with `checksum`, `sign` or `compress`.
+    #[cfg(feature = "alloc")]
+    pub fn envelope(self, envelope: envelope::Envelope) -> Self {
+        self.wrap(|fmt| ByteFormat::Envelope(Arc::new(envelope::Enveloping { fmt, envelope })))
+    }
+
+    /// Compresses values as configured by `compression` before encoding them, see
+    /// [`compress`]. Fails with [`Encoding::array`], as compressed values are text.
+    #[cfg(feature = "compress")]
+    pub fn compress(self, compression: compress::Compression) -> Self {
+        self.wrap(|fmt| {
+            ByteFormat::Compressing(Arc::new(compress::Compressing { fmt, compression }))
+        })
+    }
+
+    /// Appends the HMAC of the bytes to them before encoding them, see [`sign`].
+    #[cfg(feature = "hmac")]
+    pub fn sign(self, hmac: sign::Hmac) -> Self {
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind: self.encode_kind.within_sequences(|fmt| fmt.signing(hmac)),
+            yaml_tag: self.yaml_tag,
+        }
+    }
+
+    /// Appends a checksum to the bytes before encoding them, see [`checksum`].
+    #[cfg(feature = "checksum")]
+    pub fn checksum(self, checksum: checksum::Checksum) -> Self {
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind: self
+                .encode_kind
+                .within_sequences(|fmt| fmt.checksumming(checksum)),
+            yaml_tag: self.yaml_tag,
+        }
+    }
+
+    /// Encrypts the bytes selected by `encryption` instead of encoding them, see [`encrypt`].
+    #[cfg(feature = "encrypt")]
+    pub fn encrypt(self, encryption: encrypt::Encryption) -> Self {
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind: self
+                .encode_kind
+                .within_sequences(|fmt| fmt.encrypting(encryption)),
+            yaml_tag: self.yaml_tag,
+        }
+    }
+
+    /// Also encodes sequences and tuples of `u8`, like `Vec<u8>` and `[u8; 32]` fields without
+    /// `#[serde(with = "serde_bytes")]`, in human readable formats. Elements are collected
+    /// while they are `u8`, and written as a sequence as soon as one isn't. Empty sequences
+    /// stay sequences.
+    #[cfg(feature = "alloc")]
+    pub fn u8_sequences(self) -> Self {
+        let encode_kind = match self.encode_kind {
+            ByteFormat::Sequences(fmt) => ByteFormat::Sequences(fmt),
+            fmt => ByteFormat::Sequences(Arc::new(fmt)),
+        };
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind,
+            yaml_tag: self.yaml_tag,
+        }
+    }
+
+    /// Wraps the encoding with `wrap`.
+    #[cfg(feature = "alloc")]
+    fn wrap(self, wrap: impl FnOnce(ByteFormat) -> ByteFormat) -> Self {
+        ByteFmtSerializer {
+            inner: self.inner,
+            encode_kind: self.encode_kind.within_sequences(|fmt| fmt.wrap(wrap)),
+            yaml_tag: self.yaml_tag,
         }
     }
+
+    #[cfg(feature = "alloc")]
+    fn encode(&self, v: &[u8]) -> String {
+        self.encode_kind.encode(v)
+    }
 }
 
 /// Deserializer-adapter which decodes bytes from a specified format.
 pub struct ByteFmtDeserializer<D> {
     pub inner: D,
     fmt: ByteFormat,
+    /// Set by the `yaml` helpers, whose plain scalars may read as integers.
+    plain_scalars: bool,
 }
 
 impl<D> ByteFmtDeserializer<D> {
+    /// Creates an adapter which deserializes from the given [`Encoding`].
+    pub fn new(deserializer: D, encoding: Encoding) -> Self {
+        ByteFmtDeserializer {
+            inner: deserializer,
+            fmt: encoding.0,
+            plain_scalars: false,
+        }
+    }
+
     /// Crates an adapter which deserializes from a Base64 representation. Provide a
     /// configuration from the `base64` crate specifying the specifics on how you want the bytes
     /// encoded.
@@ -126,7 +1031,8 @@ impl<D> ByteFmtDeserializer<D> {
     pub fn new_base64(deserializer: D, alphabet: Alphabet, config: GeneralPurposeConfig) -> Self {
         ByteFmtDeserializer {
             inner: deserializer,
-            fmt: ByteFormat::Base64(alphabet, config),
+            fmt: ByteFormat::base64(&alphabet, config),
+            plain_scalars: false,
         }
     }
 
@@ -135,6 +1041,161 @@ impl<D> ByteFmtDeserializer<D> {
         ByteFmtDeserializer {
             inner: deserializer,
             fmt: ByteFormat::Hex,
+            plain_scalars: false,
+        }
+    }
+
+    /// Creates an adapter which deserializes from YAML `!!binary` scalars. Both tagged and
+    /// untagged scalars are accepted, line breaks inside the Base64 are ignored and values
+    /// carrying any other tag are rejected.
+    pub fn new_yaml_binary(deserializer: D) -> Self {
+        ByteFmtDeserializer {
+            inner: deserializer,
+            fmt: ByteFormat::YamlBinary { tagged: false },
+            plain_scalars: false,
+        }
+    }
+
+    /// Reads blob references written by [`ByteFmtSerializer::blob_store`] from `store`, see
+    /// [`blob`].
+    #[cfg(feature = "blob")]
+    pub fn blob_store(self, store: Arc<dyn blob::BlobStore>) -> Self {
+        let externalizing = blob::Externalizing {
+            fmt: self.fmt,
+            store,
+            threshold: 0,
+        };
+        ByteFmtDeserializer {
+            fmt: ByteFormat::Blob(Arc::new(externalizing)),
+            ..self
+        }
+    }
+
+    /// Reads envelopes written by [`ByteFmtSerializer::envelope`] with the keys of `envelope`,
+    /// decoding them as they say and checking their length. Plain values are still decoded
+    /// with the adapter's encoding. Envelopes fail along with `checksum` or `verify`. See
+    /// [`envelope`].
+    #[cfg(feature = "alloc")]
+    pub fn envelope(self, envelope: envelope::Envelope) -> Self {
+        let enveloping = envelope::Enveloping {
+            fmt: self.fmt,
+            envelope,
+        };
+        ByteFmtDeserializer {
+            fmt: ByteFormat::Envelope(Arc::new(enveloping)),
+            ..self
         }
     }
+
+    /// Decompresses values written by [`ByteFmtSerializer::compress`], failing on values that
+    /// would be longer than `limit` bytes once decompressed. See [`compress`].
+    #[cfg(feature = "compress")]
+    pub fn decompress(self, limit: usize) -> Self {
+        let decompressing = compress::Decompressing {
+            fmt: self.fmt,
+            limit,
+        };
+        ByteFmtDeserializer {
+            fmt: ByteFormat::Decompressing(Arc::new(decompressing)),
+            ..self
+        }
+    }
+
+    /// Checks and removes signatures appended by [`ByteFmtSerializer::sign`], failing on
+    /// values without a signature from one of the keys of `hmac`. See [`sign`].
+    #[cfg(feature = "hmac")]
+    pub fn verify(self, hmac: sign::Hmac) -> Self {
+        ByteFmtDeserializer {
+            fmt: self.fmt.signing(hmac),
+            ..self
+        }
+    }
+
+    /// Checks and removes checksums appended by [`ByteFmtSerializer::checksum`], see
+    /// [`checksum`].
+    #[cfg(feature = "checksum")]
+    pub fn checksum(self, checksum: checksum::Checksum) -> Self {
+        ByteFmtDeserializer {
+            fmt: self.fmt.checksumming(checksum),
+            ..self
+        }
+    }
+
+    /// Opens the bytes selected by `encryption`, which were written by
+    /// [`ByteFmtSerializer::encrypt`], and fails if they aren't encrypted. See [`encrypt`].
+    #[cfg(feature = "encrypt")]
+    pub fn decrypt(self, encryption: encrypt::Encryption) -> Self {
+        ByteFmtDeserializer {
+            fmt: self.fmt.encrypting(encryption),
+            ..self
+        }
+    }
+
+    /// Decodes the bytes selected by `constant_time` without branching on their characters,
+    /// for private keys and other secrets. See [`constant_time`](crate::constant_time).
+    #[cfg(feature = "alloc")]
+    pub fn constant_time(self, constant_time: constant_time::ConstantTime) -> Self {
+        let decoding = constant_time::ConstantTimeDecoding {
+            fmt: self.fmt,
+            constant_time,
+        };
+        ByteFmtDeserializer {
+            fmt: ByteFormat::ConstantTime(Arc::new(decoding)),
+            ..self
+        }
+    }
+
+    /// Also decodes strings where a sequence or tuple of `u8` is expected, like values written
+    /// by [`ByteFmtSerializer::u8_sequences`]. Sequences are still accepted. Decoded bytes
+    /// can't be read as other integers, so a string where a `Vec<u16>` is expected fails.
+    #[cfg(feature = "alloc")]
+    pub fn u8_sequences(self) -> Self {
+        ByteFmtDeserializer {
+            fmt: ByteFormat::Sequences(Arc::new(self.fmt)),
+            ..self
+        }
+    }
+
+    /// Deserializes a `T`. Unlike `T::deserialize(self)`, a value that fails to decode is
+    /// reported with its path, offset and an excerpt, see [`error`].
+    #[cfg(feature = "alloc")]
+    pub fn deserialize<'de, T>(self) -> Result<T, error::Error<D::Error>>
+    where
+        T: serde::Deserialize<'de>,
+        D: serde::Deserializer<'de>,
+    {
+        deserializer::deserialize(self)
+    }
+
+    /// Deserializes a `T`, replacing every value that fails to decode with empty bytes
+    /// instead of stopping at the first one. Returns the value along with the values that
+    /// were replaced, so all of them can be reported at once.
+    ///
+    /// Only targets that accept empty bytes can be recovered. Fixed-length ones like `[u8; 4]`
+    /// still fail, and the error keeps the replaced values in
+    /// [`recovered`](error::Error::recovered).
+    ///
+    /// ```rust
+    /// use serde_bytes::ByteBuf;
+    /// use serde_bytes_repr::ByteFmtDeserializer;
+    ///
+    /// let mut json_de = serde_json::Deserializer::from_str(r#"["00", "0x", "01", "zz"]"#);
+    /// let (keys, errors) = ByteFmtDeserializer::new_hex(&mut json_de)
+    ///     .deserialize_recovering::<Vec<ByteBuf>>()
+    ///     .unwrap();
+    /// let keys: Vec<_> = keys.into_iter().map(ByteBuf::into_vec).collect();
+    /// assert_eq!(vec![vec![0], vec![], vec![1], vec![]], keys);
+    /// let paths: Vec<_> = errors.iter().map(|err| err.path()).collect();
+    /// assert_eq!(vec!["/1", "/3"], paths);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn deserialize_recovering<'de, T>(
+        self,
+    ) -> Result<(T, Vec<error::DecodeError>), error::Error<D::Error>>
+    where
+        T: serde::Deserialize<'de>,
+        D: serde::Deserializer<'de>,
+    {
+        deserializer::deserialize_recovering(self)
+    }
 }
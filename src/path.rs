@@ -0,0 +1,121 @@
+//! Selecting values inside a document by path, for the helpers that work on documents without
+//! knowing their Rust types.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+/// One step into a document: a map key or a sequence index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Segment::Key(key) => f.write_str(&key.replace('~', "~0").replace('/', "~1")),
+            Segment::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+/// Formats a path as a JSON Pointer.
+pub(crate) struct DisplayPath<'a>(pub(crate) &'a [Segment]);
+
+impl fmt::Display for DisplayPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for segment in self.0 {
+            write!(f, "/{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pattern {
+    /// A key or index, where `*` matches any run of characters and `?` any single one.
+    Glob(String),
+    /// `**` matches any number of keys or indices, including none.
+    AnyDepth,
+}
+
+/// A JSON Pointer (RFC 6901) extended with globs: `*` and `?` inside a segment match any run
+/// of characters or a single one, so `*` on its own matches any key or index, and a `**`
+/// segment matches any number of levels. For example `/users/*/key` or `/**/*_key`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Pointer {
+    patterns: Vec<Pattern>,
+}
+
+impl Pointer {
+    pub(crate) fn parse(pointer: &str) -> Result<Self, String> {
+        if pointer.is_empty() {
+            return Ok(Pointer {
+                patterns: Vec::new(),
+            });
+        }
+        let rest = pointer
+            .strip_prefix('/')
+            .ok_or_else(|| format!("invalid pointer `{}`, expected a leading `/`", pointer))?;
+        let patterns = rest
+            .split('/')
+            .map(|segment| match segment {
+                "**" => Pattern::AnyDepth,
+                _ => Pattern::Glob(segment.replace("~1", "/").replace("~0", "~")),
+            })
+            .collect();
+        Ok(Pointer { patterns })
+    }
+
+    pub(crate) fn matches(&self, path: &[Segment]) -> bool {
+        matches(&self.patterns, path)
+    }
+}
+
+fn matches(patterns: &[Pattern], path: &[Segment]) -> bool {
+    match patterns.split_first() {
+        None => path.is_empty(),
+        Some((Pattern::AnyDepth, rest)) => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+        Some((Pattern::Glob(glob), rest)) => match path.split_first() {
+            Some((Segment::Key(key), path)) => glob_matches(glob, key) && matches(rest, path),
+            Some((Segment::Index(index), path)) => {
+                glob_matches(glob, &index.to_string()) && matches(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+fn glob_matches(glob: &str, s: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let s: Vec<char> = s.chars().collect();
+    // Position in `glob` after the last `*` and the position in `s` it was tried at.
+    let mut backtrack = None;
+    let (mut g, mut i) = (0, 0);
+    while i < s.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g + 1, i));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == s[i] => {
+                g += 1;
+                i += 1;
+            }
+            _ => match backtrack {
+                Some((after_star, tried)) => {
+                    backtrack = Some((after_star, tried + 1));
+                    g = after_star;
+                    i = tried + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
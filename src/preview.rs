@@ -0,0 +1,129 @@
+//! Abbreviating large values, for printing documents with big attachments.
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use serde_bytes_repr::{preview::Preview, ByteFmtSerializer};
+//!
+//! #[derive(Serialize)]
+//! struct Attachment {
+//!     #[serde(with = "serde_bytes")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! let attachment = Attachment { data: vec![0xab; 1024] };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! let ser = ByteFmtSerializer::hex(&mut ser).preview(Preview::head(4));
+//! attachment.serialize(ser).unwrap();
+//! assert_eq!(
+//!     r#"{"data":"abababab…(+1020 bytes)"}"#,
+//!     String::from_utf8(out).unwrap()
+//! );
+//! ```
+//!
+//! Previews can't be decoded again, [`ByteFmtDeserializer`](crate::ByteFmtDeserializer)
+//! rejects them with an error naming the value.
+use crate::{ByteFormat, PREVIEW_MARKER};
+use core::fmt;
+
+/// How much of a value is shown, for [`ByteFmtSerializer::preview`].
+///
+/// Values longer than the head and tail together are written as the head in the serializer's
+/// encoding, `…(+N bytes)` for the `N` bytes left out, and the tail after another `…`. Shorter
+/// values are written whole.
+///
+/// [`ByteFmtSerializer::preview`]: crate::ByteFmtSerializer::preview
+#[derive(Clone, Debug)]
+pub struct Preview {
+    head: usize,
+    tail: usize,
+    digest: Option<Digest>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Digest {
+    #[cfg(feature = "sha2")]
+    Sha256,
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl Preview {
+    /// Shows the first `len` bytes.
+    pub fn head(len: usize) -> Self {
+        Preview {
+            head: len,
+            tail: 0,
+            digest: None,
+        }
+    }
+
+    /// Also shows the last `len` bytes.
+    pub fn tail(mut self, len: usize) -> Self {
+        self.tail = len;
+        self
+    }
+
+    /// Adds the SHA-256 of the whole value, like `…(+1020 bytes, sha256:0a1b…)`.
+    #[cfg(feature = "sha2")]
+    pub fn sha256(mut self) -> Self {
+        self.digest = Some(Digest::Sha256);
+        self
+    }
+
+    /// Adds the BLAKE3 hash of the whole value, like `…(+1020 bytes, blake3:0a1b…)`.
+    #[cfg(feature = "blake3")]
+    pub fn blake3(mut self) -> Self {
+        self.digest = Some(Digest::Blake3);
+        self
+    }
+}
+
+/// A serializer's encoding along with a [`Preview`].
+#[derive(Clone)]
+pub(crate) struct Previewing {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) preview: Preview,
+}
+
+impl Previewing {
+    /// Whether `v` is written whole.
+    pub(crate) fn fits(&self, v: &[u8]) -> bool {
+        v.len() <= self.preview.head.saturating_add(self.preview.tail)
+    }
+
+    /// Writes the preview of `v`, which doesn't fit.
+    pub(crate) fn write<W: fmt::Write>(&self, v: &[u8], out: &mut W) -> fmt::Result {
+        let Preview { head, tail, digest } = self.preview;
+        // The head and tail of a preview are text, integer arrays included.
+        let fmt = &self.fmt;
+        fmt.encode_to(&v[..head], out)?;
+        write!(out, "{}{} bytes", PREVIEW_MARKER, v.len() - head - tail)?;
+        match digest {
+            None => {}
+            #[cfg(feature = "sha2")]
+            Some(Digest::Sha256) => {
+                use sha2::{Digest, Sha256};
+
+                out.write_str(", sha256:")?;
+                write_hex(&Sha256::digest(v), out)?;
+            }
+            #[cfg(feature = "blake3")]
+            Some(Digest::Blake3) => {
+                out.write_str(", blake3:")?;
+                write_hex(blake3::hash(v).as_bytes(), out)?;
+            }
+        }
+        out.write_char(')')?;
+        if tail > 0 {
+            out.write_char('…')?;
+            fmt.encode_to(&v[v.len() - tail..], out)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "sha2", feature = "blake3"))]
+fn write_hex<W: fmt::Write>(v: &[u8], out: &mut W) -> fmt::Result {
+    v.iter().try_for_each(|b| write!(out, "{:02x}", b))
+}
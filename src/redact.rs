@@ -0,0 +1,148 @@
+//! Replacing bytes with a placeholder, for logging values that hold secrets.
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use serde_bytes_repr::{redact::Redaction, ByteFmtSerializer};
+//!
+//! #[derive(Serialize)]
+//! struct Request {
+//!     #[serde(with = "serde_bytes")]
+//!     id: Vec<u8>,
+//!     #[serde(with = "serde_bytes")]
+//!     token: Vec<u8>,
+//! }
+//!
+//! let request = Request { id: vec![1, 2], token: vec![0xab; 32] };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! let ser = ByteFmtSerializer::hex(&mut ser).redact(Redaction::marker().fields(["token"]));
+//! request.serialize(ser).unwrap();
+//! assert_eq!(
+//!     r#"{"id":"0102","token":"<redacted 32 bytes>"}"#,
+//!     String::from_utf8(out).unwrap()
+//! );
+//! ```
+use crate::{selection::Selection, ByteFormat};
+#[cfg(feature = "sha2")]
+use alloc::vec::Vec;
+use core::fmt;
+
+/// How bytes are redacted and which ones, for [`ByteFmtSerializer::redact`].
+///
+/// Bytes below the selected struct fields and newtype structs are redacted, the others are
+/// written with the serializer's encoding. Without any field or newtype, all bytes are
+/// redacted.
+///
+/// The output can't be deserialized again.
+///
+/// [`ByteFmtSerializer::redact`]: crate::ByteFmtSerializer::redact
+#[derive(Clone, Debug)]
+pub struct Redaction {
+    mode: Mode,
+    selection: Selection,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Mode {
+    Marker,
+    Prefix(usize),
+    #[cfg(feature = "sha2")]
+    Fingerprint(Vec<u8>),
+}
+
+impl Redaction {
+    fn new(mode: Mode) -> Self {
+        Redaction {
+            mode,
+            selection: Selection::default(),
+        }
+    }
+
+    /// Writes `<redacted 32 bytes>`.
+    pub fn marker() -> Self {
+        Redaction::new(Mode::Marker)
+    }
+
+    /// Writes the first `len` bytes in HEX, like `<redacted 32 bytes: 0a1b…>`. At most half
+    /// of a value is shown, whatever `len` is.
+    pub fn prefix(len: usize) -> Self {
+        Redaction::new(Mode::Prefix(len))
+    }
+
+    /// Writes the start of the SHA-256 of `salt` followed by the bytes, like
+    /// `<redacted 32 bytes sha256:0a1b2c3d4e5f6a7b>`. Equal values get the same fingerprint,
+    /// so they can be matched up across log lines without being shown.
+    #[cfg(feature = "sha2")]
+    pub fn fingerprint(salt: &[u8]) -> Self {
+        Redaction::new(Mode::Fingerprint(salt.to_vec()))
+    }
+
+    /// Redacts the bytes below struct fields with these names.
+    pub fn fields<I>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.selection.add_fields(fields);
+        self
+    }
+
+    /// Redacts the bytes below newtype structs with these names, like `SecretKey` for
+    /// `struct SecretKey(#[serde(with = "serde_bytes")] Vec<u8>)`.
+    pub fn newtypes<I>(mut self, newtypes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.selection.add_newtypes(newtypes);
+        self
+    }
+}
+
+/// A serializer's encoding along with a [`Redaction`].
+#[derive(Clone)]
+pub(crate) struct Redacting {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) redaction: Redaction,
+}
+
+impl Redacting {
+    /// The values that are redacted.
+    pub(crate) fn selection(&self) -> &Selection {
+        &self.redaction.selection
+    }
+
+    pub(crate) fn mode(&self) -> Mode {
+        self.redaction.mode.clone()
+    }
+}
+
+/// Writes the placeholder for `v`.
+pub(crate) fn write<W: fmt::Write>(mode: &Mode, v: &[u8], out: &mut W) -> fmt::Result {
+    write!(out, "<redacted {} bytes", v.len())?;
+    match mode {
+        Mode::Marker => {}
+        Mode::Prefix(len) => {
+            out.write_str(": ")?;
+            for b in &v[..(*len).min(v.len() / 2)] {
+                write!(out, "{:02x}", b)?;
+            }
+            out.write_char('…')?;
+        }
+        #[cfg(feature = "sha2")]
+        Mode::Fingerprint(salt) => {
+            use sha2::{Digest, Sha256};
+
+            let digest = Sha256::new().chain_update(salt).chain_update(v).finalize();
+            out.write_str(" sha256:")?;
+            for b in &digest[..FINGERPRINT_LEN] {
+                write!(out, "{:02x}", b)?;
+            }
+        }
+    }
+    out.write_char('>')
+}
+
+/// Bytes of the SHA-256 shown in fingerprints.
+#[cfg(feature = "sha2")]
+const FINGERPRINT_LEN: usize = 8;
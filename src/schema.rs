@@ -0,0 +1,280 @@
+//! JSON Schemas for bytes fields, with `schemars`.
+//!
+//! `schemars` describes `Vec<u8>` as an array of integers whatever the adapter writes. The
+//! types here stand in for bytes fields with `#[schemars(with = "...")]` and describe the
+//! strings [`ByteFmtSerializer`](crate::ByteFmtSerializer) writes instead: their
+//! `contentEncoding`, a `pattern` and, from the lengths of the bytes, `minLength` and
+//! `maxLength`. [`BytesSchema`] builds the same schemas for an [`Encoding`] chosen at runtime.
+//!
+//! ```rust
+//! use schemars::JsonSchema;
+//! use serde::Serialize;
+//! use serde_bytes_repr::schema;
+//!
+//! #[derive(Serialize, JsonSchema)]
+//! struct Account {
+//!     #[serde(with = "serde_bytes")]
+//!     #[schemars(with = "schema::Hex<4, 4>")]
+//!     id: Vec<u8>,
+//!     #[serde(with = "serde_bytes")]
+//!     #[schemars(with = "schema::Base64Url")]
+//!     avatar: Vec<u8>,
+//! }
+//!
+//! let schema = schemars::schema_for!(Account);
+//! assert_eq!(
+//!     serde_json::json!({
+//!         "type": "string",
+//!         "contentEncoding": "base16",
+//!         "pattern": "^(?:[0-9a-f]{2})*$",
+//!         "minLength": 8,
+//!         "maxLength": 8,
+//!     }),
+//!     schema.as_value()["properties"]["id"]
+//! );
+//! assert_eq!(
+//!     "base64url",
+//!     schema.as_value()["properties"]["avatar"]["contentEncoding"]
+//! );
+//! ```
+use crate::{ByteFormat, Encoding};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use base64::{
+    alphabet::{self, Alphabet},
+    engine::{general_purpose, Config},
+    Engine,
+};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+/// The schema of bytes in an [`Encoding`], optionally limited to some lengths.
+#[derive(Clone)]
+pub struct BytesSchema {
+    encoding: Encoding,
+    min_len: usize,
+    max_len: Option<usize>,
+}
+
+impl BytesSchema {
+    /// Bytes of any length in `encoding`.
+    pub fn new(encoding: Encoding) -> Self {
+        BytesSchema {
+            encoding,
+            min_len: 0,
+            max_len: None,
+        }
+    }
+
+    /// Only bytes that are exactly `len` long, like keys and hashes.
+    pub fn len(self, len: usize) -> Self {
+        self.min_len(len).max_len(len)
+    }
+
+    /// Only bytes that are at least `len` long.
+    pub fn min_len(mut self, len: usize) -> Self {
+        self.min_len = len;
+        self
+    }
+
+    /// Only bytes that are at most `len` long.
+    pub fn max_len(mut self, len: usize) -> Self {
+        self.max_len = Some(len);
+        self
+    }
+
+    /// The schema of the values the serializer writes.
+    ///
+    /// HEX is written in lowercase and Base64 with or without padding as configured, so the
+    /// `pattern` doesn't match every value the deserializer accepts.
+    pub fn schema(&self) -> Schema {
+        let fmt = &self.encoding.0;
+        let schema = match fmt {
+            ByteFormat::Array => {
+                let schema = json_schema!({
+                    "type": "array",
+                    "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                });
+                return with_bounds(schema, "minItems", self.min_len, "maxItems", self.max_len);
+            }
+            ByteFormat::Hex => json_schema!({
+                "type": "string",
+                "contentEncoding": "base16",
+                "pattern": "^(?:[0-9a-f]{2})*$",
+            }),
+            ByteFormat::Base64(codec) => {
+                let padding = codec.engine.config().encode_padding();
+                let mut schema = json_schema!({ "type": "string" });
+                if let Some(encoding) = content_encoding(&codec.alphabet) {
+                    schema.insert("contentEncoding".to_string(), encoding.into());
+                }
+                let pattern = base64_pattern(&codec.alphabet, padding, false);
+                schema.insert("pattern".to_string(), pattern.into());
+                schema
+            }
+            ByteFormat::YamlBinary { .. } => json_schema!({
+                "type": "string",
+                "contentEncoding": "base64",
+                "pattern": base64_pattern(&alphabet::STANDARD, true, true),
+            }),
+            _ => unreachable!("encodings are never wrapped"),
+        };
+        // Lengths that don't fit a `usize` aren't limited.
+        let min_len = fmt.encoded_len(self.min_len).unwrap_or(0);
+        let max_len = self.max_len.and_then(|len| fmt.encoded_len(len));
+        with_bounds(schema, "minLength", min_len, "maxLength", max_len)
+    }
+}
+
+/// Declares a type standing in for bytes in `$encoding` with `#[schemars(with = "...")]`.
+macro_rules! encoded_bytes {
+    ($(#[$doc:meta])* $name:ident, $encoding:expr) => {
+        $(#[$doc])*
+        ///
+        /// The bytes are at least `MIN` and at most `MAX` long, `usize::MAX` leaves the length
+        /// open.
+        pub struct $name<const MIN: usize = 0, const MAX: usize = { usize::MAX }>;
+
+        impl<const MIN: usize, const MAX: usize> JsonSchema for $name<MIN, MAX> {
+            fn inline_schema() -> bool {
+                true
+            }
+
+            fn schema_name() -> Cow<'static, str> {
+                stringify!($name).into()
+            }
+
+            fn schema_id() -> Cow<'static, str> {
+                format!("{}::{}<{}, {}>", module_path!(), stringify!($name), MIN, MAX).into()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                let schema = BytesSchema::new($encoding).min_len(MIN);
+                if MAX == usize::MAX {
+                    schema.schema()
+                } else {
+                    schema.max_len(MAX).schema()
+                }
+            }
+        }
+    };
+}
+
+encoded_bytes!(
+    /// Bytes in lowercase HEX.
+    Hex,
+    Encoding::hex()
+);
+encoded_bytes!(
+    /// Bytes in standard Base64 with padding.
+    Base64,
+    Encoding::base64(alphabet::STANDARD, general_purpose::PAD)
+);
+encoded_bytes!(
+    /// Bytes in URL-safe Base64 with padding.
+    Base64Url,
+    Encoding::base64(alphabet::URL_SAFE, general_purpose::PAD)
+);
+encoded_bytes!(
+    /// Bytes in standard Base64 without padding.
+    Base64NoPad,
+    Encoding::base64(alphabet::STANDARD, general_purpose::NO_PAD)
+);
+encoded_bytes!(
+    /// Bytes in URL-safe Base64 without padding.
+    Base64UrlNoPad,
+    Encoding::base64(alphabet::URL_SAFE, general_purpose::NO_PAD)
+);
+encoded_bytes!(
+    /// Bytes as standard Base64 folded like a YAML `!!binary` scalar.
+    YamlBinary,
+    Encoding::yaml_binary()
+);
+encoded_bytes!(
+    /// Bytes as a sequence of integers.
+    Array,
+    Encoding::array()
+);
+
+/// Adds bounds on the length of a string or array, leaving out a minimum of zero.
+fn with_bounds(
+    mut schema: Schema,
+    min: &str,
+    min_len: usize,
+    max: &str,
+    max_len: Option<usize>,
+) -> Schema {
+    if min_len > 0 {
+        schema.insert(min.to_string(), min_len.into());
+    }
+    if let Some(max_len) = max_len {
+        schema.insert(max.to_string(), max_len.into());
+    }
+    schema
+}
+
+/// The name RFC 4648 gives the alphabet, if it is a standard one.
+fn content_encoding(alphabet: &Alphabet) -> Option<&'static str> {
+    if alphabet.as_str() == alphabet::STANDARD.as_str() {
+        Some("base64")
+    } else if alphabet.as_str() == alphabet::URL_SAFE.as_str() {
+        Some("base64url")
+    } else {
+        None
+    }
+}
+
+/// Groups of four symbols and a shorter last one. `folded` allows the line breaks of YAML's
+/// `!!binary`, which come after whole groups.
+fn base64_pattern(alphabet: &Alphabet, padding: bool, folded: bool) -> String {
+    let symbol = symbol_class(alphabet.as_str());
+    let line_break = if folded { "\\n?" } else { "" };
+    let last = if padding {
+        format!("{0}{{2}}==|{0}{{3}}=", symbol)
+    } else {
+        format!("{}{{2,3}}", symbol)
+    };
+    format!("^(?:{}{{4}}{})*(?:{})?$", symbol, line_break, last)
+}
+
+/// A regular expression character class matching the symbols, with runs of digits and letters
+/// like `A-Z` written as ranges.
+fn symbol_class(symbols: &str) -> String {
+    let mut symbols: Vec<char> = symbols.chars().collect();
+    symbols.sort_unstable();
+    let mut class = String::from("[");
+    for run in symbols.chunk_by(|&a, &b| b as u32 == a as u32 + 1 && kind(a) == kind(b)) {
+        if run.len() > 2 {
+            class.push_str(&format!(
+                "{}-{}",
+                escape(run[0]),
+                escape(run[run.len() - 1])
+            ));
+        } else {
+            run.iter().for_each(|&c| class.push_str(&escape(c)));
+        }
+    }
+    class.push(']');
+    class
+}
+
+/// Whether `c` is a digit, an uppercase letter, a lowercase letter or something else.
+fn kind(c: char) -> u8 {
+    match c {
+        '0'..='9' => 1,
+        'A'..='Z' => 2,
+        'a'..='z' => 3,
+        _ => 0,
+    }
+}
+
+/// Escapes the characters with a meaning inside a character class.
+fn escape(c: char) -> String {
+    match c {
+        '\\' | '[' | ']' | '^' | '-' => format!("\\{}", c),
+        c => c.to_string(),
+    }
+}
@@ -0,0 +1,98 @@
+//! Serializing `secrecy::SecretSlice<u8>` fields as bytes, with
+//! `#[serde(with = "serde_bytes_repr::secret")]`.
+//!
+//! `secrecy` doesn't serialize secrets and deserializes them from sequences of integers. These
+//! functions go through serde's bytes instead, so the adapters encode them, and copy the bytes
+//! into the secret without leaving them behind in freed memory. Together with the `zeroize`
+//! feature, which wipes the buffers the adapters decode into, key material only stays in the
+//! secret itself.
+//!
+//! ```rust
+//! use secrecy::{ExposeSecret, SecretSlice};
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Account {
+//!     #[serde(with = "serde_bytes_repr::secret")]
+//!     private_key: SecretSlice<u8>,
+//! }
+//!
+//! let account = Account { private_key: vec![0x9f, 0x86, 0xd0, 0x81].into() };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! account.serialize(ByteFmtSerializer::hex(&mut ser)).unwrap();
+//! assert_eq!(br#"{"private_key":"9f86d081"}"#, &out[..]);
+//!
+//! let mut de = serde_json::Deserializer::from_slice(&out);
+//! let account = Account::deserialize(ByteFmtDeserializer::new_hex(&mut de)).unwrap();
+//! assert_eq!(&[0x9f, 0x86, 0xd0, 0x81], account.private_key.expose_secret());
+//! ```
+use crate::wipe::{self, Wipe, Wiped};
+use alloc::vec::Vec;
+use core::fmt;
+use secrecy::{ExposeSecret, SecretSlice};
+use serde::{de, Deserializer, Serializer};
+
+/// Serializes the secret as bytes.
+pub fn serialize<S: Serializer>(
+    secret: &SecretSlice<u8>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(secret.expose_secret())
+}
+
+/// Deserializes a secret from bytes, a string or a sequence of integers.
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SecretSlice<u8>, D::Error> {
+    deserializer.deserialize_byte_buf(SecretVisitor)
+}
+
+/// Copies bytes into a secret of exactly their length, so it isn't reallocated.
+fn secret(bytes: &[u8]) -> SecretSlice<u8> {
+    SecretSlice::from(bytes.to_vec())
+}
+
+struct SecretVisitor;
+
+impl<'de> de::Visitor<'de> for SecretVisitor {
+    type Value = SecretSlice<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(secret(v))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, mut v: Vec<u8>) -> Result<Self::Value, E> {
+        if v.capacity() == v.len() {
+            return Ok(SecretSlice::from(v));
+        }
+        // Shrinking would free the buffer without wiping it.
+        let secret = secret(&v);
+        v.wipe();
+        Ok(secret)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(secret(v.as_bytes()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: alloc::string::String) -> Result<Self::Value, E> {
+        self.visit_byte_buf(v.into_bytes())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut bytes = Wiped(Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096)));
+        while let Some(b) = seq.next_element()? {
+            wipe::extend(&mut bytes, &[b]);
+        }
+        Ok(secret(&bytes))
+    }
+}
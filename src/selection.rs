@@ -0,0 +1,48 @@
+//! Struct fields and newtype structs selecting the bytes that redaction, encryption and
+//! constant time decoding apply to.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The names of the selected struct fields and newtype structs. Without any, every value is
+/// selected.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Selection {
+    fields: Vec<String>,
+    newtypes: Vec<String>,
+}
+
+impl Selection {
+    pub(crate) fn add_fields<I>(&mut self, fields: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let fields = fields.into_iter().map(|field| field.as_ref().to_string());
+        self.fields.extend(fields);
+    }
+
+    pub(crate) fn add_newtypes<I>(&mut self, newtypes: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let newtypes = newtypes.into_iter().map(|name| name.as_ref().to_string());
+        self.newtypes.extend(newtypes);
+    }
+
+    /// Whether every value is selected, not only those below the names.
+    pub(crate) fn everything(&self) -> bool {
+        self.fields.is_empty() && self.newtypes.is_empty()
+    }
+
+    pub(crate) fn is_field(&self, key: &str) -> bool {
+        self.fields.iter().any(|field| field == key)
+    }
+
+    pub(crate) fn is_newtype(&self, name: &str) -> bool {
+        self.newtypes.iter().any(|newtype| newtype == name)
+    }
+}
@@ -0,0 +1,148 @@
+//! `serde_with` adapters, for `#[serde_as(as = "...")]` annotations.
+//!
+//! Each type encodes bytes like [`ByteFmtSerializer`] with the same encoding and decodes them
+//! like [`ByteFmtDeserializer`], so values and error messages are the same. They work for
+//! anything that is `AsRef<[u8]>` and `TryFrom<Vec<u8>>`, like `Vec<u8>` and `[u8; 32]`, and
+//! inside containers like `Vec<Hex>`, `Option<Base64>` and `HashMap<Hex, _>`. `serde_with`'s
+//! `Bytes` keeps the integer arrays.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::serde_as::{Base64Url, Hex};
+//! use serde_with::serde_as;
+//! use std::collections::BTreeMap;
+//!
+//! #[serde_as]
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Keys {
+//!     #[serde_as(as = "Hex")]
+//!     id: [u8; 2],
+//!     #[serde_as(as = "Option<Base64Url>")]
+//!     avatar: Option<Vec<u8>>,
+//!     #[serde_as(as = "BTreeMap<Hex, Vec<Hex>>")]
+//!     signatures: BTreeMap<Vec<u8>, Vec<Vec<u8>>>,
+//! }
+//!
+//! let keys = Keys {
+//!     id: [1, 2],
+//!     avatar: Some(vec![0xfb, 0xff]),
+//!     signatures: BTreeMap::from([(vec![0xab], vec![vec![0xcd], vec![0xef]])]),
+//! };
+//! let json = serde_json::to_string(&keys).unwrap();
+//! assert_eq!(
+//!     r#"{"id":"0102","avatar":"-_8=","signatures":{"ab":["cd","ef"]}}"#,
+//!     json
+//! );
+//! assert_eq!(keys, serde_json::from_str(&json).unwrap());
+//! ```
+//!
+//! [`ByteFmtSerializer`]: crate::ByteFmtSerializer
+//! [`ByteFmtDeserializer`]: crate::ByteFmtDeserializer
+use crate::{ByteFmtDeserializer, ByteFmtSerializer, Encoding};
+use alloc::vec::Vec;
+use base64::{alphabet, engine::general_purpose};
+use core::{convert::TryFrom, fmt};
+use serde::{de, Deserializer, Serializer};
+use serde_with::{DeserializeAs, SerializeAs};
+
+/// Declares a `serde_with` adapter for bytes in `$encoding`.
+macro_rules! encoded_bytes {
+    ($(#[$doc:meta])* $name:ident, $encoding:expr) => {
+        $(#[$doc])*
+        pub struct $name;
+
+        impl<T: AsRef<[u8]>> SerializeAs<T> for $name {
+            fn serialize_as<S: Serializer>(source: &T, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize(source.as_ref(), serializer, $encoding)
+            }
+        }
+
+        impl<'de, T: TryFrom<Vec<u8>>> DeserializeAs<'de, T> for $name {
+            fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+                deserialize(deserializer, $encoding)
+            }
+        }
+    };
+}
+
+encoded_bytes!(
+    /// Bytes in lowercase HEX, uppercase is accepted too.
+    Hex,
+    Encoding::hex()
+);
+encoded_bytes!(
+    /// Bytes in standard Base64 with padding.
+    Base64,
+    Encoding::base64(alphabet::STANDARD, general_purpose::PAD)
+);
+encoded_bytes!(
+    /// Bytes in URL-safe Base64 with padding.
+    Base64Url,
+    Encoding::base64(alphabet::URL_SAFE, general_purpose::PAD)
+);
+encoded_bytes!(
+    /// Bytes in standard Base64 without padding.
+    Base64NoPad,
+    Encoding::base64(alphabet::STANDARD, general_purpose::NO_PAD)
+);
+encoded_bytes!(
+    /// Bytes in URL-safe Base64 without padding.
+    Base64UrlNoPad,
+    Encoding::base64(alphabet::URL_SAFE, general_purpose::NO_PAD)
+);
+encoded_bytes!(
+    /// Bytes as standard Base64 folded like a YAML `!!binary` scalar.
+    YamlBinary,
+    Encoding::yaml_binary()
+);
+
+/// Encodes `v` the way [`ByteFmtSerializer`] does.
+fn serialize<S: Serializer>(
+    v: &[u8],
+    serializer: S,
+    encoding: Encoding,
+) -> Result<S::Ok, S::Error> {
+    ByteFmtSerializer::new(serializer, encoding).serialize_bytes(v)
+}
+
+/// Decodes bytes the way [`ByteFmtDeserializer`] does and converts them to `T`.
+fn deserialize<'de, T, D>(deserializer: D, encoding: Encoding) -> Result<T, D::Error>
+where
+    T: TryFrom<Vec<u8>>,
+    D: Deserializer<'de>,
+{
+    let bytes =
+        ByteFmtDeserializer::new(deserializer, encoding).deserialize_byte_buf(BytesVisitor)?;
+    let len = bytes.len();
+    T::try_from(bytes)
+        .map_err(|_| de::Error::invalid_length(len, &"bytes of the length of the target type"))
+}
+
+struct BytesVisitor;
+
+impl<'de> de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(bytes)
+    }
+}
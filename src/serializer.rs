@@ -1,21 +1,30 @@
-use crate::{ByteFmtSerializer, ByteFormat};
+#[cfg(feature = "alloc")]
+use crate::wipe;
+use crate::{wipe::Wiped, ByteFmtSerializer, ByteFormat};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
 use serde::{
     ser::{self, Error},
-    serde_if_integer128, Serialize, Serializer,
+    Serialize, Serializer,
+};
+#[cfg(feature = "yaml")]
+use serde_yaml::{
+    value::{Tag, TaggedValue},
+    Value,
 };
-use std::fmt::Display;
 
-impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
+impl<S: Serializer, const N: usize> Serializer for ByteFmtSerializer<S, N> {
     type Ok = S::Ok;
     type Error = S::Error;
 
-    type SerializeSeq = SerializeSeq<S::SerializeSeq>;
-    type SerializeTuple = SerializeTuple<S::SerializeTuple>;
-    type SerializeTupleStruct = SerializeTupleStruct<S::SerializeTupleStruct>;
-    type SerializeTupleVariant = SerializeTupleVariant<S::SerializeTupleVariant>;
-    type SerializeMap = SerializeMap<S::SerializeMap>;
-    type SerializeStruct = SerializeStruct<S::SerializeStruct>;
-    type SerializeStructVariant = SerializeStructVariant<S::SerializeStructVariant>;
+    type SerializeSeq = SerializeSeq<S, N>;
+    type SerializeTuple = SerializeTuple<S, N>;
+    type SerializeTupleStruct = SerializeTupleStruct<S::SerializeTupleStruct, N>;
+    type SerializeTupleVariant = SerializeTupleVariant<S::SerializeTupleVariant, N>;
+    type SerializeMap = SerializeMap<S::SerializeMap, N>;
+    type SerializeStruct = SerializeStruct<S::SerializeStruct, N>;
+    type SerializeStructVariant = SerializeStructVariant<S::SerializeStructVariant, N>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         S::serialize_bool(self.inner, v)
@@ -37,11 +46,9 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
         S::serialize_i64(self.inner, v)
     }
 
-    serde_if_integer128! {
-        fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-            let _ = v;
-            Err(Error::custom("i128 is not supported"))
-        }
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(Error::custom("i128 is not supported"))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
@@ -60,11 +67,9 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
         S::serialize_u64(self.inner, v)
     }
 
-    serde_if_integer128! {
-        fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-            let _ = v;
-            Err(Error::custom("u128 is not supported"))
-        }
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        let _ = v;
+        Err(Error::custom("u128 is not supported"))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
@@ -84,17 +89,174 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        let encoded = self.encode(v);
-        S::serialize_str(self.inner, &encoded)
+        #[cfg(feature = "alloc")]
+        if let ByteFormat::Sequences(fmt) = &self.encode_kind {
+            let ser = ByteFmtSerializer::<_, N> {
+                inner: self.inner,
+                encode_kind: (**fmt).clone(),
+                yaml_tag: self.yaml_tag,
+            };
+            return ser.serialize_bytes(v);
+        }
+        #[cfg(feature = "alloc")]
+        if let ByteFormat::Redacting(redacting) = &self.encode_kind {
+            let encode_kind = if redacting.selection().everything() {
+                ByteFormat::Redacted(redacting.mode())
+            } else {
+                redacting.fmt.clone()
+            };
+            let ser = ByteFmtSerializer::<_, N> {
+                inner: self.inner,
+                encode_kind,
+                yaml_tag: self.yaml_tag,
+            };
+            return ser.serialize_bytes(v);
+        }
+        #[cfg(feature = "alloc")]
+        if let ByteFormat::Preview(previewing) = &self.encode_kind {
+            if previewing.fits(v) {
+                let ser = ByteFmtSerializer::<_, N> {
+                    inner: self.inner,
+                    encode_kind: previewing.fmt.clone(),
+                    yaml_tag: self.yaml_tag,
+                };
+                return ser.serialize_bytes(v);
+            }
+        }
+        #[cfg(feature = "hmac")]
+        if let ByteFormat::Signing(signing) = &self.encode_kind {
+            if signing.fmt.envelops() && self.inner.is_human_readable() {
+                return Err(S::Error::custom(crate::envelope::APPENDED));
+            }
+            let ser = ByteFmtSerializer::<_, N> {
+                inner: self.inner,
+                encode_kind: signing.fmt.clone(),
+                yaml_tag: self.yaml_tag,
+            };
+            return ser.serialize_bytes(&Wiped(signing.hmac.sign(v)));
+        }
+        #[cfg(feature = "checksum")]
+        if let ByteFormat::Checksumming(checksumming) = &self.encode_kind {
+            if checksumming.fmt.envelops() && self.inner.is_human_readable() {
+                return Err(S::Error::custom(crate::envelope::APPENDED));
+            }
+            let ser = ByteFmtSerializer::<_, N> {
+                inner: self.inner,
+                encode_kind: checksumming.fmt.clone(),
+                yaml_tag: self.yaml_tag,
+            };
+            return ser.serialize_bytes(&Wiped(checksumming.checksum.append(v)));
+        }
+        #[cfg(feature = "encrypt")]
+        if let ByteFormat::Encrypting(encrypting) = &self.encode_kind {
+            let encode_kind = if encrypting.selection().everything() {
+                ByteFormat::Sealed(encrypting.clone())
+            } else {
+                encrypting.fmt.clone()
+            };
+            let ser = ByteFmtSerializer::<_, N> {
+                inner: self.inner,
+                encode_kind,
+                yaml_tag: self.yaml_tag,
+            };
+            return ser.serialize_bytes(v);
+        }
+        #[cfg(feature = "encrypt")]
+        if let ByteFormat::Sealed(encrypting) = &self.encode_kind {
+            let sealed = Wiped(encrypting.seal(v).map_err(S::Error::custom)?);
+            return S::serialize_str(self.inner, &sealed);
+        }
+        #[cfg(feature = "alloc")]
+        if let ByteFormat::Envelope(enveloping) = &self.encode_kind {
+            if !self.inner.is_human_readable() {
+                let ser = ByteFmtSerializer::<_, N> {
+                    inner: self.inner,
+                    encode_kind: enveloping.fmt.clone(),
+                    yaml_tag: self.yaml_tag,
+                };
+                return ser.serialize_bytes(v);
+            }
+            #[cfg(feature = "compress")]
+            if let ByteFormat::Compressing(_) = enveloping.fmt {
+                return Err(S::Error::custom(crate::envelope::COMPRESSED));
+            }
+            let encoding = enveloping.encoding().ok_or_else(|| {
+                S::Error::custom(format_args!(
+                    "bytes in an envelope must be encoded in {}",
+                    crate::envelope::ENCODINGS
+                ))
+            })?;
+            let envelope = &enveloping.envelope;
+            let data = Wiped(enveloping.fmt.encode(v));
+            let mut map = S::serialize_map(self.inner, Some(3))?;
+            ser::SerializeMap::serialize_entry(&mut map, &envelope.encoding_key, encoding)?;
+            ser::SerializeMap::serialize_entry(&mut map, &envelope.length_key, &v.len())?;
+            ser::SerializeMap::serialize_entry(&mut map, &envelope.data_key, &*data)?;
+            return ser::SerializeMap::end(map);
+        }
+        #[cfg(feature = "compress")]
+        if let ByteFormat::Compressing(compressing) = &self.encode_kind {
+            if let ByteFormat::Array = compressing.fmt.encoding() {
+                return Err(S::Error::custom(
+                    "compressed values can't be written as integer arrays",
+                ));
+            }
+            if compressing.fmt.envelops() && self.inner.is_human_readable() {
+                return Err(S::Error::custom(crate::envelope::COMPRESSED));
+            }
+            if let Some(encoded) = compressing.encode(v).map_err(S::Error::custom)? {
+                return S::serialize_str(self.inner, &Wiped(encoded));
+            }
+            let ser = ByteFmtSerializer::<_, N> {
+                inner: self.inner,
+                encode_kind: compressing.fmt.clone(),
+                yaml_tag: self.yaml_tag,
+            };
+            return ser.serialize_bytes(v);
+        }
+        #[cfg(feature = "blob")]
+        if let ByteFormat::Blob(externalizing) = &self.encode_kind {
+            if externalizing.fits(v) {
+                let ser = ByteFmtSerializer::<_, N> {
+                    inner: self.inner,
+                    encode_kind: externalizing.fmt.clone(),
+                    yaml_tag: self.yaml_tag,
+                };
+                return ser.serialize_bytes(v);
+            }
+            let key = externalizing.put(v).map_err(S::Error::custom)?;
+            let mut map = S::serialize_map(self.inner, Some(1))?;
+            ser::SerializeMap::serialize_entry(&mut map, crate::blob::REFERENCE_KEY, &key)?;
+            return ser::SerializeMap::end(map);
+        }
+        if let ByteFormat::Array = self.encode_kind {
+            return S::collect_seq(self.inner, v);
+        }
+        let mut buf = Wiped([0; N]);
+        if let Some(encoded) = self.encode_kind.encode_to_slice(v, &mut buf[..]) {
+            return self.serialize_encoded(encoded);
+        }
+        #[cfg(feature = "alloc")]
+        let encoded = Wiped(self.encode(v));
+        #[cfg(feature = "alloc")]
+        let encoded = &*encoded;
+        #[cfg(not(feature = "alloc"))]
+        let fmt = self.encode_kind.clone();
+        #[cfg(not(feature = "alloc"))]
+        let encoded = &Encoded {
+            fmt: &fmt,
+            bytes: v,
+        };
+        self.serialize_encoded(encoded)
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
         S::serialize_none(self.inner)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         value.serialize(self)
     }
@@ -116,22 +278,31 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
         S::serialize_unit_variant(self.inner, name, variant_index, variant)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
+        #[cfg(feature = "bytes")]
+        if name == crate::bytes::STREAMED {
+            return value.serialize(Streaming(self));
+        }
+        // Not `BytesSerialize::new`, a newtype struct doesn't get its own YAML tag.
         S::serialize_newtype_struct(
             self.inner,
             name,
-            &BytesSerialize::new(value, self.encode_kind.clone()),
+            &BytesSerialize::<_, N> {
+                value,
+                fmt: self.encode_kind.newtype(name),
+                yaml_tag: self.yaml_tag,
+            },
         )
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         name: &'static str,
         variant_index: u32,
@@ -139,23 +310,40 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
+        // The variant name is written as the YAML tag, a value can't have two.
         S::serialize_newtype_variant(
             self.inner,
             name,
             variant_index,
             variant,
-            &BytesSerialize::new(value, self.encode_kind.clone()),
+            &BytesSerialize::<_, N> {
+                value,
+                fmt: self.encode_kind,
+                yaml_tag: false,
+            },
         )
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if self.collects_u8() {
+            return Ok(SerializeSeq::collecting(self.inner, len, self.encode_kind));
+        }
         let kind = self.encode_kind;
         S::serialize_seq(self.inner, len).map(|ser| SerializeSeq::new(ser, kind))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if self.collects_u8() {
+            return Ok(SerializeTuple::collecting(
+                self.inner,
+                len,
+                self.encode_kind,
+            ));
+        }
         let kind = self.encode_kind;
         S::serialize_tuple(self.inner, len).map(|ser| SerializeTuple::new(ser, kind))
     }
@@ -213,10 +401,24 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
         I: IntoIterator,
         <I as IntoIterator>::Item: Serialize,
     {
+        // `Vec<u8>` is collected, which would skip `serialize_seq`.
+        #[cfg(feature = "alloc")]
+        if self.collects_u8() {
+            let iter = iter.into_iter();
+            let len = match iter.size_hint() {
+                (lower, Some(upper)) if lower == upper => Some(upper),
+                _ => None,
+            };
+            let mut seq = self.serialize_seq(len)?;
+            for item in iter {
+                ser::SerializeSeq::serialize_element(&mut seq, &item)?;
+            }
+            return ser::SerializeSeq::end(seq);
+        }
         let kind = self.encode_kind;
         let iter = iter
             .into_iter()
-            .map(|item| BytesSerializeSized::new(item, kind.clone()));
+            .map(|item| BytesSerializeSized::<_, N>::new(item, kind.clone()));
         self.inner.collect_seq(iter)
     }
 
@@ -229,46 +431,128 @@ impl<S: Serializer> Serializer for ByteFmtSerializer<S> {
         let kind = self.encode_kind;
         let iter = iter.into_iter().map(|(k, v)| {
             (
-                BytesSerializeSized::new(k, kind.clone()),
-                BytesSerializeSized::new(v, kind.clone()),
+                BytesSerializeSized::<_, N>::new(k, kind.clone()),
+                BytesSerializeSized::<_, N>::new(v, kind.clone()),
             )
         });
         self.inner.collect_map(iter)
     }
 
-    #[cfg(any(feature = "std", feature = "alloc"))]
-    fn collect_str<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn collect_str<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: Display,
+        T: ?Sized + Display,
     {
-        self.inner.serialize_str(&value.to_string())
+        S::collect_str(self.inner, value)
     }
 
-    #[cfg(not(any(feature = "std", feature = "alloc")))]
-    fn collect_str<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<S: Serializer, const N: usize> ByteFmtSerializer<S, N> {
+    /// Whether sequences are collected while their elements are `u8`, to be encoded as bytes.
+    /// Formats that aren't human readable keep them as they are, and so does an encoding that
+    /// writes bytes as sequences anyway.
+    #[cfg(feature = "alloc")]
+    fn collects_u8(&self) -> bool {
+        match &self.encode_kind {
+            ByteFormat::Sequences(fmt) => {
+                !matches!(**fmt, ByteFormat::Array) && self.inner.is_human_readable()
+            }
+            _ => false,
+        }
+    }
+
+    /// Writes values of [`bytes`](crate::bytes) fields while they are encoded when they don't
+    /// fit the stack buffer, instead of encoding them into a `String` first.
+    #[cfg(feature = "bytes")]
+    fn serialize_streamed(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        let streams = match &self.encode_kind {
+            ByteFormat::Base64(_) | ByteFormat::Hex | ByteFormat::YamlBinary { .. } => self
+                .encode_kind
+                .encoded_len(v.len())
+                .is_some_and(|len| len > N),
+            _ => false,
+        };
+        // Formats may collect the pieces in a `String`, which wouldn't be wiped.
+        if !streams || cfg!(feature = "zeroize") {
+            return self.serialize_bytes(v);
+        }
+        let fmt = self.encode_kind.clone();
+        self.serialize_encoded(&Encoded {
+            fmt: &fmt,
+            bytes: v,
+        })
+    }
+
+    /// Writes an encoded value, as a string unless it needs a YAML tag.
+    fn serialize_encoded<T>(self, encoded: &T) -> Result<S::Ok, S::Error>
     where
-        T: Display,
+        T: ?Sized + Serialize,
     {
-        S::collect_str(self.inner, value)
+        #[cfg(feature = "yaml")]
+        if let (ByteFormat::YamlBinary { tagged: true }, true) = (&self.encode_kind, self.yaml_tag)
+        {
+            // `serde_yaml` can only write local tags, so this is `!binary`.
+            let tagged = TaggedValue {
+                tag: Tag::new("binary"),
+                value: serde_yaml::to_value(encoded).map_err(S::Error::custom)?,
+            };
+            let result = tagged.serialize(self.inner);
+            if let Value::String(encoded) = tagged.value {
+                drop(Wiped(encoded));
+            }
+            return result;
+        }
+        encoded.serialize(self.inner)
     }
+}
 
-    fn is_human_readable(&self) -> bool {
-        self.inner.is_human_readable()
+/// Bytes that are encoded while they are written, for serializing without an allocator and
+/// for `bytes` fields.
+#[cfg(any(not(feature = "alloc"), feature = "bytes"))]
+struct Encoded<'a> {
+    fmt: &'a ByteFormat,
+    bytes: &'a [u8],
+}
+
+#[cfg(any(not(feature = "alloc"), feature = "bytes"))]
+impl Display for Encoded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt.encode_to(self.bytes, f)
+    }
+}
+
+#[cfg(any(not(feature = "alloc"), feature = "bytes"))]
+impl ser::Serialize for Encoded<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
     }
 }
 
-pub struct BytesSerialize<'a, T: ?Sized> {
+pub struct BytesSerialize<'a, T: ?Sized, const N: usize> {
     value: &'a T,
     fmt: ByteFormat,
+    /// Cleared for the content of a newtype variant, whose name is already the YAML tag.
+    yaml_tag: bool,
 }
 
-impl<'a, T: ?Sized> BytesSerialize<'a, T> {
+impl<'a, T: ?Sized, const N: usize> BytesSerialize<'a, T, N> {
+    /// Wraps an element of a compound value, which can be tagged again.
     fn new(value: &'a T, fmt: ByteFormat) -> Self {
-        BytesSerialize { value, fmt }
+        BytesSerialize {
+            value,
+            fmt,
+            yaml_tag: true,
+        }
     }
 }
 
-impl<'a, T: ?Sized> ser::Serialize for BytesSerialize<'a, T>
+impl<'a, T: ?Sized, const N: usize> ser::Serialize for BytesSerialize<'a, T, N>
 where
     T: ser::Serialize,
 {
@@ -278,26 +562,27 @@ where
     {
         ser::Serialize::serialize(
             self.value,
-            ByteFmtSerializer {
+            ByteFmtSerializer::<_, N> {
                 inner: serializer,
                 encode_kind: self.fmt.clone(),
+                yaml_tag: self.yaml_tag,
             },
         )
     }
 }
 
-struct BytesSerializeSized<T> {
+struct BytesSerializeSized<T, const N: usize> {
     value: T,
     fmt: ByteFormat,
 }
 
-impl<T> BytesSerializeSized<T> {
+impl<T, const N: usize> BytesSerializeSized<T, N> {
     fn new(value: T, fmt: ByteFormat) -> Self {
         BytesSerializeSized { value, fmt }
     }
 }
 
-impl<T> ser::Serialize for BytesSerializeSized<T>
+impl<T, const N: usize> ser::Serialize for BytesSerializeSized<T, N>
 where
     T: ser::Serialize,
 {
@@ -307,29 +592,44 @@ where
     {
         ser::Serialize::serialize(
             &self.value,
-            ByteFmtSerializer {
+            ByteFmtSerializer::<_, N> {
                 inner: serializer,
                 encode_kind: self.fmt.clone(),
+                yaml_tag: true,
             },
         )
     }
 }
 
-pub struct SerializeSeq<S> {
-    ser: S,
+pub struct SerializeSeq<S: Serializer, const N: usize> {
+    /// Set once the sequence was started.
+    ser: Option<S::SerializeSeq>,
     fmt: ByteFormat,
+    #[cfg(feature = "alloc")]
+    collecting: Option<Collecting<S, Option<usize>>>,
 }
 
-impl<S> SerializeSeq<S> {
-    fn new(ser: S, fmt: ByteFormat) -> Self {
-        SerializeSeq { ser, fmt }
+impl<S: Serializer, const N: usize> SerializeSeq<S, N> {
+    fn new(ser: S::SerializeSeq, fmt: ByteFormat) -> Self {
+        SerializeSeq {
+            ser: Some(ser),
+            fmt,
+            #[cfg(feature = "alloc")]
+            collecting: None,
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn collecting(inner: S, len: Option<usize>, fmt: ByteFormat) -> Self {
+        SerializeSeq {
+            ser: None,
+            fmt,
+            collecting: Some(Collecting::new(inner, len)),
+        }
     }
 }
 
-impl<S> ser::SerializeSeq for SerializeSeq<S>
-where
-    S: ser::SerializeSeq,
-{
+impl<S: Serializer, const N: usize> ser::SerializeSeq for SerializeSeq<S, N> {
     type Ok = S::Ok;
     type Error = S::Error;
 
@@ -337,33 +637,65 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser
-            .serialize_element(&BytesSerialize::new(value, self.fmt.clone()))
+        #[cfg(feature = "alloc")]
+        if let Some(collecting) = &mut self.collecting {
+            if collecting.push(value) {
+                return Ok(());
+            }
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(collecting) = self.collecting.take() {
+            let mut ser = S::serialize_seq(collecting.inner, collecting.len)?;
+            for b in collecting.bytes.iter() {
+                ser.serialize_element(b)?;
+            }
+            self.ser = Some(ser);
+        }
+        let ser = self.ser.as_mut().expect("started unless collecting");
+        ser.serialize_element(&BytesSerialize::<_, N>::new(value, self.fmt.clone()))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.ser.end()
+        #[cfg(feature = "alloc")]
+        if let Some(collecting) = self.collecting {
+            if collecting.bytes.is_empty() {
+                return S::serialize_seq(collecting.inner, Some(0))?.end();
+            }
+            return collecting.end::<N>(self.fmt);
+        }
+        self.ser.expect("started unless collecting").end()
     }
 }
 
-pub struct SerializeTuple<S> {
-    ser: S,
+pub struct SerializeTuple<S: Serializer, const N: usize> {
+    /// Set once the tuple was started.
+    ser: Option<S::SerializeTuple>,
     fmt: ByteFormat,
+    #[cfg(feature = "alloc")]
+    collecting: Option<Collecting<S, usize>>,
 }
 
-impl<S> SerializeTuple<S> {
-    fn new(serialize_tuple: S, fmt: ByteFormat) -> Self {
+impl<S: Serializer, const N: usize> SerializeTuple<S, N> {
+    fn new(serialize_tuple: S::SerializeTuple, fmt: ByteFormat) -> Self {
         SerializeTuple {
-            ser: serialize_tuple,
+            ser: Some(serialize_tuple),
             fmt,
+            #[cfg(feature = "alloc")]
+            collecting: None,
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn collecting(inner: S, len: usize, fmt: ByteFormat) -> Self {
+        SerializeTuple {
+            ser: None,
+            fmt,
+            collecting: Some(Collecting::new(inner, len)),
         }
     }
 }
 
-impl<S> ser::SerializeTuple for SerializeTuple<S>
-where
-    S: ser::SerializeTuple,
-{
+impl<S: Serializer, const N: usize> ser::SerializeTuple for SerializeTuple<S, N> {
     type Ok = S::Ok;
     type Error = S::Error;
 
@@ -371,21 +703,269 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser
-            .serialize_element(&BytesSerialize::new(value, self.fmt.clone()))
+        #[cfg(feature = "alloc")]
+        if let Some(collecting) = &mut self.collecting {
+            if collecting.push(value) {
+                return Ok(());
+            }
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(collecting) = self.collecting.take() {
+            let mut ser = S::serialize_tuple(collecting.inner, collecting.len)?;
+            for b in collecting.bytes.iter() {
+                ser.serialize_element(b)?;
+            }
+            self.ser = Some(ser);
+        }
+        let ser = self.ser.as_mut().expect("started unless collecting");
+        ser.serialize_element(&BytesSerialize::<_, N>::new(value, self.fmt.clone()))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.ser.end()
+        #[cfg(feature = "alloc")]
+        if let Some(collecting) = self.collecting {
+            if collecting.bytes.is_empty() {
+                return S::serialize_tuple(collecting.inner, 0)?.end();
+            }
+            return collecting.end::<N>(self.fmt);
+        }
+        self.ser.expect("started unless collecting").end()
+    }
+}
+
+/// The start of a sequence or tuple, collected while its elements are `u8`. `len` is the
+/// length it is started with if one isn't.
+#[cfg(feature = "alloc")]
+struct Collecting<S, L> {
+    inner: S,
+    len: L,
+    bytes: Wiped<Vec<u8>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Serializer, L> Collecting<S, L> {
+    fn new(inner: S, len: L) -> Self {
+        Collecting {
+            inner,
+            len,
+            bytes: Wiped(Vec::new()),
+        }
+    }
+
+    /// Collects `value` if it is a `u8`.
+    fn push<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> bool {
+        match value.serialize(U8Probe) {
+            Ok(b) => {
+                wipe::extend(&mut self.bytes, &[b]);
+                true
+            }
+            Err(NotU8) => false,
+        }
+    }
+
+    /// Writes the collected bytes in `fmt`.
+    fn end<const N: usize>(self, fmt: ByteFormat) -> Result<S::Ok, S::Error> {
+        let ser = ByteFmtSerializer::<_, N> {
+            inner: self.inner,
+            encode_kind: fmt,
+            yaml_tag: true,
+        };
+        ser.serialize_bytes(&self.bytes)
+    }
+}
+
+/// Serializes `u8` to itself and fails for anything else.
+#[cfg(feature = "alloc")]
+struct U8Probe;
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct NotU8;
+
+#[cfg(feature = "alloc")]
+impl Display for NotU8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("not a u8")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ser::StdError for NotU8 {}
+
+#[cfg(feature = "alloc")]
+impl ser::Error for NotU8 {
+    fn custom<T: Display>(_: T) -> Self {
+        NotU8
+    }
+}
+
+/// Fails for everything but `u8`.
+#[cfg(feature = "alloc")]
+macro_rules! not_u8 {
+    ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, NotU8> {
+                Err(NotU8)
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl Serializer for U8Probe {
+    type Ok = u8;
+    type Error = NotU8;
+    type SerializeSeq = ser::Impossible<u8, NotU8>;
+    type SerializeTuple = ser::Impossible<u8, NotU8>;
+    type SerializeTupleStruct = ser::Impossible<u8, NotU8>;
+    type SerializeTupleVariant = ser::Impossible<u8, NotU8>;
+    type SerializeMap = ser::Impossible<u8, NotU8>;
+    type SerializeStruct = ser::Impossible<u8, NotU8>;
+    type SerializeStructVariant = ser::Impossible<u8, NotU8>;
+
+    fn serialize_u8(self, v: u8) -> Result<u8, NotU8> {
+        Ok(v)
+    }
+
+    not_u8! {
+        serialize_bool(bool) -> u8;
+        serialize_i8(i8) -> u8;
+        serialize_i16(i16) -> u8;
+        serialize_i32(i32) -> u8;
+        serialize_i64(i64) -> u8;
+        serialize_u16(u16) -> u8;
+        serialize_u32(u32) -> u8;
+        serialize_u64(u64) -> u8;
+        serialize_f32(f32) -> u8;
+        serialize_f64(f64) -> u8;
+        serialize_char(char) -> u8;
+        serialize_str(&str) -> u8;
+        serialize_bytes(&[u8]) -> u8;
+        serialize_none() -> u8;
+        serialize_unit() -> u8;
+        serialize_unit_struct(&'static str) -> u8;
+        serialize_unit_variant(&'static str, u32, &'static str) -> u8;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<u8, NotU8> {
+        Err(NotU8)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<u8, NotU8> {
+        Err(NotU8)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<u8, NotU8> {
+        Err(NotU8)
     }
 }
 
-pub struct SerializeTupleStruct<S> {
+/// Hands the bytes of a [`bytes`](crate::bytes) field to the adapter, and fails for anything
+/// else.
+#[cfg(feature = "bytes")]
+struct Streaming<S, const N: usize>(ByteFmtSerializer<S, N>);
+
+/// Fails for everything but bytes.
+#[cfg(feature = "bytes")]
+macro_rules! not_bytes {
+    ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, S::Error> {
+                Err(S::Error::custom("expected bytes"))
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "bytes")]
+impl<S: Serializer, const N: usize> Serializer for Streaming<S, N> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = ser::Impossible<S::Ok, S::Error>;
+    type SerializeTuple = ser::Impossible<S::Ok, S::Error>;
+    type SerializeTupleStruct = ser::Impossible<S::Ok, S::Error>;
+    type SerializeTupleVariant = ser::Impossible<S::Ok, S::Error>;
+    type SerializeMap = ser::Impossible<S::Ok, S::Error>;
+    type SerializeStruct = ser::Impossible<S::Ok, S::Error>;
+    type SerializeStructVariant = ser::Impossible<S::Ok, S::Error>;
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.0.serialize_streamed(v)
+    }
+
+    not_bytes! {
+        serialize_bool(bool) -> S::Ok;
+        serialize_i8(i8) -> S::Ok;
+        serialize_i16(i16) -> S::Ok;
+        serialize_i32(i32) -> S::Ok;
+        serialize_i64(i64) -> S::Ok;
+        serialize_u8(u8) -> S::Ok;
+        serialize_u16(u16) -> S::Ok;
+        serialize_u32(u32) -> S::Ok;
+        serialize_u64(u64) -> S::Ok;
+        serialize_f32(f32) -> S::Ok;
+        serialize_f64(f64) -> S::Ok;
+        serialize_char(char) -> S::Ok;
+        serialize_str(&str) -> S::Ok;
+        serialize_none() -> S::Ok;
+        serialize_unit() -> S::Ok;
+        serialize_unit_struct(&'static str) -> S::Ok;
+        serialize_unit_variant(&'static str, u32, &'static str) -> S::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<S::Ok, S::Error> {
+        Err(S::Error::custom("expected bytes"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<S::Ok, S::Error> {
+        Err(S::Error::custom("expected bytes"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<S::Ok, S::Error> {
+        Err(S::Error::custom("expected bytes"))
+    }
+}
+
+pub struct SerializeTupleStruct<S, const N: usize> {
     ser: S,
     fmt: ByteFormat,
 }
 
-impl<S> SerializeTupleStruct<S> {
+impl<S, const N: usize> SerializeTupleStruct<S, N> {
     fn new(serialize_tuple_struct: S, fmt: ByteFormat) -> Self {
         SerializeTupleStruct {
             ser: serialize_tuple_struct,
@@ -394,7 +974,7 @@ impl<S> SerializeTupleStruct<S> {
     }
 }
 
-impl<S> ser::SerializeTupleStruct for SerializeTupleStruct<S>
+impl<S, const N: usize> ser::SerializeTupleStruct for SerializeTupleStruct<S, N>
 where
     S: ser::SerializeTupleStruct,
 {
@@ -406,7 +986,7 @@ where
         T: ?Sized + ser::Serialize,
     {
         self.ser
-            .serialize_field(&BytesSerialize::new(value, self.fmt.clone()))
+            .serialize_field(&BytesSerialize::<_, N>::new(value, self.fmt.clone()))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -414,12 +994,12 @@ where
     }
 }
 
-pub struct SerializeTupleVariant<S> {
+pub struct SerializeTupleVariant<S, const N: usize> {
     ser: S,
     kind: ByteFormat,
 }
 
-impl<S> SerializeTupleVariant<S> {
+impl<S, const N: usize> SerializeTupleVariant<S, N> {
     fn new(serialize_tuple_variant: S, kind: ByteFormat) -> Self {
         SerializeTupleVariant {
             ser: serialize_tuple_variant,
@@ -428,7 +1008,7 @@ impl<S> SerializeTupleVariant<S> {
     }
 }
 
-impl<S> ser::SerializeTupleVariant for SerializeTupleVariant<S>
+impl<S, const N: usize> ser::SerializeTupleVariant for SerializeTupleVariant<S, N>
 where
     S: ser::SerializeTupleVariant,
 {
@@ -440,7 +1020,7 @@ where
         T: ?Sized + ser::Serialize,
     {
         self.ser
-            .serialize_field(&BytesSerialize::new(value, self.kind.clone()))
+            .serialize_field(&BytesSerialize::<_, N>::new(value, self.kind.clone()))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -448,12 +1028,12 @@ where
     }
 }
 
-pub struct SerializeMap<S> {
+pub struct SerializeMap<S, const N: usize> {
     ser: S,
     fmt: ByteFormat,
 }
 
-impl<S> SerializeMap<S> {
+impl<S, const N: usize> SerializeMap<S, N> {
     fn new(serialize_map: S, fmt: ByteFormat) -> Self {
         SerializeMap {
             ser: serialize_map,
@@ -462,7 +1042,7 @@ impl<S> SerializeMap<S> {
     }
 }
 
-impl<S> ser::SerializeMap for SerializeMap<S>
+impl<S, const N: usize> ser::SerializeMap for SerializeMap<S, N>
 where
     S: ser::SerializeMap,
 {
@@ -474,7 +1054,7 @@ where
         T: ?Sized + ser::Serialize,
     {
         self.ser
-            .serialize_key(&BytesSerialize::new(key, self.fmt.clone()))
+            .serialize_key(&BytesSerialize::<_, N>::new(key, self.fmt.clone()))
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -482,7 +1062,7 @@ where
         T: ?Sized + ser::Serialize,
     {
         self.ser
-            .serialize_value(&BytesSerialize::new(value, self.fmt.clone()))
+            .serialize_value(&BytesSerialize::<_, N>::new(value, self.fmt.clone()))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -495,24 +1075,24 @@ where
         V: ?Sized + ser::Serialize,
     {
         self.ser.serialize_entry(
-            &BytesSerialize::new(key, self.fmt.clone()),
-            &BytesSerialize::new(value, self.fmt.clone()),
+            &BytesSerialize::<_, N>::new(key, self.fmt.clone()),
+            &BytesSerialize::<_, N>::new(value, self.fmt.clone()),
         )
     }
 }
 
-pub struct SerializeStruct<S> {
+pub struct SerializeStruct<S, const N: usize> {
     ser: S,
     fmt: ByteFormat,
 }
 
-impl<S> SerializeStruct<S> {
+impl<S, const N: usize> SerializeStruct<S, N> {
     fn new(ser: S, fmt: ByteFormat) -> Self {
         SerializeStruct { ser, fmt }
     }
 }
 
-impl<S: ser::SerializeStruct> ser::SerializeStruct for SerializeStruct<S> {
+impl<S: ser::SerializeStruct, const N: usize> ser::SerializeStruct for SerializeStruct<S, N> {
     type Ok = S::Ok;
     type Error = S::Error;
 
@@ -520,8 +1100,10 @@ impl<S: ser::SerializeStruct> ser::SerializeStruct for SerializeStruct<S> {
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser
-            .serialize_field(key, &BytesSerialize::new(value, self.fmt.clone()))
+        self.ser.serialize_field(
+            key,
+            &BytesSerialize::<_, N>::new(value, self.fmt.field(key)),
+        )
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -533,12 +1115,12 @@ impl<S: ser::SerializeStruct> ser::SerializeStruct for SerializeStruct<S> {
     }
 }
 
-pub struct SerializeStructVariant<S> {
+pub struct SerializeStructVariant<S, const N: usize> {
     ser: S,
     fmt: ByteFormat,
 }
 
-impl<S> SerializeStructVariant<S> {
+impl<S, const N: usize> SerializeStructVariant<S, N> {
     fn new(serialize_struct_variant: S, fmt: ByteFormat) -> Self {
         SerializeStructVariant {
             ser: serialize_struct_variant,
@@ -547,7 +1129,7 @@ impl<S> SerializeStructVariant<S> {
     }
 }
 
-impl<S> ser::SerializeStructVariant for SerializeStructVariant<S>
+impl<S, const N: usize> ser::SerializeStructVariant for SerializeStructVariant<S, N>
 where
     S: ser::SerializeStructVariant,
 {
@@ -558,8 +1140,10 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser
-            .serialize_field(key, &BytesSerialize::new(value, self.fmt.clone()))
+        self.ser.serialize_field(
+            key,
+            &BytesSerialize::<_, N>::new(value, self.fmt.field(key)),
+        )
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
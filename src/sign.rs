@@ -0,0 +1,121 @@
+//! Signing bytes with HMAC-SHA256, for values that pass through untrusted hands.
+//!
+//! The 32 byte HMAC of the bytes is appended to them before they are encoded. A deserializer
+//! set up with the key checks it in constant time and removes it, and fails on values that
+//! were changed or signed with another key.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use serde_bytes_repr::{sign::Hmac, ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Page {
+//!     #[serde(with = "serde_bytes")]
+//!     cursor: Vec<u8>,
+//! }
+//!
+//! let hmac = Hmac::sha256(b"server secret");
+//! let page = Page { cursor: vec![0, 0, 0, 42] };
+//! let mut out = vec![];
+//! let mut ser = serde_json::Serializer::new(&mut out);
+//! page.serialize(ByteFmtSerializer::hex(&mut ser).sign(hmac.clone()))
+//!     .unwrap();
+//! let json = String::from_utf8(out).unwrap();
+//! // The bytes followed by 32 bytes of signature.
+//! assert!(json.starts_with(r#"{"cursor":"0000002a"#));
+//! assert_eq!(r#"{"cursor":""#.len() + 2 * (4 + 32) + 2, json.len());
+//!
+//! let edited = json.replacen("2a", "2b", 1);
+//! let mut de = serde_json::Deserializer::from_str(&edited);
+//! let de = ByteFmtDeserializer::new_hex(&mut de).verify(hmac);
+//! let err = Page::deserialize(de).err().unwrap();
+//! assert_eq!(
+//!     "invalid value: bytes with an invalid signature, expected bytes signed with a known key \
+//!      in `/cursor` at line 1 column 84",
+//!     err.to_string()
+//! );
+//! ```
+use crate::{wipe::Wiped, ByteFormat};
+use alloc::vec::Vec;
+use core::fmt;
+use hmac::Mac;
+use sha2::Sha256;
+
+type HmacSha256 = hmac::Hmac<Sha256>;
+
+/// Bytes of the signature appended to values.
+const SIGNATURE_LEN: usize = 32;
+
+/// The keys for [`ByteFmtSerializer::sign`](crate::ByteFmtSerializer::sign) and
+/// [`ByteFmtDeserializer::verify`](crate::ByteFmtDeserializer::verify).
+#[derive(Clone)]
+pub struct Hmac {
+    /// The key values are signed with, followed by those that are only used for verifying.
+    keys: Vec<HmacSha256>,
+}
+
+impl Hmac {
+    /// HMAC-SHA256 with `key`, which signs values and verifies them.
+    pub fn sha256(key: &[u8]) -> Self {
+        Hmac {
+            keys: alloc::vec![new(key)],
+        }
+    }
+
+    /// Also accepts values signed with `key`, like one that was replaced by the current key.
+    pub fn old_key(mut self, key: &[u8]) -> Self {
+        self.keys.push(new(key));
+        self
+    }
+
+    /// `v` with its signature appended.
+    pub(crate) fn sign(&self, v: &[u8]) -> Vec<u8> {
+        let signature = self.keys[0].clone().chain_update(v).finalize().into_bytes();
+        let mut signed = Vec::with_capacity(v.len() + SIGNATURE_LEN);
+        signed.extend_from_slice(v);
+        signed.extend_from_slice(&signature);
+        signed
+    }
+
+    /// Checks the signature at the end of `v` against every key and removes it, `None` if no
+    /// key matches.
+    pub(crate) fn strip(&self, v: Vec<u8>) -> Option<Vec<u8>> {
+        let mut v = Wiped(v);
+        let len = v.len().checked_sub(SIGNATURE_LEN)?;
+        let (bytes, signature) = v.split_at(len);
+        // Every key is tried, so the time doesn't tell which one matched.
+        let valid = self.keys.iter().fold(false, |valid, key| {
+            key.clone()
+                .chain_update(bytes)
+                .verify_slice(signature)
+                .is_ok()
+                | valid
+        });
+        if !valid {
+            return None;
+        }
+        v.truncate(len);
+        Some(v.into_inner())
+    }
+}
+
+/// HMAC accepts keys of any length.
+fn new(key: &[u8]) -> HmacSha256 {
+    HmacSha256::new_from_slice(key).unwrap()
+}
+
+/// Only shows the number of keys.
+impl fmt::Debug for Hmac {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hmac")
+            .field("keys", &self.keys.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An encoding along with an [`Hmac`].
+#[derive(Clone)]
+pub(crate) struct Signing {
+    pub(crate) fmt: ByteFormat,
+    pub(crate) hmac: Hmac,
+}
@@ -0,0 +1,79 @@
+//! Base64 through `base64-simd` and HEX through `faster-hex`, which pick SSE4.1, AVX2 or NEON
+//! code at runtime.
+//!
+//! Base64 is only accelerated for engines that accept exactly the same input. Values either
+//! crate rejects are decoded again by the scalar code, so errors are the same with and without
+//! the feature.
+
+use base64::{
+    alphabet::{self, Alphabet},
+    engine::{Config, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use base64_simd::{Base64, Out};
+
+/// The `base64-simd` variants matching an engine.
+#[derive(Clone, Copy)]
+pub(crate) struct Base64Simd {
+    encode: &'static Base64,
+    /// `None` when the engine accepts both padded and unpadded input, or non-zero trailing bits.
+    decode: Option<&'static Base64>,
+}
+
+impl Base64Simd {
+    /// Returns `None` for alphabets other than the standard and URL-safe ones.
+    pub(crate) fn new(alphabet: &Alphabet, config: &GeneralPurposeConfig) -> Option<Self> {
+        let (padded, unpadded) = if *alphabet == alphabet::STANDARD {
+            (&base64_simd::STANDARD, &base64_simd::STANDARD_NO_PAD)
+        } else if *alphabet == alphabet::URL_SAFE {
+            (&base64_simd::URL_SAFE, &base64_simd::URL_SAFE_NO_PAD)
+        } else {
+            return None;
+        };
+
+        // `base64` has no getters for the decoding options, so they are read back from how an
+        // engine with the same configuration decodes a few probes: "QQ" has no padding, and the
+        // last character of "QR" has trailing bits set.
+        let engine = GeneralPurpose::new(&alphabet::STANDARD, *config);
+        let accepts = |probe: &str| engine.decode_slice(probe, &mut [0; 3]).is_ok();
+        let trailing_bits = accepts("QR==") || accepts("QR");
+        let decode = match (accepts("QQ=="), accepts("QQ"), trailing_bits) {
+            (true, false, false) => Some(padded),
+            (false, true, false) => Some(unpadded),
+            _ => None,
+        };
+        let encode = if config.encode_padding() {
+            padded
+        } else {
+            unpadded
+        };
+        Some(Base64Simd { encode, decode })
+    }
+
+    /// Encodes `v` into `buf`, which has to fit the encoded value.
+    pub(crate) fn encode(&self, v: &[u8], buf: &mut [u8]) -> usize {
+        self.encode.encode(v, Out::from_slice(buf)).len()
+    }
+
+    /// Decodes `v` into `out`, `None` if the value should go through the scalar engine.
+    pub(crate) fn decode(&self, v: &[u8], out: &mut [u8]) -> Option<usize> {
+        let simd = self.decode?;
+        // `base64-simd` panics instead of failing when `out` is too short.
+        if simd.decoded_length(v).ok()? > out.len() {
+            return None;
+        }
+        Some(simd.decode(v, Out::from_slice(out)).ok()?.len())
+    }
+}
+
+/// Encodes `v` as lowercase HEX into `buf`, which has to be exactly twice as long.
+pub(crate) fn encode_hex(v: &[u8], buf: &mut [u8]) {
+    faster_hex::hex_encode(v, buf).expect("buffer fits the encoded value");
+}
+
+/// Decodes HEX into `out`, `None` if the value should go through the `hex` crate.
+pub(crate) fn decode_hex(v: &[u8], out: &mut [u8]) -> Option<usize> {
+    let decoded = out.get_mut(..v.len() / 2)?;
+    faster_hex::hex_decode(v, decoded).ok()?;
+    Some(decoded.len())
+}
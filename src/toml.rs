@@ -0,0 +1,91 @@
+//! Helpers for reading and writing TOML documents with a chosen byte representation.
+//!
+//! TOML has no type for binary data, so the `toml` crate reports strings through `visit_str`
+//! even when bytes are requested. [`ByteFmtDeserializer`] decodes those strings when the target
+//! type asks for bytes.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Demo {
+//!     #[serde(with = "serde_bytes")]
+//!     bytes: Vec<u8>,
+//! }
+//!
+//! let demo = Demo { bytes: b"testing".to_vec() };
+//! let toml = serde_bytes_repr::toml::to_string_hex(&demo).unwrap();
+//! assert_eq!("bytes = \"74657374696e67\"\n", toml);
+//!
+//! let demo: Demo = serde_bytes_repr::toml::from_str_hex(&toml).unwrap();
+//! assert_eq!(b"testing", demo.bytes.as_slice());
+//! ```
+use crate::{ByteFmtDeserializer, ByteFmtSerializer, ByteFormat};
+use base64::{alphabet::Alphabet, engine::GeneralPurposeConfig};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Serializes `value` to a TOML string with bytes encoded as Base64.
+pub fn to_string_base64<T>(
+    value: &T,
+    alphabet: Alphabet,
+    config: GeneralPurposeConfig,
+) -> Result<String, ::toml::ser::Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(value, ByteFormat::base64(&alphabet, config))
+}
+
+/// Serializes `value` to a TOML string with bytes encoded as HEX.
+pub fn to_string_hex<T>(value: &T) -> Result<String, ::toml::ser::Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(value, ByteFormat::Hex)
+}
+
+/// Deserializes a TOML string with bytes encoded as Base64.
+pub fn from_str_base64<T>(
+    s: &str,
+    alphabet: Alphabet,
+    config: GeneralPurposeConfig,
+) -> Result<T, ::toml::de::Error>
+where
+    T: DeserializeOwned,
+{
+    from_str(s, ByteFormat::base64(&alphabet, config))
+}
+
+/// Deserializes a TOML string with bytes encoded as HEX.
+pub fn from_str_hex<T>(s: &str) -> Result<T, ::toml::de::Error>
+where
+    T: DeserializeOwned,
+{
+    from_str(s, ByteFormat::Hex)
+}
+
+fn to_string<T>(value: &T, fmt: ByteFormat) -> Result<String, ::toml::ser::Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = String::new();
+    let ser: ByteFmtSerializer<_> = ByteFmtSerializer {
+        inner: ::toml::Serializer::new(&mut out),
+        encode_kind: fmt,
+        yaml_tag: true,
+    };
+    value.serialize(ser)?;
+    Ok(out)
+}
+
+fn from_str<T>(s: &str, fmt: ByteFormat) -> Result<T, ::toml::de::Error>
+where
+    T: DeserializeOwned,
+{
+    let de = ByteFmtDeserializer {
+        inner: ::toml::Deserializer::new(s),
+        fmt,
+        plain_scalars: false,
+    };
+    T::deserialize(de)
+}
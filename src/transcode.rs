@@ -0,0 +1,561 @@
+//! Streaming conversion of a document from one byte representation to another, without
+//! knowing its Rust types.
+//!
+//! Without a type, every encoded value just looks like a string, so the byte fields are given
+//! as JSON Pointers (RFC 6901) extended with globs: `*` and `?` match within a key or index,
+//! and a `**` segment matches any number of levels, like `/users/*/key` or `/**/*_key`.
+//!
+//! Strings at those paths are decoded with the deserializer's encoding and options, like
+//! checksums, decompression or envelopes, and written with the serializer's. Options that
+//! select struct fields match the keys on the path, newtype structs don't show in documents.
+//! Everything else is copied as is, except tagged YAML values like `!tag value`, which are
+//! written like externally tagged enums: as a map with the tag as its only key.
+//!
+//! ```rust
+//! use serde_bytes_repr::{transcode::transcode, ByteFmtDeserializer, ByteFmtSerializer};
+//!
+//! let json = br#"{"users":[{"name":"a","key":"74657374696e67"}]}"#;
+//! let mut json_de = serde_json::Deserializer::from_slice(json);
+//! let de = ByteFmtDeserializer::new_hex(&mut json_de);
+//!
+//! let mut out = vec![];
+//! let mut json_ser = serde_json::Serializer::new(&mut out);
+//! let base64_config = base64::engine::GeneralPurposeConfig::new();
+//! let ser = ByteFmtSerializer::base64(&mut json_ser, base64::alphabet::URL_SAFE, base64_config);
+//!
+//! transcode(de, ser, &["/users/*/key"]).unwrap();
+//! assert_eq!(
+//!     r#"{"users":[{"name":"a","key":"dGVzdGluZw=="}]}"#,
+//!     String::from_utf8(out).unwrap()
+//! );
+//! ```
+use crate::{
+    deserializer,
+    path::{DisplayPath, Pointer, Segment},
+    wipe::Wiped,
+    ByteFmtDeserializer, ByteFmtSerializer, ByteFormat,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    cell::{Cell, RefCell},
+    fmt,
+};
+use serde::{
+    de,
+    ser::{self, SerializeMap, SerializeSeq},
+};
+
+/// Streams `deserializer` into `serializer`, re-encoding the strings at `byte_fields`.
+///
+/// The input has to be self-describing, the transcoder drives it with `deserialize_any`.
+/// Values at `byte_fields` must be encoded strings (integer sequences for
+/// [`Encoding::array`](crate::Encoding::array)) or `null`, anything else is an error.
+pub fn transcode<'de, D, S>(
+    deserializer: ByteFmtDeserializer<D>,
+    serializer: ByteFmtSerializer<S>,
+    byte_fields: &[&str],
+) -> Result<S::Ok, Error<D::Error, S::Error>>
+where
+    D: de::Deserializer<'de>,
+    S: ser::Serializer,
+{
+    let byte_fields = byte_fields
+        .iter()
+        .map(|pointer| Pointer::parse(pointer))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| Error::Ser(ser::Error::custom(err)))?;
+    let ctx = Context {
+        decoding: deserializer::Context::for_transcoding(deserializer.fmt),
+        byte_fields,
+        path: RefCell::new(Vec::new()),
+        key: RefCell::new(None),
+        failed: Cell::new(None),
+    };
+    let ser_error = RefCell::new(None);
+    let visitor = Visitor {
+        ser: serializer,
+        ctx: &ctx,
+        is_key: false,
+        ser_error: Some(&ser_error),
+    };
+    deserializer.inner.deserialize_any(visitor).map_err(|err| {
+        match (ctx.failed.get(), ser_error.into_inner()) {
+            (_, Some(ser_error)) => Error::Ser(ser_error),
+            (Some(Side::Ser), None) => Error::Ser(ser::Error::custom(err)),
+            _ => Error::De(err),
+        }
+    })
+}
+
+/// Error of [`transcode`]: the deserializer's if reading or decoding the input failed first,
+/// the serializer's otherwise.
+///
+/// The deserializer and the serializer call each other for nested values, so an error that
+/// happened below the top level has to pass through the other one. It does so as a message,
+/// and is returned as an error of the side it came from, like the deserializer's with its
+/// position in the input.
+#[derive(Debug)]
+pub enum Error<D, S> {
+    /// Reading the input failed, or a value in it didn't decode.
+    De(D),
+    /// Writing the output failed.
+    Ser(S),
+}
+
+impl<D: fmt::Display, S: fmt::Display> fmt::Display for Error<D, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::De(err) => err.fmt(f),
+            Error::Ser(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D, S> std::error::Error for Error<D, S>
+where
+    D: std::error::Error + 'static,
+    S: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::De(err) => Some(err),
+            Error::Ser(err) => Some(err),
+        }
+    }
+}
+
+struct Context {
+    /// The encoding of the input, along with the options of the deserializer.
+    decoding: deserializer::Context,
+    byte_fields: Vec<Pointer>,
+    /// Path to the value being transcoded.
+    path: RefCell<Vec<Segment>>,
+    /// The last map key that was transcoded, pushed to `path` for its value.
+    key: RefCell<Option<Segment>>,
+    /// Which side failed first.
+    failed: Cell<Option<Side>>,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    De,
+    Ser,
+}
+
+impl Context {
+    fn is_bytes(&self) -> bool {
+        let path = self.path.borrow();
+        self.byte_fields
+            .iter()
+            .any(|pointer| pointer.matches(&path))
+    }
+
+    fn in_path<T>(&self, segment: Segment, f: impl FnOnce() -> T) -> T {
+        self.path.borrow_mut().push(segment);
+        let result = f();
+        self.path.borrow_mut().pop();
+        result
+    }
+
+    fn error<E: fmt::Display>(&self, err: E) -> String {
+        alloc::format!("{}: {}", DisplayPath(&self.path.borrow()), err)
+    }
+
+    /// Passes an error of the deserializer on through the serializer.
+    fn d2s<S: ser::Error>(&self, err: impl fmt::Display) -> S {
+        self.fail(Side::De);
+        S::custom(err)
+    }
+
+    /// Passes an error of the serializer on through the deserializer.
+    fn s2d<D: de::Error>(&self, err: impl fmt::Display) -> D {
+        self.fail(Side::Ser);
+        D::custom(err)
+    }
+
+    fn fail(&self, side: Side) {
+        if self.failed.get().is_none() {
+            self.failed.set(Some(side));
+        }
+    }
+}
+
+struct Transcoder<'a, D> {
+    de: RefCell<Option<D>>,
+    ctx: &'a Context,
+    is_key: bool,
+}
+
+impl<'a, D> Transcoder<'a, D> {
+    fn new(de: D, ctx: &'a Context, is_key: bool) -> Self {
+        Transcoder {
+            de: RefCell::new(Some(de)),
+            ctx,
+            is_key,
+        }
+    }
+}
+
+impl<'de, 'a, D> ser::Serialize for Transcoder<'a, D>
+where
+    D: de::Deserializer<'de>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.de
+            .borrow_mut()
+            .take()
+            .expect("Transcoder may only be serialized once")
+            .deserialize_any(Visitor {
+                ser: serializer,
+                ctx: self.ctx,
+                is_key: self.is_key,
+                ser_error: None,
+            })
+            .map_err(|err| self.ctx.d2s(err))
+    }
+}
+
+struct Visitor<'a, S: ser::Serializer> {
+    ser: S,
+    ctx: &'a Context,
+    is_key: bool,
+    /// Where the outermost visitor keeps its serializer's error, if it is the first one.
+    ser_error: Option<&'a RefCell<Option<S::Error>>>,
+}
+
+impl<'a, S: ser::Serializer> Visitor<'a, S> {
+    fn record_key<K: ToString>(&self, key: K) {
+        if self.is_key {
+            *self.ctx.key.borrow_mut() = Some(Segment::Key(key.to_string()));
+        }
+    }
+
+    /// Fails if a value which isn't a string is found at one of the byte fields.
+    fn expect_not_bytes<E>(&self, unexpected: de::Unexpected) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        if !self.is_key && self.ctx.is_bytes() {
+            let err = E::invalid_type(unexpected, &"an encoded string");
+            return Err(E::custom(self.ctx.error(err)));
+        }
+        Ok(())
+    }
+
+    /// Writes with the serializer, passing its error on.
+    fn write<E: de::Error>(
+        self,
+        write: impl FnOnce(S) -> Result<S::Ok, S::Error>,
+    ) -> Result<S::Ok, E> {
+        let (ctx, ser_error) = (self.ctx, self.ser_error);
+        write(self.ser).map_err(|err| Self::s2d(ctx, ser_error, err))
+    }
+
+    /// Passes an error of the serializer on through the deserializer.
+    fn s2d<E: de::Error>(
+        ctx: &Context,
+        ser_error: Option<&RefCell<Option<S::Error>>>,
+        err: S::Error,
+    ) -> E {
+        match ser_error {
+            Some(ser_error) if ctx.failed.get().is_none() => {
+                let converted = ctx.s2d(&err);
+                *ser_error.borrow_mut() = Some(err);
+                converted
+            }
+            _ => ctx.s2d(err),
+        }
+    }
+}
+
+impl<'de, 'a, S> de::Visitor<'de> for Visitor<'a, S>
+where
+    S: ser::Serializer,
+{
+    type Value = S::Ok;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.expect_not_bytes(de::Unexpected::Bool(v))?;
+        self.record_key(v);
+        self.write(|ser| ser.serialize_bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.expect_not_bytes(de::Unexpected::Signed(v))?;
+        self.record_key(v);
+        self.write(|ser| ser.serialize_i64(v))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.expect_not_bytes(de::Unexpected::Other("128-bit integer"))?;
+        self.record_key(v);
+        self.write(|ser| ser.serialize_i128(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.expect_not_bytes(de::Unexpected::Unsigned(v))?;
+        self.record_key(v);
+        self.write(|ser| ser.serialize_u64(v))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.expect_not_bytes(de::Unexpected::Other("128-bit integer"))?;
+        self.record_key(v);
+        self.write(|ser| ser.serialize_u128(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.expect_not_bytes(de::Unexpected::Float(v))?;
+        self.record_key(v);
+        self.write(|ser| ser.serialize_f64(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        let mut buf = [0; 4];
+        self.visit_str(v.encode_utf8(&mut buf))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.record_key(v);
+        if let ByteFormat::Array = self.ctx.decoding.encoding() {
+            self.expect_not_bytes(de::Unexpected::Str(v))?;
+        }
+        if !self.is_key && self.ctx.is_bytes() {
+            let decoded = self
+                .ctx
+                .decoding
+                .decode_at::<E>(v.as_bytes(), &self.ctx.path.borrow())
+                .map_err(|err| E::custom(self.ctx.error(err)))?;
+            return self.write(|ser| ser.serialize_bytes(&Wiped(decoded)));
+        }
+        self.write(|ser| ser.serialize_str(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.write(|ser| ser.serialize_unit())
+    }
+
+    fn visit_none<E>(self) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.write(|ser| ser.serialize_none())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<S::Ok, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let transcoder = Transcoder::new(deserializer, self.ctx, self.is_key);
+        self.write(|ser| ser.serialize_some(&transcoder))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<S::Ok, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // The name isn't known, and self-describing formats don't write it anyway.
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<S::Ok, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let ctx = self.ctx;
+        if let (ByteFormat::Array, true) = (ctx.decoding.encoding(), ctx.is_bytes()) {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(b) = seq
+                .next_element::<u8>()
+                .map_err(|err| de::Error::custom(ctx.error(err)))?
+            {
+                bytes.push(b);
+            }
+            let bytes = Wiped(
+                ctx.decoding
+                    .verify_array::<A::Error>(bytes)
+                    .map_err(|err| de::Error::custom(ctx.error(err)))?,
+            );
+            return self.write(|ser| ser.serialize_bytes(&bytes));
+        }
+        self.expect_not_bytes(de::Unexpected::Seq)?;
+        let ser_error = self.ser_error;
+        let fail = |err| Self::s2d(ctx, ser_error, err);
+        let mut ser = self.ser.serialize_seq(seq.size_hint()).map_err(fail)?;
+        let mut index = 0;
+        while ctx
+            .in_path(Segment::Index(index), || {
+                seq.next_element_seed(SeqSeed { ser: &mut ser, ctx })
+            })?
+            .is_some()
+        {
+            index += 1;
+        }
+        ser.end().map_err(fail)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<S::Ok, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let ctx = self.ctx;
+        // Blob references and envelopes in place of bytes.
+        if !self.is_key && ctx.is_bytes() && ctx.decoding.reads_objects() {
+            let decoded = Wiped(
+                ctx.decoding
+                    .decode_object(map, &ctx.path.borrow())
+                    .map_err(|err| de::Error::custom(ctx.error(err)))?,
+            );
+            return self.write(|ser| ser.serialize_bytes(&decoded));
+        }
+        self.expect_not_bytes(de::Unexpected::Map)?;
+        let ser_error = self.ser_error;
+        let fail = |err| Self::s2d(ctx, ser_error, err);
+        let mut ser = self.ser.serialize_map(map.size_hint()).map_err(fail)?;
+        while map.next_key_seed(KeySeed { ser: &mut ser, ctx })?.is_some() {
+            let key = ctx
+                .key
+                .borrow_mut()
+                .take()
+                .unwrap_or_else(|| Segment::Key(String::new()));
+            ctx.in_path(key, || {
+                map.next_value_seed(ValueSeed { ser: &mut ser, ctx })
+            })?;
+        }
+        ser.end().map_err(fail)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<S::Ok, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        // `serde_yaml` reports tagged values (`!tag value`) as enums.
+        let ctx = self.ctx;
+        let (tag, variant) = data.variant::<String>()?;
+        if let (false, true, ByteFormat::YamlBinary { .. }) =
+            (self.is_key, ctx.is_bytes(), ctx.decoding.encoding())
+        {
+            if tag == "binary" {
+                let encoded: String = de::VariantAccess::newtype_variant(variant)?;
+                return self.visit_str(&encoded);
+            }
+        }
+        self.expect_not_bytes(de::Unexpected::Other("tagged value"))?;
+        let ser_error = self.ser_error;
+        let fail = |err| Self::s2d(ctx, ser_error, err);
+        let mut ser = self.ser.serialize_map(Some(1)).map_err(fail)?;
+        ser.serialize_key(&tag).map_err(fail)?;
+        ctx.in_path(Segment::Key(tag), || {
+            de::VariantAccess::newtype_variant_seed(variant, ValueSeed { ser: &mut ser, ctx })
+        })?;
+        ser.end().map_err(fail)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<S::Ok, E>
+    where
+        E: de::Error,
+    {
+        self.write(|ser| ser.serialize_bytes(v))
+    }
+}
+
+struct SeqSeed<'a, 'b, S> {
+    ser: &'b mut S,
+    ctx: &'a Context,
+}
+
+impl<'de, 'a, 'b, S> de::DeserializeSeed<'de> for SeqSeed<'a, 'b, S>
+where
+    S: ser::SerializeSeq,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.ser
+            .serialize_element(&Transcoder::new(deserializer, self.ctx, false))
+            .map_err(|err| self.ctx.s2d(err))
+    }
+}
+
+struct KeySeed<'a, 'b, S> {
+    ser: &'b mut S,
+    ctx: &'a Context,
+}
+
+impl<'de, 'a, 'b, S> de::DeserializeSeed<'de> for KeySeed<'a, 'b, S>
+where
+    S: ser::SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.ser
+            .serialize_key(&Transcoder::new(deserializer, self.ctx, true))
+            .map_err(|err| self.ctx.s2d(err))
+    }
+}
+
+struct ValueSeed<'a, 'b, S> {
+    ser: &'b mut S,
+    ctx: &'a Context,
+}
+
+impl<'de, 'a, 'b, S> de::DeserializeSeed<'de> for ValueSeed<'a, 'b, S>
+where
+    S: ser::SerializeMap,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.ser
+            .serialize_value(&Transcoder::new(deserializer, self.ctx, false))
+            .map_err(|err| self.ctx.s2d(err))
+    }
+}
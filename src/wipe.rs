@@ -0,0 +1,70 @@
+//! Wiping the buffers the adapters hold bytes or their encoding in before they are freed,
+//! which only happens with the `zeroize` feature.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+pub(crate) trait Wipe {
+    /// Overwrites the buffer with zeros, including the spare capacity of a `Vec` or `String`.
+    fn wipe(&mut self);
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize + ?Sized> Wipe for T {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T: ?Sized> Wipe for T {
+    #[inline]
+    fn wipe(&mut self) {}
+}
+
+/// A buffer that is wiped when dropped.
+pub(crate) struct Wiped<T: Wipe>(pub(crate) T);
+
+#[cfg(feature = "alloc")]
+impl<T: Wipe + Default> Wiped<T> {
+    /// Hands the buffer over to code that wipes it itself, or that keeps it.
+    pub(crate) fn into_inner(mut self) -> T {
+        core::mem::take(&mut self.0)
+    }
+}
+
+impl<T: Wipe> Deref for Wiped<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Wipe> DerefMut for Wiped<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Wipe> Drop for Wiped<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+/// Appends `bytes` to `v`. Unlike `Vec::extend_from_slice`, a buffer that is too short is
+/// wiped after it was copied into a larger one.
+#[cfg(feature = "alloc")]
+pub(crate) fn extend(v: &mut Vec<u8>, bytes: &[u8]) {
+    #[cfg(feature = "zeroize")]
+    if v.capacity() - v.len() < bytes.len() {
+        let capacity = (v.len() + bytes.len()).max(2 * v.capacity()).max(8);
+        let mut grown = Vec::with_capacity(capacity);
+        grown.extend_from_slice(v);
+        core::mem::swap(v, &mut grown);
+        grown.wipe();
+    }
+    v.extend_from_slice(bytes);
+}
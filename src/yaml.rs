@@ -0,0 +1,116 @@
+//! Helpers for reading and writing YAML documents with a chosen byte representation.
+//!
+//! `serde_yaml` reports scalars through `visit_str` even when bytes are requested.
+//! [`ByteFmtDeserializer`] decodes those strings when the target type asks for bytes.
+//!
+//! Besides the Base64 and HEX helpers, [`to_string_binary`] and [`from_str_binary`] write and
+//! read binary scalars. `serde_yaml` can only emit local tags, so these are written as `!binary`
+//! rather than YAML's standard `!!binary`; both are read.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Demo {
+//!     #[serde(with = "serde_bytes")]
+//!     bytes: Vec<u8>,
+//! }
+//!
+//! let demo = Demo { bytes: b"testing".to_vec() };
+//! let yaml = serde_bytes_repr::yaml::to_string_hex(&demo).unwrap();
+//! assert_eq!("bytes: '74657374696e67'\n", yaml);
+//!
+//! let demo: Demo = serde_bytes_repr::yaml::from_str_hex(&yaml).unwrap();
+//! assert_eq!(b"testing", demo.bytes.as_slice());
+//! ```
+use crate::{ByteFmtDeserializer, ByteFmtSerializer, ByteFormat};
+use base64::{alphabet::Alphabet, engine::GeneralPurposeConfig};
+use serde::{Deserialize, Serialize};
+
+/// Serializes `value` to a YAML string with bytes encoded as Base64.
+pub fn to_string_base64<T>(
+    value: &T,
+    alphabet: Alphabet,
+    config: GeneralPurposeConfig,
+) -> Result<String, serde_yaml::Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(value, ByteFormat::base64(&alphabet, config))
+}
+
+/// Serializes `value` to a YAML string with bytes encoded as HEX.
+pub fn to_string_hex<T>(value: &T) -> Result<String, serde_yaml::Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(value, ByteFormat::Hex)
+}
+
+/// Serializes `value` to a YAML string with bytes written as binary scalars.
+///
+/// The scalars are tagged with the local `!binary` tag, since `serde_yaml` can't write the
+/// standard `!!binary` shorthand. Tools which only accept `!!binary` won't recognize them.
+pub fn to_string_binary<T>(value: &T) -> Result<String, serde_yaml::Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(value, ByteFormat::YamlBinary { tagged: true })
+}
+
+/// Deserializes a YAML string with bytes written as `!!binary` or `!binary` scalars. Untagged
+/// Base64 scalars are accepted too.
+pub fn from_str_binary<'de, T>(s: &'de str) -> Result<T, serde_yaml::Error>
+where
+    T: Deserialize<'de>,
+{
+    from_str(s, ByteFormat::YamlBinary { tagged: false })
+}
+
+/// Deserializes a YAML string with bytes encoded as Base64.
+pub fn from_str_base64<'de, T>(
+    s: &'de str,
+    alphabet: Alphabet,
+    config: GeneralPurposeConfig,
+) -> Result<T, serde_yaml::Error>
+where
+    T: Deserialize<'de>,
+{
+    from_str(s, ByteFormat::base64(&alphabet, config))
+}
+
+/// Deserializes a YAML string with bytes encoded as HEX.
+pub fn from_str_hex<'de, T>(s: &'de str) -> Result<T, serde_yaml::Error>
+where
+    T: Deserialize<'de>,
+{
+    from_str(s, ByteFormat::Hex)
+}
+
+fn to_string<T>(value: &T, fmt: ByteFormat) -> Result<String, serde_yaml::Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let bytefmt_ser: ByteFmtSerializer<_> = ByteFmtSerializer {
+        inner: &mut ser,
+        encode_kind: fmt,
+        yaml_tag: true,
+    };
+    value.serialize(bytefmt_ser)?;
+    // The YAML emitter only ever writes valid UTF-8.
+    Ok(String::from_utf8(out).expect("serde_yaml emitted invalid UTF-8"))
+}
+
+fn from_str<'de, T>(s: &'de str, fmt: ByteFormat) -> Result<T, serde_yaml::Error>
+where
+    T: Deserialize<'de>,
+{
+    let de = ByteFmtDeserializer {
+        inner: serde_yaml::Deserializer::from_str(s),
+        fmt,
+        plain_scalars: true,
+    };
+    T::deserialize(de)
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "blob")]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{
+    blob::{BlobStore, FsBlobStore},
+    preview::Preview,
+    redact::Redaction,
+    Encoding,
+};
+
+mod common;
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Artifact {
+    #[serde(with = "serde_bytes")]
+    name: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+fn artifact() -> Artifact {
+    Artifact {
+        name: b"ab".to_vec(),
+        data: b"foo".to_vec(),
+    }
+}
+
+const FOO_KEY: &str = "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae";
+
+#[derive(Default)]
+struct MemoryStore(Mutex<HashMap<String, Vec<u8>>>);
+
+impl BlobStore for MemoryStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.0.lock().unwrap().insert(key.into(), bytes.into());
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> io::Result<Vec<u8>> {
+        let blobs = self.0.lock().unwrap();
+        blobs
+            .get(key)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found"))
+    }
+}
+
+fn to_json(value: &Artifact, store: Arc<dyn BlobStore>) -> String {
+    common::to_json(value, |ser| ser.blob_store(store, 2))
+}
+
+fn from_json(json: &str, store: Arc<dyn BlobStore>) -> Result<Artifact, serde_json::Error> {
+    common::from_json(json, |de| de.blob_store(store))
+}
+
+#[test]
+fn blob_roundtrip() {
+    let store = Arc::new(MemoryStore::default());
+    let json = to_json(&artifact(), store.clone());
+    assert_eq!(
+        format!(r#"{{"name":"6162","data":{{"$blob":"{}"}}}}"#, FOO_KEY),
+        json
+    );
+    assert_eq!(b"foo", &store.get(FOO_KEY).unwrap()[..]);
+    assert_eq!(artifact(), from_json(&json, store).unwrap());
+}
+
+#[test]
+fn blob_fs_store() {
+    let dir = std::env::temp_dir().join(format!("serde-bytes-repr-blob-{}", std::process::id()));
+    let store = Arc::new(FsBlobStore::new(&dir));
+    let json = to_json(&artifact(), store.clone());
+    // Storing the same content again keeps the existing file.
+    assert_eq!(json, to_json(&artifact(), store.clone()));
+    let file = dir.join(FOO_KEY.replace(':', "-"));
+    assert_eq!(b"foo", &std::fs::read(&file).unwrap()[..]);
+    assert_eq!(artifact(), from_json(&json, store).unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn blob_inline_values() {
+    // Plain encoded values are still accepted.
+    let store = Arc::new(MemoryStore::default());
+    let json = r#"{"name":"6162","data":"666f6f"}"#;
+    assert_eq!(artifact(), from_json(json, store).unwrap());
+}
+
+#[test]
+fn blob_hash_mismatch() {
+    let store = Arc::new(MemoryStore::default());
+    let json = to_json(&artifact(), store.clone());
+    store.put(FOO_KEY, b"bar").unwrap();
+    assert_eq!(
+        format!(
+            "blob `{}` doesn't match its hash in `/data` at line 1 column 105",
+            FOO_KEY
+        ),
+        from_json(&json, store).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn blob_missing() {
+    let store = Arc::new(MemoryStore::default());
+    let json = format!(r#"{{"name":"6162","data":{{"$blob":"{}"}}}}"#, FOO_KEY);
+    assert_eq!(
+        format!(
+            "blob `{}`: not found in `/data` at line 1 column 105",
+            FOO_KEY
+        ),
+        from_json(&json, store).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn blob_invalid_reference() {
+    let store = Arc::new(MemoryStore::default());
+    let json = r#"{"name":"6162","data":{"$blob":"sha256:../../etc/passwd"}}"#;
+    assert_eq!(
+        "invalid blob reference `sha256:../../etc/passwd` in `/data` at line 1 column 57",
+        from_json(json, store.clone()).unwrap_err().to_string()
+    );
+    let json = r#"{"name":"6162","data":{"other":"x"}}"#;
+    assert_eq!(
+        "invalid type: map, expected encoded bytes or a blob reference in `/data` at line 1 column 30",
+        from_json(json, store).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn blob_redacted() {
+    let store = Arc::new(MemoryStore::default());
+    let json = common::to_json(&artifact(), |ser| {
+        ser.redact(Redaction::marker().fields(["data"]))
+            .blob_store(store.clone(), 0)
+    });
+    assert_eq!(
+        r#"{"name":{"$blob":"sha256:fb8e20fc2e4c3f248c60c39bd652f3c1347298bb977b8b4d5903b85055620603"},"data":"<redacted 3 bytes>"}"#,
+        json
+    );
+    assert!(store.get(FOO_KEY).is_err());
+}
+
+#[test]
+fn blob_preview_array() {
+    let store = Arc::new(MemoryStore::default());
+    let json = common::try_to_json(&artifact(), Encoding::array(), |ser| {
+        ser.blob_store(store.clone(), 2).preview(Preview::head(1))
+    });
+    assert_eq!(
+        r#"{"name":"61…(+1 bytes)","data":"66…(+2 bytes)"}"#,
+        json.unwrap()
+    );
+    assert!(store.get(FOO_KEY).is_err());
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn blob_yaml_binary() {
+    use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+
+    let store = Arc::new(MemoryStore::default());
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::yaml_binary(&mut ser).blob_store(store.clone(), 2);
+    artifact().serialize(ser).unwrap();
+    let yaml = String::from_utf8(out).unwrap();
+    assert_eq!(format!("name: YWI=\ndata:\n  $blob: {}\n", FOO_KEY), yaml);
+    let de = serde_yaml::Deserializer::from_str(&yaml);
+    let de = ByteFmtDeserializer::new_yaml_binary(de).blob_store(store);
+    assert_eq!(artifact(), Artifact::deserialize(de).unwrap());
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "bytes")]
+use base64::{alphabet, engine::general_purpose};
+use bytes::{Bytes, BytesMut};
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Counts the bytes allocated on this thread.
+struct Counting;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Packet {
+    #[serde(with = "serde_bytes_repr::bytes")]
+    header: BytesMut,
+    #[serde(with = "serde_bytes_repr::bytes")]
+    payload: Bytes,
+}
+
+fn packet() -> Packet {
+    Packet {
+        header: BytesMut::from(&b"\x01\x02"[..]),
+        payload: Bytes::from_static(b"ping"),
+    }
+}
+
+#[test]
+fn bytes_round_trip() {
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::base64(&mut ser, alphabet::STANDARD, general_purpose::PAD);
+    packet().serialize(ser).unwrap();
+    assert_eq!(br#"{"header":"AQI=","payload":"cGluZw=="}"#, &out[..]);
+
+    let mut de = serde_json::Deserializer::from_slice(&out);
+    let de = ByteFmtDeserializer::new_base64(&mut de, alphabet::STANDARD, general_purpose::PAD);
+    assert_eq!(packet(), Packet::deserialize(de).unwrap());
+
+    // Without the adapter, bytes are integer arrays.
+    let json = serde_json::to_string(&packet()).unwrap();
+    assert_eq!(r#"{"header":[1,2],"payload":[112,105,110,103]}"#, json);
+    assert_eq!(packet(), serde_json::from_str(&json).unwrap());
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn bytes_streamed_yaml_binary() {
+    // Longer than the stack buffer once encoded.
+    let packet = Packet {
+        header: BytesMut::from(&[7; 4][..]),
+        payload: Bytes::from(vec![0; 120]),
+    };
+    let yaml = serde_bytes_repr::yaml::to_string_binary(&packet).unwrap();
+    assert_eq!(
+        format!(
+            "header: !binary BwcHBw==\npayload: !binary |-\n  {0}\n  {0}\n  {1}\n",
+            "A".repeat(76),
+            "A".repeat(8)
+        ),
+        yaml
+    );
+    assert_eq!(
+        packet,
+        serde_bytes_repr::yaml::from_str_binary(&yaml).unwrap()
+    );
+}
+
+/// Bytes allocated by `f`.
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (result, ALLOCATED.with(Cell::get) - before)
+}
+
+#[test]
+fn bytes_are_not_copied() {
+    let payload: Vec<u8> = (0..1 << 20).map(|i| i as u8).collect();
+    let json = format!(r#"{{"header":"","payload":"{}"}}"#, hex::encode(&payload));
+    let (packet, allocated) = allocations(|| {
+        let mut de = serde_json::Deserializer::from_str(&json);
+        Packet::deserialize(ByteFmtDeserializer::new_hex(&mut de)).unwrap()
+    });
+    assert_eq!(payload, packet.payload);
+    // The decoded value and a little bookkeeping, not a second copy.
+    assert!(allocated < payload.len() + 1024, "{}", allocated);
+
+    let ((), allocated) = allocations(|| {
+        let mut out = Vec::with_capacity(json.len() + 1024);
+        let mut ser = serde_json::Serializer::new(&mut out);
+        packet.serialize(ByteFmtSerializer::hex(&mut ser)).unwrap();
+        assert_eq!(json.as_bytes(), &out[..]);
+    });
+    // The output, and the encoded value only if it has to be wiped.
+    let copies = if cfg!(feature = "zeroize") { 2 } else { 1 };
+    assert!(allocated <= copies * json.len() + 1024, "{}", allocated);
+}
@@ -0,0 +1,168 @@
+#![cfg(any(feature = "crc32c", feature = "xxhash", feature = "sha2"))]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::checksum::Checksum;
+
+mod common;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Config {
+    #[serde(with = "serde_bytes")]
+    name: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    key: Vec<u8>,
+}
+
+fn config() -> Config {
+    Config {
+        name: b"ab".to_vec(),
+        key: vec![0x0a, 0x0b],
+    }
+}
+
+fn to_json(value: &Config, checksum: Checksum) -> String {
+    common::to_json(value, |ser| ser.checksum(checksum))
+}
+
+fn from_json(json: &str, checksum: Checksum) -> Result<Config, serde_json::Error> {
+    common::from_json(json, |de| de.checksum(checksum))
+}
+
+#[cfg(feature = "crc32c")]
+#[test]
+fn checksum_crc32c() {
+    let json = to_json(&config(), Checksum::crc32c());
+    assert_eq!(
+        format!(
+            r#"{{"name":"6162{}","key":"0a0b{}"}}"#,
+            hex::encode(crc32c::crc32c(b"ab").to_be_bytes()),
+            hex::encode(crc32c::crc32c(&[0x0a, 0x0b]).to_be_bytes())
+        ),
+        json
+    );
+    assert_eq!(config(), from_json(&json, Checksum::crc32c()).unwrap());
+}
+
+#[cfg(feature = "xxhash")]
+#[test]
+fn checksum_xxh3() {
+    let json = to_json(&config(), Checksum::xxh3());
+    let sum = xxhash_rust::xxh3::xxh3_64(&[0x0a, 0x0b]).to_be_bytes();
+    assert!(
+        json.ends_with(&format!(r#""key":"0a0b{}"}}"#, hex::encode(sum))),
+        "{}",
+        json
+    );
+    assert_eq!(config(), from_json(&json, Checksum::xxh3()).unwrap());
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn checksum_sha256_truncated() {
+    let json = to_json(&config(), Checksum::sha256().len(4));
+    // The first 4 bytes of the SHA-256 of "ab".
+    assert_eq!(
+        r#"{"name":"6162fb8e20fc","key":"0a0b"#,
+        &json[..json.len() - 10]
+    );
+    assert_eq!(
+        config(),
+        from_json(&json, Checksum::sha256().len(4)).unwrap()
+    );
+    // The length is part of the configuration.
+    assert!(from_json(&json, Checksum::sha256()).is_err());
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn checksum_mismatch() {
+    let json = to_json(&config(), Checksum::sha256().len(4)).replace("0a0b", "0a0c");
+    assert_eq!(
+        "invalid value: checksum mismatch, expected bytes with a valid sha256 checksum in `/key` \
+         at line 1 column 43",
+        from_json(&json, Checksum::sha256().len(4))
+            .unwrap_err()
+            .to_string()
+    );
+
+    // Values shorter than the checksum can't have one.
+    let json = r#"{"name":"6162","key":""}"#;
+    let err = from_json(json, Checksum::sha256().len(4)).unwrap_err();
+    assert!(err.to_string().contains("in `/name`"), "{}", err);
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn checksum_recovering() {
+    use serde_bytes_repr::ByteFmtDeserializer;
+
+    let json = to_json(&config(), Checksum::sha256().len(4)).replace("6162", "6163");
+    let mut de = serde_json::Deserializer::from_str(&json);
+    let (value, errors) = ByteFmtDeserializer::new_hex(&mut de)
+        .checksum(Checksum::sha256().len(4))
+        .deserialize_recovering::<Config>()
+        .unwrap();
+    assert_eq!(vec![0x0a, 0x0b], value.key);
+    assert!(value.name.is_empty());
+    assert_eq!(1, errors.len());
+    assert_eq!("/name", errors[0].path());
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+#[should_panic(expected = "sha256 checksums are 1 to 32 bytes long")]
+fn checksum_len_zero() {
+    let _ = Checksum::sha256().len(0);
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn checksum_array() {
+    use serde_bytes_repr::Encoding;
+
+    let checksum = || Checksum::sha256().len(2);
+    let json =
+        common::try_to_json(&config(), Encoding::array(), |ser| ser.checksum(checksum())).unwrap();
+    assert_eq!(r#"{"name":[97,98,251,142],"key":[10,11,"#, &json[..37]);
+
+    let from_json = |json: &str| {
+        common::from_json_with::<Config>(json, Encoding::array(), |de| de.checksum(checksum()))
+    };
+    assert_eq!(config(), from_json(&json).unwrap());
+
+    let err = from_json(&json.replace("[97,98,", "[97,99,")).unwrap_err();
+    assert!(err.to_string().contains("in `/name`"), "{}", err);
+}
+
+#[cfg(all(feature = "sha2", feature = "gzip"))]
+#[test]
+fn checksum_compressed() {
+    use serde_bytes_repr::compress::Compression;
+
+    let value = Config {
+        name: vec![],
+        key: b"GET /index.html 200\n".repeat(100),
+    };
+    let json = common::to_json(&value, |ser| {
+        ser.compress(Compression::gzip())
+            .checksum(Checksum::sha256().len(4))
+    });
+    assert!(json.contains(r#""key":"gzip:"#), "{}", json);
+
+    // The checksum covers the bytes before compressing.
+    let decoded = common::from_json(&json, |de| {
+        de.checksum(Checksum::sha256().len(4)).decompress(3000)
+    });
+    assert_eq!(value, decoded.unwrap());
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn checksum_redacted() {
+    use serde_bytes_repr::redact::Redaction;
+
+    let json = common::to_json(&config(), |ser| {
+        ser.redact(Redaction::marker().fields(["key"]))
+            .checksum(Checksum::sha256().len(1))
+    });
+    assert_eq!(r#"{"name":"6162fb","key":"<redacted 2 bytes>"}"#, json);
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_serde-bytes-repr"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+#[test]
+fn json_stdin_to_stdout() {
+    let input = r#"{"users":[{"name":"a","key":"74657374"},{"name":"b","key":null}]}"#;
+    let output = run(
+        &[
+            "--format",
+            "json",
+            "--from",
+            "hex",
+            "--to",
+            "base64",
+            "-f",
+            "/users/*/key",
+        ],
+        input,
+    );
+    let value: serde_json::Value = serde_json::from_str(stdout(&output)).unwrap();
+    assert_eq!(
+        serde_json::json!({ "users": [{ "name": "a", "key": "dGVzdA==" }, { "name": "b", "key": null }] }),
+        value
+    );
+}
+
+#[test]
+fn yaml_arrays_with_globs() {
+    let input = "a_key: [116, 101]\nother: [1, 2]\n";
+    let output = run(
+        &[
+            "--format",
+            "yaml",
+            "--from",
+            "array",
+            "--to",
+            "base64url-nopad",
+            "-f",
+            "/**/*_key",
+        ],
+        input,
+    );
+    assert_eq!("a_key: dGU\nother:\n- 1\n- 2\n", stdout(&output));
+}
+
+#[test]
+fn toml_in_place_keeps_datetimes() {
+    let dir = std::env::temp_dir().join(format!("serde-bytes-repr-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("config.toml");
+    fs::write(
+        &file,
+        "when = 1979-05-27T07:32:00Z\n\n[keys]\nmain = \"74657374\"\n",
+    )
+    .unwrap();
+
+    let output = run(
+        &[
+            "--from",
+            "hex",
+            "--to",
+            "base64",
+            "-f",
+            "/keys/*",
+            "-i",
+            file.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert_eq!("", stdout(&output));
+    assert_eq!(
+        "when = 1979-05-27T07:32:00Z\n\n[keys]\nmain = \"dGVzdA==\"\n",
+        fs::read_to_string(&file).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_reports_fields_not_in_target_encoding() {
+    let args = ["--format", "json", "--check", "--to", "base64", "-f", "/*"];
+    let output = run(&args, r#"{"a":"dGVzdA==","b":null}"#);
+    assert_eq!("", stdout(&output));
+
+    let output = run(&args, r#"{"a":"dGVzdA","b":"74 65"}"#);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("/a: not in canonical form"), "{}", stderr);
+    assert!(stderr.contains("/b: "), "{}", stderr);
+}
+
+#[test]
+fn invalid_field_is_an_error() {
+    let output = run(
+        &[
+            "--format", "json", "--from", "hex", "--to", "base64", "-f", "/a",
+        ],
+        r#"{"a":"zz"}"#,
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: /a: invalid value"), "{}", stderr);
+}
+
+#[test]
+fn help_says_in_place_loses_comments() {
+    let help = run(&["--help"], "");
+    assert!(
+        stdout(&help).contains("comments and formatting in YAML and TOML files are lost"),
+        "{}",
+        stdout(&help)
+    );
+}
@@ -0,0 +1,55 @@
+//! JSON round trips through the adapters, shared by the tests of their options.
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer, Encoding};
+
+pub type Serializer<'a> = ByteFmtSerializer<&'a mut serde_json::Serializer<Vec<u8>>>;
+pub type Deserializer<'a, 'de> =
+    ByteFmtDeserializer<&'a mut serde_json::Deserializer<serde_json::de::StrRead<'de>>>;
+
+/// Writes `value` as JSON with HEX bytes, through the adapter `options` sets up.
+pub fn to_json<T>(value: &T, options: impl FnOnce(Serializer<'_>) -> Serializer<'_>) -> String
+where
+    T: ?Sized + Serialize,
+{
+    try_to_json(value, Encoding::hex(), options).unwrap()
+}
+
+/// Writes `value` as JSON with the bytes in `encoding`, through the adapter `options` sets up.
+pub fn try_to_json<T>(
+    value: &T,
+    encoding: Encoding,
+    options: impl FnOnce(Serializer<'_>) -> Serializer<'_>,
+) -> Result<String, serde_json::Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut ser = serde_json::Serializer::new(Vec::new());
+    value.serialize(options(ByteFmtSerializer::new(&mut ser, encoding)))?;
+    Ok(String::from_utf8(ser.into_inner()).unwrap())
+}
+
+/// Reads a `T` from JSON with HEX bytes, through the adapter `options` sets up.
+pub fn from_json<'de, T>(
+    json: &'de str,
+    options: impl for<'a> FnOnce(Deserializer<'a, 'de>) -> Deserializer<'a, 'de>,
+) -> Result<T, serde_json::Error>
+where
+    T: Deserialize<'de>,
+{
+    from_json_with(json, Encoding::hex(), options)
+}
+
+/// Reads a `T` from JSON with the bytes in `encoding`, through the adapter `options` sets up.
+pub fn from_json_with<'de, T>(
+    json: &'de str,
+    encoding: Encoding,
+    options: impl for<'a> FnOnce(Deserializer<'a, 'de>) -> Deserializer<'a, 'de>,
+) -> Result<T, serde_json::Error>
+where
+    T: Deserialize<'de>,
+{
+    let mut de = serde_json::Deserializer::from_str(json);
+    T::deserialize(options(ByteFmtDeserializer::new(&mut de, encoding)))
+}
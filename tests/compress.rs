@@ -0,0 +1,171 @@
+#![cfg(any(feature = "gzip", feature = "deflate", feature = "zstd"))]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::compress::Compression;
+
+mod common;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Log {
+    #[serde(with = "serde_bytes")]
+    name: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    text: Vec<u8>,
+}
+
+fn log() -> Log {
+    Log {
+        name: b"access".to_vec(),
+        text: b"GET /index.html 200\n".repeat(100),
+    }
+}
+
+fn to_json(value: &Log, compression: Compression) -> String {
+    common::to_json(value, |ser| ser.compress(compression))
+}
+
+fn from_json(json: &str, limit: usize) -> Result<Log, serde_json::Error> {
+    common::from_json(json, |de| de.decompress(limit))
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_gzip() {
+    let json = to_json(&log(), Compression::gzip());
+    assert!(
+        json.starts_with(r#"{"name":"616363657373","text":"gzip:1f8b08"#),
+        "{}",
+        json
+    );
+    assert!(json.len() < 200, "{}", json);
+    assert_eq!(log(), from_json(&json, 2000).unwrap());
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn compress_deflate() {
+    let json = to_json(&log(), Compression::deflate());
+    assert!(json.contains(r#""text":"deflate:"#), "{}", json);
+    assert_eq!(log(), from_json(&json, 2000).unwrap());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn compress_zstd() {
+    let json = to_json(&log(), Compression::zstd());
+    // Zstandard frames start with 28 b5 2f fd.
+    assert!(json.contains(r#""text":"zstd:28b52ffd"#), "{}", json);
+    assert_eq!(log(), from_json(&json, 2000).unwrap());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_threshold() {
+    let json = to_json(&log(), Compression::gzip().threshold(2000));
+    assert_eq!(
+        format!(
+            r#"{{"name":"616363657373","text":"{}"}}"#,
+            hex::encode(log().text)
+        ),
+        json
+    );
+    // Plain values are read as before.
+    assert_eq!(log(), from_json(&json, 0).unwrap());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_incompressible() {
+    let mut state = 1u32;
+    let noise: Vec<u8> = (0..100)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+    let value = Log {
+        name: vec![],
+        text: noise.clone(),
+    };
+    assert_eq!(
+        format!(r#"{{"name":"","text":"{}"}}"#, hex::encode(noise)),
+        to_json(&value, Compression::gzip().threshold(0))
+    );
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_limit() {
+    let json = to_json(&log(), Compression::gzip());
+    assert_eq!(log(), from_json(&json, 2000).unwrap());
+    let err = from_json(&json, 1999).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("decompresses to more than 1999 bytes in `/text` at line 1"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_invalid() {
+    use serde_bytes_repr::ByteFmtDeserializer;
+
+    let json = r#"{"name":"","text":"gzip:1f8b0800"}"#;
+    assert_eq!(
+        "invalid value: invalid gzip data, expected compressed bytes in `/text` at line 1 column 33",
+        from_json(json, 2000).unwrap_err().to_string()
+    );
+
+    // Offsets count from the start of the value.
+    let json = r#"{"name":"","text":"gzip:1f8b0x"}"#;
+    let mut de = serde_json::Deserializer::from_str(json);
+    let err = ByteFmtDeserializer::new_hex(&mut de)
+        .decompress(2000)
+        .deserialize::<Log>()
+        .unwrap_err();
+    assert_eq!(Some(10), err.decode_error().unwrap().offset());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_without_decompress() {
+    let json = to_json(&log(), Compression::gzip());
+    let err = common::from_json::<Log>(&json, |de| de).unwrap_err();
+    assert!(err.to_string().contains("in `/text`"), "{}", err);
+}
+
+#[cfg(all(feature = "gzip", feature = "yaml"))]
+#[test]
+fn compress_yaml_binary() {
+    use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::yaml_binary(&mut ser).compress(Compression::gzip());
+    log().serialize(ser).unwrap();
+    let yaml = String::from_utf8(out).unwrap();
+    assert!(
+        yaml.starts_with("name: YWNjZXNz\ntext: |-\n  gzip:H4sI"),
+        "{}",
+        yaml
+    );
+    let de = serde_yaml::Deserializer::from_str(&yaml);
+    let de = ByteFmtDeserializer::new_yaml_binary(de).decompress(2000);
+    assert_eq!(log(), Log::deserialize(de).unwrap());
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn compress_array() {
+    use serde_bytes_repr::Encoding;
+
+    let err = common::try_to_json(&log(), Encoding::array(), |ser| {
+        ser.compress(Compression::gzip())
+    })
+    .unwrap_err();
+    assert_eq!(
+        "compressed values can't be written as integer arrays",
+        err.to_string()
+    );
+}
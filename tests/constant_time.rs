@@ -0,0 +1,139 @@
+#![cfg(feature = "alloc")]
+use base64::{
+    alphabet::{self, Alphabet},
+    engine::{general_purpose, DecodePaddingMode, GeneralPurposeConfig},
+};
+use serde::Deserialize;
+use serde_bytes::ByteBuf;
+use serde_bytes_repr::{constant_time::ConstantTime, ByteFmtDeserializer, Encoding};
+
+/// Decodes the JSON string `v` as bytes, along with the detailed error if it fails.
+fn decode(encoding: &Encoding, v: &str, constant_time: bool) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_string(v).unwrap();
+    let mut de = serde_json::Deserializer::from_str(&json);
+    let mut de = ByteFmtDeserializer::new(&mut de, encoding.clone());
+    if constant_time {
+        de = de.constant_time(ConstantTime::new());
+    }
+    de.deserialize::<ByteBuf>()
+        .map(ByteBuf::into_vec)
+        .map_err(|err| format!("{} {:?}", err, err.decode_error()))
+}
+
+/// Every string of up to `max_len` of `chars`, after `prefix`.
+fn strings(prefix: &str, chars: &[char], max_len: usize) -> Vec<String> {
+    let mut strings = vec![prefix.to_string()];
+    let mut last = strings.clone();
+    for _ in 0..max_len {
+        last = last
+            .iter()
+            .flat_map(|s| chars.iter().map(move |&c| format!("{}{}", s, c)))
+            .collect();
+        strings.extend(last.iter().cloned());
+    }
+    strings
+}
+
+fn assert_equivalent(encoding: Encoding, inputs: &[String]) {
+    let mut valid = 0;
+    for v in inputs {
+        let expected = decode(&encoding, v, false);
+        valid += expected.is_ok() as usize;
+        assert_eq!(expected, decode(&encoding, v, true), "{:?}", v);
+    }
+    // Both outcomes are covered.
+    assert!(valid > 0 && valid < inputs.len());
+}
+
+fn base64(alphabet: Alphabet, config: GeneralPurposeConfig) -> Encoding {
+    Encoding::base64(alphabet, config)
+}
+
+const BASE64_CHARS: &[char] = &['A', 'B', 'Q', '/', '-', '=', '!'];
+
+#[test]
+fn constant_time_hex() {
+    let mut inputs = strings("", &['0', 'a', 'F', 'g', ' ', 'é'], 5);
+    inputs.extend(strings("9f86d081", &['7', 'c', 'G', 'x'], 3));
+    assert_equivalent(Encoding::hex(), &inputs);
+}
+
+#[test]
+fn constant_time_base64() {
+    let mut inputs = strings("", BASE64_CHARS, 5);
+    inputs.extend(strings("QUJDREVG", BASE64_CHARS, 4));
+    assert_equivalent(base64(alphabet::STANDARD, general_purpose::PAD), &inputs);
+    assert_equivalent(base64(alphabet::URL_SAFE, general_purpose::NO_PAD), &inputs);
+}
+
+#[test]
+fn constant_time_base64_lenient() {
+    let config = GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true);
+    let inputs = strings("", BASE64_CHARS, 5);
+    assert_equivalent(base64(alphabet::STANDARD, config), &inputs);
+    assert_equivalent(base64(alphabet::BCRYPT, general_purpose::NO_PAD), &inputs);
+}
+
+#[test]
+fn constant_time_yaml_binary() {
+    let mut inputs = strings("", &['A', 'Q', 'w', '=', '!', '\n'], 5);
+    inputs.extend(strings("QUJD\nREVG\n", &['Q', '=', '\n', '!'], 5));
+    assert_equivalent(Encoding::yaml_binary(), &inputs);
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct PrivateKey(#[serde(with = "serde_bytes")] Vec<u8>);
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Account {
+    #[serde(with = "serde_bytes")]
+    id: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    seed: Vec<u8>,
+    keys: Vec<PrivateKey>,
+}
+
+fn account(json: &str, constant_time: Option<ConstantTime>) -> Result<Account, String> {
+    let mut de = serde_json::Deserializer::from_str(json);
+    let mut de = ByteFmtDeserializer::new_hex(&mut de);
+    if let Some(constant_time) = constant_time {
+        de = de.constant_time(constant_time);
+    }
+    Account::deserialize(de).map_err(|err| err.to_string())
+}
+
+#[test]
+fn constant_time_selected() {
+    let selection = || {
+        ConstantTime::new()
+            .fields(["seed"])
+            .newtypes(["PrivateKey"])
+    };
+    let json = r#"{"id":"0102","seed":"0a0B","keys":["ff","00ee"]}"#;
+    let expected = Account {
+        id: vec![1, 2],
+        seed: vec![0x0a, 0x0b],
+        keys: vec![PrivateKey(vec![0xff]), PrivateKey(vec![0x00, 0xee])],
+    };
+    assert_eq!(expected, account(json, Some(selection())).unwrap());
+
+    for invalid in [
+        r#"{"id":"0102","seed":"0a0x","keys":[]}"#,
+        r#"{"id":"0102","seed":"0a0","keys":[]}"#,
+        r#"{"id":"0102","seed":"","keys":["ff","0g"]}"#,
+        r#"{"id":"01x2","seed":"","keys":[]}"#,
+    ] {
+        assert_eq!(account(invalid, None), account(invalid, Some(selection())));
+    }
+    assert_eq!(
+        "invalid value: character `g`, expected valid hex character at index 1 in `/keys/1` \
+         at line 1 column 40",
+        account(
+            r#"{"id":"0102","seed":"","keys":["ff","0g"]}"#,
+            Some(selection())
+        )
+        .unwrap_err()
+    );
+}
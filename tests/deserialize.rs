@@ -61,6 +61,25 @@ fn serialize_seq_hex() {
     assert_eq!("testing", deserialized.as_str());
 }
 
+#[test]
+fn deserialize_struct_integer_array() {
+    #[derive(Serialize, Deserialize)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+        buf: serde_bytes::ByteBuf,
+    }
+
+    // Sequences of integers are still read, like without the adapter.
+    let json = br#"{"bytes":[1,2,3],"buf":[1,2,3,4]}"#;
+    let mut json_de = serde_json::Deserializer::from_slice(json);
+    let bytefmt_json_de = ByteFmtDeserializer::new_hex(&mut json_de);
+    let demo: Demo = Demo::deserialize(bytefmt_json_de).unwrap();
+
+    assert_eq!(vec![1, 2, 3], demo.bytes);
+    assert_eq!(vec![1, 2, 3, 4], demo.buf.into_vec());
+}
+
 #[test]
 fn deserialize_option_base64() {
     #[derive(Serialize, Deserialize)]
@@ -124,7 +143,7 @@ fn deserialize_invalid_struct_base64() {
 
     let msg = format!("{}", demo.unwrap_err());
     assert_eq!(
-        "invalid length 5, expected valid base64 length at line 1 column 16",
+        "invalid length 5, expected valid base64 length in `/bytes` at line 1 column 16",
         msg
     );
 
@@ -136,7 +155,7 @@ fn deserialize_invalid_struct_base64() {
     let demo = Demo::deserialize(bytefmt_json_de);
 
     let msg = format!("{}", demo.unwrap_err());
-    assert_eq!("invalid value: character `%`, expected valid base64 character at index 5 at line 1 column 17", msg);
+    assert_eq!("invalid value: character `%`, expected valid base64 character at index 5 in `/bytes` at line 1 column 17", msg);
 
     let json = br#"{"bytes":"123456"}"#;
     let mut json_de = serde_json::Deserializer::from_slice(json);
@@ -147,7 +166,7 @@ fn deserialize_invalid_struct_base64() {
     let demo = Demo::deserialize(bytefmt_json_de);
 
     let msg = format!("{}", demo.unwrap_err());
-    assert_eq!("invalid value: character `6`, expected valid character ending base64 string at line 1 column 17", msg);
+    assert_eq!("invalid value: character `6`, expected valid character ending base64 string in `/bytes` at line 1 column 17", msg);
 }
 
 #[test]
@@ -165,7 +184,7 @@ fn deserialize_invalid_struct_hex() {
 
     let msg = format!("{}", demo.unwrap_err());
     assert_eq!(
-        "invalid length 13, expected even length at line 1 column 24",
+        "invalid length 13, expected even length in `/bytes` at line 1 column 24",
         msg
     );
 
@@ -176,7 +195,234 @@ fn deserialize_invalid_struct_hex() {
 
     let msg = format!("{}", demo.unwrap_err());
     assert_eq!(
-        "invalid value: character `g`, expected valid hex character at index 3 at line 1 column 25",
+        "invalid value: character `g`, expected valid hex character at index 3 in `/bytes` at line 1 column 25",
         msg
     );
 }
+
+#[test]
+fn deserialize_struct_encoding() {
+    #[derive(Serialize, Deserialize)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+    }
+
+    let json = br#"{"bytes":"dGVzdGluZw=="}"#;
+    let mut json_de = serde_json::Deserializer::from_slice(json);
+    let encoding = serde_bytes_repr::Encoding::base64(
+        base64::alphabet::URL_SAFE,
+        base64::engine::GeneralPurposeConfig::new(),
+    );
+    let bytefmt_json_de = ByteFmtDeserializer::new(&mut json_de, encoding);
+    let demo: Demo = Demo::deserialize(bytefmt_json_de).unwrap();
+
+    let deserialized = String::from_utf8(demo.bytes).unwrap();
+    assert_eq!("testing", deserialized.as_str());
+}
+
+#[test]
+#[cfg(not(feature = "alloc"))]
+fn deserialize_too_long_without_alloc() {
+    let json = format!("\"{}\"", "00".repeat(257));
+    let mut json_de = serde_json::Deserializer::from_str(&json);
+    let de = ByteFmtDeserializer::new_hex(&mut json_de);
+    let err = serde_bytes::ByteBuf::deserialize(de).unwrap_err();
+    assert_eq!(
+        "invalid length 514, expected at most 256 bytes once decoded at line 1 column 516",
+        err.to_string()
+    );
+}
+
+#[test]
+#[cfg(not(feature = "alloc"))]
+fn deserialize_base64_buffer_boundary_without_alloc() {
+    use base64::Engine as _;
+
+    let decode = |len: usize| {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(vec![7; len]);
+        let json = format!("\"{}\"", encoded);
+        let mut json_de = serde_json::Deserializer::from_str(&json);
+        let de = ByteFmtDeserializer::new_base64(
+            &mut json_de,
+            base64::alphabet::STANDARD,
+            base64::engine::GeneralPurposeConfig::new(),
+        );
+        serde_bytes::ByteBuf::deserialize(de).map(serde_bytes::ByteBuf::into_vec)
+    };
+    assert_eq!(vec![7; 256], decode(256).unwrap());
+    assert_eq!(vec![7; 255], decode(255).unwrap());
+    assert_eq!(
+        "invalid length 344, expected at most 256 bytes once decoded at line 1 column 346",
+        decode(257).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn deserialize_error_path() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    enum Key {
+        Single(serde_bytes::ByteBuf),
+        Pair {
+            public: serde_bytes::ByteBuf,
+            private: serde_bytes::ByteBuf,
+        },
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Demo {
+        keys: std::collections::BTreeMap<String, Vec<Key>>,
+    }
+
+    let error = |json: &str| {
+        let mut json_de = serde_json::Deserializer::from_str(json);
+        Demo::deserialize(ByteFmtDeserializer::new_hex(&mut json_de))
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        "invalid value: character `x`, expected valid hex character at index 1 in `/keys/a~1b/1/Single` at line 1 column 46",
+        error(r#"{"keys":{"a/b":[{"Single":"00"},{"Single":"0x"}]}}"#)
+    );
+    assert_eq!(
+        "invalid length 3, expected even length in `/keys/c/0/Pair/private` at line 1 column 52",
+        error(r#"{"keys":{"c":[{"Pair":{"public":"00","private":"000"}}]}}"#)
+    );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn deserialize_decode_error() {
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Demo {
+        name: String,
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+    }
+
+    let json = r#"{"name":"a","bytes":"dGVzdGluZ%=="}"#;
+    let mut json_de = serde_json::Deserializer::from_str(json);
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let err =
+        ByteFmtDeserializer::new_base64(&mut json_de, base64::alphabet::STANDARD, base64_config)
+            .deserialize::<Demo>()
+            .unwrap_err();
+    let decode = err.decode_error().unwrap();
+    assert_eq!("/bytes", decode.path());
+    assert_eq!("base64", decode.encoding());
+    assert_eq!(Some(9), decode.offset());
+    assert_eq!("GVzdGluZ%==", decode.excerpt());
+    assert_eq!(
+        "/bytes: invalid value: character `%`, expected valid base64 character at index 9",
+        decode.to_string()
+    );
+    assert_eq!(
+        "invalid value: character `%`, expected valid base64 character at index 9 in `/bytes` at line 1 column 34",
+        err.to_string()
+    );
+
+    let mut json_de = serde_json::Deserializer::from_str(r#"{"name":1,"bytes":""}"#);
+    let err = ByteFmtDeserializer::new_hex(&mut json_de)
+        .deserialize::<Demo>()
+        .unwrap_err();
+    assert!(err.decode_error().is_none());
+
+    #[cfg(feature = "std")]
+    {
+        use std::error::Error as _;
+        let source = err.source().unwrap();
+        assert!(source.is::<serde_json::Error>());
+        assert_eq!(err.to_string(), source.to_string());
+        assert_eq!(err.to_string(), err.into_inner().to_string());
+    }
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+fn deserialize_recovering() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        first: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        second: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        third: Option<Vec<u8>>,
+    }
+
+    let json = r#"{"first":"0g","second":"0102","third":"123"}"#;
+    let mut json_de = serde_json::Deserializer::from_str(json);
+    let (demo, errors) = ByteFmtDeserializer::new_hex(&mut json_de)
+        .deserialize_recovering::<Demo>()
+        .unwrap();
+    assert_eq!(
+        Demo {
+            first: vec![],
+            second: vec![1, 2],
+            third: Some(vec![]),
+        },
+        demo
+    );
+    let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        vec![
+            "/first: invalid value: character `g`, expected valid hex character at index 1",
+            "/third: invalid length 3, expected even length",
+        ],
+        errors
+    );
+
+    // Errors other than decoding still fail.
+    let json = r#"{"first":"0g","second":1,"third":null}"#;
+    let mut json_de = serde_json::Deserializer::from_str(json);
+    let err = ByteFmtDeserializer::new_hex(&mut json_de)
+        .deserialize_recovering::<Demo>()
+        .unwrap_err();
+    assert_eq!(
+        "invalid type: integer `1`, expected byte array at line 1 column 24",
+        err.to_string()
+    );
+    assert!(err.decode_error().is_none());
+    assert_eq!("/first", err.recovered()[0].path());
+
+    // Fixed-length values don't take the empty bytes.
+    use std::convert::TryInto;
+
+    struct Key;
+    impl<'de> serde::de::Visitor<'de> for Key {
+        type Value = [u8; 4];
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("4 bytes")
+        }
+
+        fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<[u8; 4], E> {
+            v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+        }
+    }
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Fixed {
+        #[serde(deserialize_with = "fixed")]
+        key: [u8; 4],
+    }
+    fn fixed<'de, D: serde::de::Deserializer<'de>>(de: D) -> Result<[u8; 4], D::Error> {
+        de.deserialize_bytes(Key)
+    }
+    let mut json_de = serde_json::Deserializer::from_str(r#"{"key":"0g0b0c0d"}"#);
+    let err = ByteFmtDeserializer::new_hex(&mut json_de)
+        .deserialize_recovering::<Fixed>()
+        .unwrap_err();
+    assert_eq!(
+        "invalid length 0, expected 4 bytes at line 1 column 17",
+        err.to_string()
+    );
+    let recovered: Vec<_> = err.recovered().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        vec!["/key: invalid value: character `g`, expected valid hex character at index 1"],
+        recovered
+    );
+}
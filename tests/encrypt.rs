@@ -0,0 +1,322 @@
+#![cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{
+    encrypt::{Encryption, Key},
+    ByteFmtDeserializer,
+};
+
+mod common;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Config {
+    #[serde(with = "serde_bytes")]
+    id: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    password: Vec<u8>,
+}
+
+fn config() -> Config {
+    Config {
+        id: vec![1, 2],
+        password: b"hunter2".to_vec(),
+    }
+}
+
+fn key(id: &str, byte: u8) -> Key {
+    #[cfg(feature = "aes-gcm")]
+    return Key::aes256_gcm(id, &[byte; 32]);
+    #[cfg(not(feature = "aes-gcm"))]
+    return Key::chacha20_poly1305(id, &[byte; 32]);
+}
+
+fn to_json<T: Serialize>(value: &T, encryption: Encryption) -> String {
+    common::to_json(value, |ser| ser.encrypt(encryption))
+}
+
+fn from_json<'de, T: Deserialize<'de>>(
+    json: &'de str,
+    encryption: Encryption,
+) -> Result<T, serde_json::Error> {
+    common::from_json(json, |de| de.decrypt(encryption))
+}
+
+/// The sealed value of the `password` field.
+fn password(json: &str) -> &str {
+    let start = json.find(r#""password":""#).unwrap() + 12;
+    &json[start..json.len() - 2]
+}
+
+#[test]
+fn encrypt_roundtrip() {
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let json = to_json(&config(), encryption.clone());
+    assert!(
+        json.starts_with(r#"{"id":"0102","password":"k1:"#),
+        "{}",
+        json
+    );
+    // 12 bytes of nonce, 7 of ciphertext and 16 of tag.
+    let sealed = password(&json).strip_prefix("k1:").unwrap();
+    assert_eq!(
+        35,
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, sealed)
+            .unwrap()
+            .len()
+    );
+    assert_eq!(config(), from_json(&json, encryption).unwrap());
+}
+
+#[test]
+fn encrypt_fresh_nonces() {
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let json = to_json(&config(), encryption.clone());
+    assert_ne!(json, to_json(&config(), encryption));
+}
+
+#[test]
+fn encrypt_everything() {
+    let encryption = Encryption::new(key("k1", 1));
+    let json = to_json(&config(), encryption.clone());
+    assert!(json.starts_with(r#"{"id":"k1:"#), "{}", json);
+    assert_eq!(config(), from_json(&json, encryption).unwrap());
+}
+
+#[test]
+fn encrypt_newtypes() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Password(#[serde(with = "serde_bytes")] Vec<u8>);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct User {
+        #[serde(with = "serde_bytes")]
+        name: Vec<u8>,
+        passwords: Vec<Password>,
+    }
+
+    let user = User {
+        name: b"ab".to_vec(),
+        passwords: vec![Password(b"x".to_vec())],
+    };
+    let encryption = Encryption::new(key("k1", 1)).newtypes(["Password"]);
+    let json = to_json(&user, encryption.clone());
+    assert!(
+        json.starts_with(r#"{"name":"6162","passwords":["k1:"#),
+        "{}",
+        json
+    );
+    assert_eq!(user, from_json(&json, encryption.clone()).unwrap());
+
+    let plain = r#"{"name":"6162","passwords":["78"]}"#;
+    assert_eq!(
+        "invalid value: unencrypted bytes, expected bytes encrypted with a known key in \
+         `/passwords/0` at line 1 column 32",
+        from_json::<User>(plain, encryption)
+            .unwrap_err()
+            .to_string()
+    );
+}
+
+#[test]
+fn encrypt_tampered() {
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let json = to_json(&config(), encryption.clone());
+    let sealed = password(&json);
+    // Flips a bit in the last byte of the tag.
+    let mut bytes =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &sealed[3..]).unwrap();
+    *bytes.last_mut().unwrap() ^= 1;
+    let tampered = json.replace(
+        sealed,
+        &format!(
+            "k1:{}",
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+        ),
+    );
+    let err = from_json::<Config>(&tampered, encryption.clone()).unwrap_err();
+    assert!(
+        err.to_string().starts_with(
+            "invalid value: bytes failing authentication, expected bytes encrypted with a \
+             known key in `/password`"
+        ),
+        "{}",
+        err
+    );
+
+    // The key ID is authenticated too.
+    let renamed = json.replace("k1:", "k2:");
+    let encryption = Encryption::new(key("k2", 1)).fields(["password"]);
+    let err = from_json::<Config>(&renamed, encryption).unwrap_err();
+    assert!(
+        err.to_string().contains("bytes failing authentication"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn encrypt_wrong_key() {
+    let json = to_json(
+        &config(),
+        Encryption::new(key("k1", 1)).fields(["password"]),
+    );
+    let err =
+        from_json::<Config>(&json, Encryption::new(key("k1", 2)).fields(["password"])).unwrap_err();
+    assert!(
+        err.to_string().contains("bytes failing authentication"),
+        "{}",
+        err
+    );
+    let err =
+        from_json::<Config>(&json, Encryption::new(key("k2", 1)).fields(["password"])).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("bytes encrypted with an unknown key, expected"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn encrypt_rotation() {
+    let old = to_json(
+        &config(),
+        Encryption::new(key("k1", 1)).fields(["password"]),
+    );
+    let encryption = Encryption::new(key("k2", 2))
+        .old_key(key("k1", 1))
+        .fields(["password"]);
+    assert_eq!(config(), from_json(&old, encryption.clone()).unwrap());
+    // Writing again seals with the current key.
+    let new = to_json(&config(), encryption.clone());
+    assert!(password(&new).starts_with("k2:"), "{}", new);
+    assert_eq!(config(), from_json(&new, encryption).unwrap());
+}
+
+#[test]
+fn encrypt_plaintext_rejected() {
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let json = r#"{"id":"0102","password":"68756e74657232"}"#;
+    assert_eq!(
+        "invalid value: unencrypted bytes, expected bytes encrypted with a known key in \
+         `/password` at line 1 column 40",
+        from_json::<Config>(json, encryption.clone())
+            .unwrap_err()
+            .to_string()
+    );
+    let json = r#"{"id":"0102","password":[104,117]}"#;
+    let err = from_json::<Config>(json, encryption).unwrap_err();
+    assert!(
+        err.to_string().starts_with("invalid type: sequence"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn encrypt_recovering() {
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let json = r#"{"id":"0102","password":"68756e74657232"}"#;
+    let mut de = serde_json::Deserializer::from_str(json);
+    let (value, errors) = ByteFmtDeserializer::new_hex(&mut de)
+        .decrypt(encryption)
+        .deserialize_recovering::<Config>()
+        .unwrap();
+    // Nothing unauthenticated gets through.
+    assert!(value.password.is_empty());
+    assert_eq!(vec![1, 2], value.id);
+    assert_eq!("/password", errors[0].path());
+}
+
+#[test]
+#[should_panic(expected = "key IDs must be non-empty and can't contain `:`")]
+fn encrypt_invalid_key_id() {
+    let _ = key("k:1", 1);
+}
+
+#[test]
+fn encrypt_key_debug() {
+    let debug = format!("{:?}", key("k1", 1));
+    assert!(
+        debug.starts_with(r#"Key { id: "k1", algorithm: "#),
+        "{}",
+        debug
+    );
+}
+
+#[cfg(all(feature = "aes-gcm", feature = "chacha20poly1305"))]
+#[test]
+fn encrypt_mixed_ciphers() {
+    let old = to_json(
+        &config(),
+        Encryption::new(Key::aes256_gcm("aes", &[1; 32])).fields(["password"]),
+    );
+    let encryption = Encryption::new(Key::chacha20_poly1305("chacha", &[2; 32]))
+        .old_key(Key::aes256_gcm("aes", &[1; 32]))
+        .fields(["password"]);
+    assert_eq!(config(), from_json(&old, encryption.clone()).unwrap());
+    let new = to_json(&config(), encryption.clone());
+    assert!(password(&new).starts_with("chacha:"), "{}", new);
+    assert_eq!(config(), from_json(&new, encryption).unwrap());
+}
+
+#[cfg(all(feature = "aes-gcm", feature = "gzip"))]
+#[test]
+fn encrypt_compressed() {
+    use serde_bytes_repr::compress::Compression;
+
+    let value = Config {
+        id: b"GET /index.html 200\n".repeat(100),
+        password: b"GET /index.html 200\n".repeat(100),
+    };
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    // Encryption stays above compression, whatever the order.
+    let json = common::to_json(&value, |ser| {
+        ser.encrypt(encryption.clone())
+            .compress(Compression::gzip())
+    });
+    assert!(json.starts_with(r#"{"id":"gzip:"#), "{}", json);
+    assert!(json.contains(r#""password":"k1:"#), "{}", json);
+
+    let decoded = common::from_json(&json, |de| de.decompress(3000).decrypt(encryption));
+    assert_eq!(value, decoded.unwrap());
+}
+
+#[cfg(all(feature = "aes-gcm", feature = "sha2"))]
+#[test]
+fn encrypt_checksum() {
+    use serde_bytes_repr::checksum::Checksum;
+
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let json = common::to_json(&config(), |ser| {
+        ser.encrypt(encryption.clone())
+            .checksum(Checksum::sha256().len(2))
+    });
+    // The checksum is encrypted along with the bytes.
+    assert!(
+        json.starts_with(r#"{"id":"0102a128","password":"k1:"#),
+        "{}",
+        json
+    );
+
+    let decoded = common::from_json(&json, |de| {
+        de.checksum(Checksum::sha256().len(2)).decrypt(encryption)
+    });
+    assert_eq!(config(), decoded.unwrap());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn encrypt_yaml_binary() {
+    use serde_bytes_repr::ByteFmtSerializer;
+
+    let encryption = Encryption::new(key("k1", 1)).fields(["password"]);
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::yaml_binary(&mut ser).encrypt(encryption.clone());
+    config().serialize(ser).unwrap();
+    let yaml = String::from_utf8(out).unwrap();
+    assert!(yaml.starts_with("id: AQI=\npassword: k1:"), "{}", yaml);
+    let de = serde_yaml::Deserializer::from_str(&yaml);
+    let de = ByteFmtDeserializer::new_yaml_binary(de).decrypt(encryption);
+    assert_eq!(config(), Config::deserialize(de).unwrap());
+}
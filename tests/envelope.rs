@@ -0,0 +1,164 @@
+#![cfg(feature = "alloc")]
+use base64::{alphabet, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{envelope::Envelope, Encoding};
+
+mod common;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Message {
+    #[serde(with = "serde_bytes")]
+    body: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    digest: Option<Vec<u8>>,
+}
+
+fn message() -> Message {
+    Message {
+        body: b"hello !".to_vec(),
+        digest: Some(vec![0xfb, 0xff]),
+    }
+}
+
+fn to_json(value: &impl Serialize, encoding: Encoding, envelope: Envelope) -> String {
+    common::try_to_json(value, encoding, |ser| ser.envelope(envelope)).unwrap()
+}
+
+fn from_json<'de, T: Deserialize<'de>>(
+    json: &'de str,
+    envelope: Envelope,
+) -> Result<T, serde_json::Error> {
+    common::from_json(json, |de| de.envelope(envelope))
+}
+
+#[test]
+fn envelope_round_trip() {
+    let encoding = Encoding::base64(alphabet::URL_SAFE, general_purpose::PAD);
+    let json = to_json(&message(), encoding, Envelope::new());
+    assert_eq!(
+        r#"{"body":{"encoding":"base64url","length":7,"data":"aGVsbG8gIQ=="},"digest":{"encoding":"base64url","length":2,"data":"-_8="}}"#,
+        json
+    );
+    assert_eq!(
+        message(),
+        from_json::<Message>(&json, Envelope::new()).unwrap()
+    );
+}
+
+#[test]
+fn keys_are_configurable() {
+    let envelope = Envelope::new()
+        .encoding_key("enc")
+        .length_key("len")
+        .data_key("b");
+    let json = to_json(&message(), Encoding::hex(), envelope.clone());
+    assert_eq!(
+        r#"{"body":{"enc":"hex","len":7,"b":"68656c6c6f2021"},"digest":{"enc":"hex","len":2,"b":"fbff"}}"#,
+        json
+    );
+    assert_eq!(message(), from_json::<Message>(&json, envelope).unwrap());
+}
+
+#[test]
+fn envelopes_pick_the_decoder() {
+    // In any order, with or without padding, with other keys and next to plain values.
+    let json = r#"{
+        "body": {"data": "aGVsbG8gIQ", "version": 2, "length": 7, "encoding": "base64"},
+        "digest": "fbff"
+    }"#;
+    assert_eq!(
+        message(),
+        from_json::<Message>(json, Envelope::new()).unwrap()
+    );
+}
+
+#[test]
+fn envelopes_are_checked() {
+    let err = |body: &str| {
+        let json = format!(r#"{{"body":{},"digest":null}}"#, body);
+        from_json::<Message>(&json, Envelope::new())
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        "invalid length 7, expected 8 bytes as declared by the envelope in `/body` at line 1 column 61",
+        err(r#"{"encoding":"hex","length":8,"data":"68656c6c6f2021"}"#)
+    );
+    assert_eq!(
+        "invalid value: string \"base32\", expected hex, base64 or base64url in `/body` at line 1 column 28",
+        err(r#"{"encoding":"base32","length":0,"data":""}"#)
+    );
+    assert_eq!(
+        "envelope without `length` in `/body` at line 1 column 50",
+        err(r#"{"encoding":"hex","data":"68656c6c6f2021"}"#)
+    );
+    assert_eq!(
+        "invalid value: character `g`, expected valid hex character at index 1 in `/body` at line 1 column 49",
+        err(r#"{"encoding":"hex","length":1,"data":"0g"}"#)
+    );
+}
+
+#[test]
+fn envelopes_name_their_encoding() {
+    let err = common::try_to_json(&message(), Encoding::yaml_binary(), |ser| {
+        ser.envelope(Envelope::new())
+    })
+    .unwrap_err();
+    assert_eq!(
+        "bytes in an envelope must be encoded in hex, base64 or base64url",
+        err.to_string()
+    );
+}
+
+#[test]
+fn envelope_preview_array() {
+    use serde_bytes_repr::preview::Preview;
+
+    let json = common::try_to_json(&message(), Encoding::array(), |ser| {
+        ser.envelope(Envelope::new()).preview(Preview::head(1))
+    });
+    assert_eq!(
+        r#"{"body":"68…(+6 bytes)","digest":"fb…(+1 bytes)"}"#,
+        json.unwrap()
+    );
+}
+
+#[test]
+#[cfg(feature = "sha2")]
+fn envelopes_hold_no_checksums() {
+    use serde_bytes_repr::checksum::Checksum;
+
+    let err = common::try_to_json(&message(), Encoding::hex(), |ser| {
+        ser.checksum(Checksum::sha256()).envelope(Envelope::new())
+    })
+    .unwrap_err();
+    assert_eq!(
+        "bytes in an envelope can't have a checksum or signature",
+        err.to_string()
+    );
+    let json = r#"{"body":{"encoding":"hex","length":7,"data":"68656c6c6f2021"},"digest":null}"#;
+    let err = common::from_json::<Message>(json, |de| {
+        de.checksum(Checksum::sha256()).envelope(Envelope::new())
+    })
+    .unwrap_err();
+    assert_eq!(
+        "bytes in an envelope can't have a checksum or signature in `/body` at line 1 column 61",
+        err.to_string()
+    );
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn envelopes_hold_no_compressed_values() {
+    use serde_bytes_repr::compress::Compression;
+
+    let compression = Compression::gzip().threshold(0);
+    for envelope_first in [false, true] {
+        let err = common::try_to_json(&message(), Encoding::hex(), |ser| match envelope_first {
+            true => ser.envelope(Envelope::new()).compress(compression.clone()),
+            false => ser.compress(compression.clone()).envelope(Envelope::new()),
+        })
+        .unwrap_err();
+        assert_eq!("bytes in an envelope can't be compressed", err.to_string());
+    }
+}
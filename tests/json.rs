@@ -0,0 +1,171 @@
+#![cfg(feature = "json")]
+
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use serde_bytes_repr::Encoding;
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct User {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    key: Vec<u8>,
+    backup: Option<ByteBuf>,
+}
+
+fn users() -> Vec<User> {
+    vec![
+        User {
+            name: "a".to_string(),
+            key: b"testing".to_vec(),
+            backup: None,
+        },
+        User {
+            name: "b".to_string(),
+            key: b"key".to_vec(),
+            backup: Some(ByteBuf::from(b"backup".to_vec())),
+        },
+    ]
+}
+
+fn base64() -> Encoding {
+    Encoding::base64(
+        base64::alphabet::URL_SAFE,
+        base64::engine::GeneralPurposeConfig::new(),
+    )
+}
+
+#[test]
+fn to_value_hex() {
+    let value = serde_bytes_repr::json::to_value_hex(&users()).unwrap();
+    let expected = json!([
+        { "name": "a", "key": "74657374696e67", "backup": null },
+        { "name": "b", "key": "6b6579", "backup": "6261636b7570" },
+    ]);
+    assert_eq!(expected, value);
+}
+
+#[test]
+fn roundtrip_value_base64() {
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let value = serde_bytes_repr::json::to_value_base64(
+        &users(),
+        base64::alphabet::URL_SAFE,
+        base64_config,
+    )
+    .unwrap();
+    assert_eq!(json!("dGVzdGluZw=="), value[0]["key"]);
+
+    let deserialized: Vec<User> =
+        serde_bytes_repr::json::from_value_base64(value, base64::alphabet::URL_SAFE, base64_config)
+            .unwrap();
+    assert_eq!(users(), deserialized);
+}
+
+#[test]
+fn from_value_invalid_hex() {
+    let value = json!({ "name": "a", "key": "746g", "backup": null });
+    let err = serde_bytes_repr::json::from_value_hex::<User>(value).unwrap_err();
+    assert_eq!(
+        "invalid value: character `g`, expected valid hex character at index 3 in `/key`",
+        err.to_string()
+    );
+}
+
+#[test]
+fn reencode_hex_to_base64() {
+    let mut value = serde_bytes_repr::json::to_value_hex(&users()).unwrap();
+    serde_bytes_repr::json::reencode(
+        &mut value,
+        &["/*/key", "/*/backup"],
+        &Encoding::hex(),
+        &base64(),
+    )
+    .unwrap();
+
+    let expected = json!([
+        { "name": "a", "key": "dGVzdGluZw==", "backup": null },
+        { "name": "b", "key": "a2V5", "backup": "YmFja3Vw" },
+    ]);
+    assert_eq!(expected, value);
+
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let deserialized: Vec<User> =
+        serde_bytes_repr::json::from_value_base64(value, base64::alphabet::URL_SAFE, base64_config)
+            .unwrap();
+    assert_eq!(users(), deserialized);
+}
+
+#[test]
+fn reencode_escaped_pointer() {
+    let mut value = json!({ "a/b": "6b6579", "a~b": "6b6579", "other": "6b6579" });
+    serde_bytes_repr::json::reencode(&mut value, &["/a~1b", "/a~0b"], &Encoding::hex(), &base64())
+        .unwrap();
+    assert_eq!(
+        json!({ "a/b": "a2V5", "a~b": "a2V5", "other": "6b6579" }),
+        value
+    );
+}
+
+#[test]
+fn reencode_invalid() {
+    let original = json!({ "a": "6b6579", "b": "6b657" });
+    let mut value = original.clone();
+    let err = serde_bytes_repr::json::reencode(&mut value, &["/*"], &Encoding::hex(), &base64())
+        .unwrap_err();
+    assert_eq!(
+        "/b: invalid length 5, expected even length",
+        err.to_string()
+    );
+    assert_eq!(original, value);
+
+    let mut value = json!({ "a": [1, 2] });
+    let err = serde_bytes_repr::json::reencode(&mut value, &["/a"], &Encoding::hex(), &base64())
+        .unwrap_err();
+    assert_eq!(
+        "/a: invalid type: sequence, expected an encoded string",
+        err.to_string()
+    );
+
+    let err = serde_bytes_repr::json::reencode(&mut value, &["a"], &Encoding::hex(), &base64())
+        .unwrap_err();
+    assert_eq!(
+        "invalid pointer `a`, expected a leading `/`",
+        err.to_string()
+    );
+}
+
+#[test]
+fn reencode_arrays() {
+    let mut value = json!({ "a": [116, 101], "b": null });
+    serde_bytes_repr::json::reencode(&mut value, &["/*"], &Encoding::array(), &Encoding::hex())
+        .unwrap();
+    assert_eq!(json!({ "a": "7465", "b": null }), value);
+
+    serde_bytes_repr::json::reencode(&mut value, &["/a"], &Encoding::hex(), &Encoding::array())
+        .unwrap();
+    assert_eq!(json!({ "a": [116, 101], "b": null }), value);
+
+    let mut value = json!({ "a": [116, 256] });
+    let err =
+        serde_bytes_repr::json::reencode(&mut value, &["/a"], &Encoding::array(), &Encoding::hex())
+            .unwrap_err();
+    assert!(err.to_string().starts_with("/a: "), "{}", err);
+}
+
+#[test]
+fn check_canonical_form() {
+    let value = json!({ "a": "7465", "b": "7A", "c": "zz", "d": null, "e": [1] });
+    serde_bytes_repr::json::check(&value, &["/a", "/d"], &Encoding::hex()).unwrap();
+
+    let errors = serde_bytes_repr::json::check(&value, &["/*"], &Encoding::hex()).unwrap_err();
+    let errors: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(3, errors.len(), "{:?}", errors);
+    assert_eq!("/b: not in canonical form", errors[0]);
+    assert!(errors[1].starts_with("/c: invalid value"), "{:?}", errors);
+    assert!(
+        errors[2].starts_with("/e: invalid type: sequence"),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "alloc")]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{preview::Preview, redact::Redaction, ByteFmtDeserializer, Encoding};
+
+mod common;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Attachment {
+    #[serde(with = "serde_bytes")]
+    name: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+fn attachment() -> Attachment {
+    Attachment {
+        name: b"ab".to_vec(),
+        data: (0..=255).cycle().take(1 << 20).collect(),
+    }
+}
+
+#[test]
+fn preview_head() {
+    let json = common::to_json(&attachment(), |ser| ser.preview(Preview::head(4)));
+    assert_eq!(
+        r#"{"name":"6162","data":"00010203…(+1048572 bytes)"}"#,
+        json
+    );
+}
+
+#[test]
+fn preview_head_and_tail() {
+    let encoding = Encoding::base64(
+        base64::alphabet::STANDARD,
+        base64::engine::general_purpose::PAD,
+    );
+    let json = common::try_to_json(&attachment(), encoding, |ser| {
+        ser.preview(Preview::head(3).tail(3))
+    });
+    assert_eq!(
+        r#"{"name":"YWI=","data":"AAEC…(+1048570 bytes)…/f7/"}"#,
+        json.unwrap()
+    );
+}
+
+#[test]
+fn preview_array() {
+    let json = common::try_to_json(&attachment(), Encoding::array(), |ser| {
+        ser.preview(Preview::head(2))
+    });
+    assert_eq!(
+        r#"{"name":[97,98],"data":"0001…(+1048574 bytes)"}"#,
+        json.unwrap()
+    );
+}
+
+#[test]
+fn preview_redacted() {
+    let expected = r#"{"name":"<redacted 2 bytes>","data":"00…(+1048575 bytes)"}"#;
+    let json = common::to_json(&attachment(), |ser| {
+        ser.redact(Redaction::marker().fields(["name"]))
+            .preview(Preview::head(1))
+    });
+    assert_eq!(expected, json);
+    let json = common::to_json(&attachment(), |ser| {
+        ser.preview(Preview::head(1))
+            .redact(Redaction::marker().fields(["name"]))
+    });
+    assert_eq!(expected, json);
+}
+
+#[test]
+#[cfg(feature = "sha2")]
+fn preview_sha256() {
+    let json = common::to_json(&attachment(), |ser| ser.preview(Preview::head(2).sha256()));
+    assert_eq!(
+        r#"{"name":"6162","data":"0001…(+1048574 bytes, sha256:fbbab289f7f94b25736c58be46a994c441fd02552cc6022352e3d86d2fab7c83)"}"#,
+        json
+    );
+}
+
+#[test]
+#[cfg(feature = "blake3")]
+fn preview_blake3() {
+    let json = common::to_json(&attachment(), |ser| ser.preview(Preview::head(2).blake3()));
+    assert_eq!(
+        r#"{"name":"6162","data":"0001…(+1048574 bytes, blake3:64479cf7293960210547db8d982359e0c4ce054525ed7086cf93030828fc0533)"}"#,
+        json
+    );
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn preview_yaml_binary() {
+    use serde_bytes_repr::ByteFmtSerializer;
+
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::yaml_binary(&mut ser).preview(Preview::head(3));
+    attachment().serialize(ser).unwrap();
+    assert_eq!(
+        "name: YWI=\ndata: AAEC…(+1048573 bytes)\n",
+        String::from_utf8(out).unwrap()
+    );
+}
+
+#[test]
+fn preview_rejected() {
+    let json = r#"{"name":"6162","data":"00010203…(+1048572 bytes)"}"#;
+    let err = common::from_json::<Attachment>(json, |de| de).unwrap_err();
+    assert_eq!(
+        "invalid value: truncated preview, expected complete encoded bytes in `/data` at line 1 column 51",
+        err.to_string()
+    );
+
+    let mut de = serde_json::Deserializer::from_str(json);
+    let err = ByteFmtDeserializer::new_hex(&mut de)
+        .deserialize::<Attachment>()
+        .unwrap_err();
+    let decode = err.decode_error().unwrap();
+    assert_eq!("/data", decode.path());
+    assert_eq!(Some(8), decode.offset());
+}
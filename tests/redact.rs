@@ -0,0 +1,99 @@
+#![cfg(feature = "alloc")]
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use serde_bytes_repr::redact::Redaction;
+
+mod common;
+
+#[derive(Serialize)]
+struct SecretKey(#[serde(with = "serde_bytes")] Vec<u8>);
+
+#[derive(Serialize)]
+struct Request {
+    #[serde(with = "serde_bytes")]
+    id: Vec<u8>,
+    key: SecretKey,
+    tokens: Vec<ByteBuf>,
+}
+
+fn request() -> Request {
+    Request {
+        id: vec![1, 2],
+        key: SecretKey(vec![0xab; 32]),
+        tokens: vec![ByteBuf::from(vec![0xcd; 4]), ByteBuf::from(vec![])],
+    }
+}
+
+fn to_json<T: Serialize>(value: &T, redaction: Redaction) -> String {
+    common::to_json(value, |ser| ser.redact(redaction))
+}
+
+#[test]
+fn redact_fields() {
+    assert_eq!(
+        r#"{"id":"0102","key":"abababababababababababababababababababababababababababababababab","tokens":["<redacted 4 bytes>","<redacted 0 bytes>"]}"#,
+        to_json(&request(), Redaction::marker().fields(["tokens"]))
+    );
+}
+
+#[test]
+fn redact_newtypes() {
+    assert_eq!(
+        r#"{"id":"0102","key":"<redacted 32 bytes>","tokens":["cdcdcdcd",""]}"#,
+        to_json(&request(), Redaction::marker().newtypes(["SecretKey"]))
+    );
+}
+
+#[test]
+fn redact_everything() {
+    assert_eq!(
+        r#"{"id":"<redacted 2 bytes>","key":"<redacted 32 bytes>","tokens":["<redacted 4 bytes>","<redacted 0 bytes>"]}"#,
+        to_json(&request(), Redaction::marker())
+    );
+}
+
+#[test]
+fn redact_prefix() {
+    assert_eq!(
+        r#"{"id":"<redacted 2 bytes: 01…>","key":"<redacted 32 bytes: abababab…>","tokens":["<redacted 4 bytes: cdcd…>","<redacted 0 bytes: …>"]}"#,
+        to_json(&request(), Redaction::prefix(4))
+    );
+}
+
+#[test]
+#[cfg(feature = "sha2")]
+fn redact_fingerprint() {
+    let json = to_json(
+        &request(),
+        Redaction::fingerprint(b"salt").newtypes(["SecretKey"]),
+    );
+    assert_eq!(
+        r#"{"id":"0102","key":"<redacted 32 bytes sha256:dd36a5e80e83f475>","tokens":["cdcdcdcd",""]}"#,
+        json
+    );
+    // A different salt gives a different fingerprint.
+    assert_ne!(
+        json,
+        to_json(
+            &request(),
+            Redaction::fingerprint(b"pepper").newtypes(["SecretKey"])
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn redact_yaml_binary() {
+    use serde_bytes_repr::ByteFmtSerializer;
+
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::yaml_binary(&mut ser).redact(Redaction::marker().fields(["id"]));
+    request().serialize(ser).unwrap();
+    let yaml = String::from_utf8(out).unwrap();
+    assert!(
+        yaml.starts_with("id: <redacted 2 bytes>\nkey: q6ur"),
+        "{}",
+        yaml
+    );
+}
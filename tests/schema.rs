@@ -0,0 +1,131 @@
+#![cfg(feature = "schemars")]
+use base64::{
+    alphabet,
+    engine::{general_purpose, GeneralPurposeConfig},
+};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_bytes_repr::{
+    schema::{self, BytesSchema},
+    ByteFmtSerializer, Encoding,
+};
+use serde_json::{json, Value};
+
+#[derive(Serialize, JsonSchema)]
+struct Keys {
+    #[serde(with = "serde_bytes")]
+    #[schemars(with = "schema::Hex<32, 32>")]
+    public: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    #[schemars(with = "Option<schema::Base64<1>>")]
+    certificate: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    #[schemars(with = "schema::Array<0, 16>")]
+    salt: Vec<u8>,
+}
+
+#[test]
+fn derived_schema() {
+    let schema = schemars::schema_for!(Keys);
+    let properties = &schema.as_value()["properties"];
+    assert_eq!(
+        json!({
+            "type": "string",
+            "contentEncoding": "base16",
+            "pattern": "^(?:[0-9a-f]{2})*$",
+            "minLength": 64,
+            "maxLength": 64,
+        }),
+        properties["public"]
+    );
+    assert_eq!(
+        json!({
+            "type": ["string", "null"],
+            "contentEncoding": "base64",
+            "pattern": "^(?:[+/0-9A-Za-z]{4})*(?:[+/0-9A-Za-z]{2}==|[+/0-9A-Za-z]{3}=)?$",
+            "minLength": 4,
+        }),
+        properties["certificate"]
+    );
+    assert_eq!(
+        json!({
+            "type": "array",
+            "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+            "maxItems": 16,
+        }),
+        properties["salt"]
+    );
+}
+
+/// Serializes `v` with the adapter and checks the result against the schema of `encoding`.
+fn assert_matches(encoding: Encoding, v: &[u8], min_len: usize, max_len: usize) {
+    let schema = BytesSchema::new(encoding.clone())
+        .min_len(min_len)
+        .max_len(max_len)
+        .schema();
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    serde_bytes::Bytes::new(v)
+        .serialize(ByteFmtSerializer::new(&mut ser, encoding))
+        .unwrap();
+    let encoded: Value = serde_json::from_slice(&out).unwrap();
+    let encoded = encoded.as_str().unwrap();
+    let pattern = Regex::new(schema.get("pattern").unwrap().as_str().unwrap()).unwrap();
+    assert!(pattern.is_match(encoded), "{:?} {}", schema, encoded);
+    let len = encoded.chars().count() as u64;
+    assert_eq!(Some(len), schema.get("minLength").and_then(Value::as_u64));
+    assert_eq!(Some(len), schema.get("maxLength").and_then(Value::as_u64));
+}
+
+#[test]
+fn patterns_match_serialized_values() {
+    let no_pad = GeneralPurposeConfig::new().with_encode_padding(false);
+    for len in 1..200 {
+        let v: Vec<u8> = (0..len).map(|i| (i * 97 + 13) as u8).collect();
+        assert_matches(Encoding::hex(), &v, len, len);
+        assert_matches(
+            Encoding::base64(alphabet::STANDARD, general_purpose::PAD),
+            &v,
+            len,
+            len,
+        );
+        assert_matches(Encoding::base64(alphabet::URL_SAFE, no_pad), &v, len, len);
+        assert_matches(Encoding::base64(alphabet::BCRYPT, no_pad), &v, len, len);
+        assert_matches(Encoding::yaml_binary(), &v, len, len);
+    }
+}
+
+#[test]
+fn custom_alphabet() {
+    let schema =
+        BytesSchema::new(Encoding::base64(alphabet::BCRYPT, general_purpose::NO_PAD)).schema();
+    assert_eq!(
+        json!({
+            "type": "string",
+            "pattern": "^(?:[./0-9A-Za-z]{4})*(?:[./0-9A-Za-z]{2,3})?$",
+        }),
+        *schema.as_value()
+    );
+}
+
+#[test]
+fn unbounded_lengths() {
+    assert_eq!(
+        json!({
+            "type": "string",
+            "contentEncoding": "base64url",
+            "pattern": "^(?:[\\-0-9A-Z_a-z]{4})*(?:[\\-0-9A-Z_a-z]{2,3})?$",
+        }),
+        *BytesSchema::new(Encoding::base64(
+            alphabet::URL_SAFE,
+            general_purpose::NO_PAD
+        ))
+        .schema()
+        .as_value()
+    );
+    assert_eq!(
+        json!({ "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } }),
+        *BytesSchema::new(Encoding::array()).schema().as_value()
+    );
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "serde_with")]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::serde_as::{Base64, Base64NoPad, Base64UrlNoPad, Hex};
+use serde_with::serde_as;
+use std::collections::HashMap;
+
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Keys {
+    #[serde_as(as = "Hex")]
+    public: [u8; 4],
+    #[serde_as(as = "Option<Base64>")]
+    certificate: Option<Vec<u8>>,
+    #[serde_as(as = "Vec<Base64UrlNoPad>")]
+    nonces: Vec<Vec<u8>>,
+    #[serde_as(as = "HashMap<Hex, Base64NoPad>")]
+    signatures: HashMap<Vec<u8>, Box<[u8]>>,
+}
+
+fn keys() -> Keys {
+    Keys {
+        public: [0xde, 0xad, 0xbe, 0xef],
+        certificate: None,
+        nonces: vec![vec![0xfb, 0xff], vec![]],
+        signatures: HashMap::from([(vec![0x01], vec![0xfb, 0xff, 0xbf].into_boxed_slice())]),
+    }
+}
+
+#[test]
+fn serde_as_round_trip() {
+    let json = serde_json::to_string(&keys()).unwrap();
+    assert_eq!(
+        r#"{"public":"deadbeef","certificate":null,"nonces":["-_8",""],"signatures":{"01":"+/+/"}}"#,
+        json
+    );
+    assert_eq!(keys(), serde_json::from_str(&json).unwrap());
+
+    let mut keys = keys();
+    keys.certificate = Some(b"cert".to_vec());
+    let json = serde_json::to_string(&keys).unwrap();
+    assert!(json.contains(r#""certificate":"Y2VydA==""#));
+    assert_eq!(keys, serde_json::from_str(&json).unwrap());
+}
+
+#[test]
+fn serde_as_reads_integer_arrays() {
+    let json = r#"{"public":[222,173,190,239],"certificate":null,"nonces":[[251,255],[]],"signatures":{"01":[251,255,191]}}"#;
+    assert_eq!(keys(), serde_json::from_str(json).unwrap());
+}
+
+fn error(json: &str) -> String {
+    serde_json::from_str::<Keys>(json).unwrap_err().to_string()
+}
+
+#[test]
+fn serde_as_errors() {
+    assert_eq!(
+        "invalid value: character `x`, expected valid hex character at index 1 at line 1 \
+         column 20",
+        error(r#"{"public":"dxadbeef","certificate":null,"nonces":[],"signatures":{}}"#)
+    );
+    assert_eq!(
+        "invalid length 3, expected bytes of the length of the target type at line 1 column 18",
+        error(r#"{"public":"deadbe","certificate":null,"nonces":[],"signatures":{}}"#)
+    );
+    assert_eq!(
+        "invalid value: invalid padding, expected valid padding at line 1 column 56",
+        error(r#"{"public":"deadbeef","certificate":null,"nonces":["-_8="],"signatures":{}}"#)
+    );
+    assert_eq!(
+        "invalid value: character `g`, expected valid hex character at index 1 at line 1 \
+         column 70",
+        error(r#"{"public":"deadbeef","certificate":null,"nonces":[],"signatures":{"0g":""}}"#)
+    );
+}
@@ -166,3 +166,71 @@ fn serialize_option_hex() {
     let serialized = String::from_utf8(out).unwrap();
     assert_eq!(r#"{"bytes":"74657374696e67"}"#, serialized.as_str());
 }
+
+#[test]
+fn serialize_struct_encoding() {
+    #[derive(Serialize, Deserialize)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+    }
+    let bytes = b"testing".to_vec();
+    let demo = Demo { bytes };
+
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::new(&mut ser, serde_bytes_repr::Encoding::hex());
+    demo.serialize(ser).unwrap();
+
+    let serialized = String::from_utf8(out).unwrap();
+    assert_eq!(r#"{"bytes":"74657374696e67"}"#, serialized.as_str());
+}
+
+#[test]
+fn serialize_long_bytes() {
+    // Longer than the buffer values are encoded through.
+    let bytes: Vec<u8> = (0..=255).cycle().take(1000).collect();
+
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::STANDARD, base64_config);
+    serde_bytes::Bytes::new(&bytes).serialize(ser).unwrap();
+    let expected = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes);
+    assert_eq!(format!("\"{}\"", expected), String::from_utf8(out).unwrap());
+
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::hex(&mut ser);
+    serde_bytes::Bytes::new(&bytes).serialize(ser).unwrap();
+    assert_eq!(
+        format!("\"{}\"", hex::encode(&bytes)),
+        String::from_utf8(out).unwrap()
+    );
+}
+
+#[test]
+fn serialize_stack_buffer_sizes() {
+    // Around the size of the stack buffer, in which values are encoded without allocating.
+    for len in [0, 1, 7, 8, 9, 64, 65] {
+        let bytes = vec![0xab; len];
+        let mut out = vec![];
+        let mut ser = serde_json::Serializer::new(&mut out);
+        let ser = ByteFmtSerializer::hex(&mut ser).stack_buffer::<16>();
+        serde_bytes::Bytes::new(&bytes).serialize(ser).unwrap();
+        assert_eq!(
+            format!("\"{}\"", "ab".repeat(len)),
+            String::from_utf8(out).unwrap()
+        );
+
+        let mut out = vec![];
+        let mut ser = serde_json::Serializer::new(&mut out);
+        let base64_config = base64::engine::GeneralPurposeConfig::new().with_encode_padding(false);
+        let ser = ByteFmtSerializer::base64(&mut ser, base64::alphabet::URL_SAFE, base64_config)
+            .stack_buffer::<16>();
+        serde_bytes::Bytes::new(&bytes).serialize(ser).unwrap();
+        let expected =
+            base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, &bytes);
+        assert_eq!(format!("\"{}\"", expected), String::from_utf8(out).unwrap());
+    }
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "hmac")]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{sign::Hmac, Encoding};
+
+mod common;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Session {
+    #[serde(with = "serde_bytes")]
+    blob: Vec<u8>,
+}
+
+fn session() -> Session {
+    Session {
+        blob: b"what do ya want for nothing?".to_vec(),
+    }
+}
+
+fn to_json(value: &Session, hmac: Hmac) -> String {
+    common::to_json(value, |ser| ser.sign(hmac))
+}
+
+fn from_json(json: &str, hmac: Hmac) -> Result<Session, serde_json::Error> {
+    common::from_json(json, |de| de.verify(hmac))
+}
+
+#[test]
+fn sign_rfc4231() {
+    // Test case 2 of RFC 4231.
+    let json = to_json(&session(), Hmac::sha256(b"Jefe"));
+    assert_eq!(
+        format!(
+            r#"{{"blob":"{}5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"}}"#,
+            hex::encode(session().blob)
+        ),
+        json
+    );
+    assert_eq!(session(), from_json(&json, Hmac::sha256(b"Jefe")).unwrap());
+}
+
+#[test]
+fn sign_tampered() {
+    let json = to_json(&session(), Hmac::sha256(b"Jefe"));
+    // "what" becomes "whau".
+    let tampered = json.replacen("77686174", "77686175", 1);
+    assert_eq!(
+        "invalid value: bytes with an invalid signature, expected bytes signed with a known key \
+         in `/blob` at line 1 column 130",
+        from_json(&tampered, Hmac::sha256(b"Jefe"))
+            .unwrap_err()
+            .to_string()
+    );
+}
+
+#[test]
+fn sign_wrong_key() {
+    let json = to_json(&session(), Hmac::sha256(b"Jefe"));
+    let err = from_json(&json, Hmac::sha256(b"Joe")).unwrap_err();
+    assert!(err.to_string().contains("invalid signature"), "{}", err);
+
+    // Values too short for a signature can't be valid.
+    let err = from_json(r#"{"blob":"00"}"#, Hmac::sha256(b"Jefe")).unwrap_err();
+    assert!(err.to_string().contains("invalid signature"), "{}", err);
+}
+
+#[test]
+fn sign_rotation() {
+    let old = to_json(&session(), Hmac::sha256(b"old"));
+    let hmac = Hmac::sha256(b"new").old_key(b"old");
+    assert_eq!(session(), from_json(&old, hmac.clone()).unwrap());
+    // Values are signed with the current key only.
+    let new = to_json(&session(), hmac.clone());
+    assert_eq!(new, to_json(&session(), Hmac::sha256(b"new")));
+    assert_eq!(session(), from_json(&new, hmac).unwrap());
+    assert!(from_json(&new, Hmac::sha256(b"old")).is_err());
+}
+
+#[test]
+fn sign_array() {
+    let hmac = || Hmac::sha256(b"Jefe");
+    let json = common::try_to_json(&session(), Encoding::array(), |ser| ser.sign(hmac())).unwrap();
+    assert!(json.ends_with(",56,67]}"), "{}", json);
+
+    let from_json = |json: &str| {
+        common::from_json_with::<Session>(json, Encoding::array(), |de| de.verify(hmac()))
+    };
+    assert_eq!(session(), from_json(&json).unwrap());
+
+    let err = from_json(&json.replacen("[119,", "[120,", 1)).unwrap_err();
+    assert!(err.to_string().contains("in `/blob`"), "{}", err);
+}
+
+#[test]
+fn sign_checksum() {
+    use serde_bytes_repr::checksum::Checksum;
+
+    let json = common::to_json(&session(), |ser| {
+        ser.checksum(Checksum::sha256().len(2))
+            .sign(Hmac::sha256(b"Jefe"))
+    });
+    // The checksum comes after the signature, whatever the order.
+    assert!(
+        json.contains("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+        "{}",
+        json
+    );
+
+    let decoded = common::from_json(&json, |de| {
+        de.verify(Hmac::sha256(b"Jefe"))
+            .checksum(Checksum::sha256().len(2))
+    });
+    assert_eq!(session(), decoded.unwrap());
+}
+
+#[test]
+fn sign_debug() {
+    let debug = format!("{:?}", Hmac::sha256(b"Jefe").old_key(b"old"));
+    assert_eq!("Hmac { keys: 2, .. }", debug);
+}
@@ -0,0 +1,225 @@
+#![cfg(feature = "simd")]
+//! Checks that the SIMD codecs decode and fail exactly like the scalar `base64` engines and the
+//! `hex` crate.
+
+use base64::{
+    alphabet::{self, Alphabet},
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use serde::{
+    de::{self, value::Error, IntoDeserializer},
+    Deserialize, Serialize,
+};
+use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+
+/// Small LCG so that the inputs are the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+fn configs() -> Vec<(&'static str, Alphabet, GeneralPurposeConfig)> {
+    let pad = GeneralPurposeConfig::new();
+    let nopad = pad
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::RequireNone);
+    vec![
+        ("standard", alphabet::STANDARD, pad),
+        ("standard nopad", alphabet::STANDARD, nopad),
+        ("url", alphabet::URL_SAFE, pad),
+        ("url nopad", alphabet::URL_SAFE, nopad),
+        (
+            "indifferent",
+            alphabet::STANDARD,
+            pad.with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        ),
+        (
+            "trailing bits",
+            alphabet::URL_SAFE,
+            pad.with_decode_allow_trailing_bits(true),
+        ),
+        (
+            "padded encoding, unpadded decoding",
+            alphabet::STANDARD,
+            pad.with_decode_padding_mode(DecodePaddingMode::RequireNone),
+        ),
+        (
+            "unpadded encoding, padded decoding",
+            alphabet::URL_SAFE,
+            pad.with_encode_padding(false),
+        ),
+    ]
+}
+
+/// The error the adapter is documented to return for a `base64` error.
+fn expected_error(err: base64::DecodeError, len: usize) -> Error {
+    match err {
+        base64::DecodeError::InvalidByte(index, b) => de::Error::invalid_value(
+            de::Unexpected::Char(b.into()),
+            &format!("valid base64 character at index {}", index).as_str(),
+        ),
+        base64::DecodeError::InvalidLength(_) => {
+            de::Error::invalid_length(len, &"valid base64 length")
+        }
+        base64::DecodeError::InvalidLastSymbol(_, b) => de::Error::invalid_value(
+            de::Unexpected::Char(b.into()),
+            &"valid character ending base64 string",
+        ),
+        base64::DecodeError::InvalidPadding => {
+            de::Error::invalid_value(de::Unexpected::Other("invalid padding"), &"valid padding")
+        }
+    }
+}
+
+fn decode(alphabet: &Alphabet, config: GeneralPurposeConfig, s: &str) -> Result<Vec<u8>, String> {
+    let de = ByteFmtDeserializer::new_base64(
+        IntoDeserializer::<Error>::into_deserializer(s),
+        alphabet.clone(),
+        config,
+    );
+    serde_bytes::ByteBuf::deserialize(de)
+        .map(|bytes| bytes.into_vec())
+        .map_err(|err| err.to_string())
+}
+
+fn encode(alphabet: &Alphabet, config: GeneralPurposeConfig, v: &[u8]) -> String {
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::base64(&mut ser, alphabet.clone(), config);
+    serde_bytes::Bytes::new(v).serialize(ser).unwrap();
+    serde_json::from_slice(&out).unwrap()
+}
+
+/// Replaces, inserts or removes a character, or adds padding.
+fn corrupt(rng: &mut Rng, s: &str) -> String {
+    const CHARS: &[u8] = b"AZaz09+/-_=.\n \xc3\xa9";
+    let mut bytes = s.as_bytes().to_vec();
+    let at = rng.next() as usize % (bytes.len() + 1);
+    let c = CHARS[rng.next() as usize % CHARS.len()];
+    match rng.next() % 4 {
+        0 if at < bytes.len() => bytes[at] = c,
+        1 if at < bytes.len() => {
+            bytes.remove(at);
+        }
+        2 => bytes.extend_from_slice(b"="),
+        _ => bytes.insert(at, c),
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[test]
+fn simd_matches_scalar() {
+    let mut rng = Rng(0x5eed);
+    for (name, alphabet, config) in configs() {
+        let engine = GeneralPurpose::new(&alphabet, config);
+        for len in (0..100).chain([255, 256, 1000, 4097]) {
+            let v = rng.bytes(len);
+            let encoded = engine.encode(&v);
+            assert_eq!(encode(&alphabet, config, &v), encoded, "{}: {:?}", name, v);
+            let expected = |s: &str| {
+                engine
+                    .decode(s)
+                    .map_err(|err| expected_error(err, s.len()).to_string())
+            };
+            // Configurations that decode other padding than they encode don't round trip.
+            assert_eq!(
+                decode(&alphabet, config, &encoded),
+                expected(&encoded),
+                "{}",
+                name
+            );
+            if expected(&encoded).is_ok() {
+                assert_eq!(expected(&encoded), Ok(v), "{}", name);
+            }
+
+            for _ in 0..20 {
+                let s = corrupt(&mut rng, &encoded);
+                assert_eq!(
+                    decode(&alphabet, config, &s),
+                    expected(&s),
+                    "{}: {:?}",
+                    name,
+                    s
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn simd_matches_scalar_padding() {
+    for (name, alphabet, config) in configs() {
+        let engine = GeneralPurpose::new(&alphabet, config);
+        for s in [
+            "", "=", "==", "A", "AA", "AB", "AA=", "AA==", "AB==", "AAA", "AAB", "AAA=", "AAB=",
+            "AAAA", "AA==AAAA", "AAAA====", "A===", "AA=A", "=AAA",
+        ] {
+            let expected = engine
+                .decode(s)
+                .map_err(|err| expected_error(err, s.len()).to_string());
+            assert_eq!(decode(&alphabet, config, s), expected, "{}: {:?}", name, s);
+        }
+    }
+}
+
+/// The error the adapter is documented to return for a `hex` error.
+fn expected_hex_error(err: hex::FromHexError, len: usize) -> Error {
+    match err {
+        hex::FromHexError::OddLength => de::Error::invalid_length(len, &"even length"),
+        hex::FromHexError::InvalidHexCharacter { c, index } => de::Error::invalid_value(
+            de::Unexpected::Char(c),
+            &format!("valid hex character at index {}", index).as_str(),
+        ),
+        hex::FromHexError::InvalidStringLength => unreachable!("decoding to a `Vec`"),
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    let de = ByteFmtDeserializer::new_hex(IntoDeserializer::<Error>::into_deserializer(s));
+    serde_bytes::ByteBuf::deserialize(de)
+        .map(|bytes| bytes.into_vec())
+        .map_err(|err| err.to_string())
+}
+
+fn encode_hex(v: &[u8]) -> String {
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    serde_bytes::Bytes::new(v)
+        .serialize(ByteFmtSerializer::hex(&mut ser))
+        .unwrap();
+    serde_json::from_slice(&out).unwrap()
+}
+
+#[test]
+fn simd_matches_scalar_hex() {
+    let mut rng = Rng(0x4e7);
+    let expected =
+        |s: &str| hex::decode(s).map_err(|err| expected_hex_error(err, s.len()).to_string());
+    for len in (0..100).chain([255, 256, 1000, 4097]) {
+        let v = rng.bytes(len);
+        let encoded = hex::encode(&v);
+        assert_eq!(encode_hex(&v), encoded, "{:?}", v);
+        assert_eq!(decode_hex(&encoded), Ok(v));
+        assert_eq!(
+            decode_hex(&encoded.to_uppercase()),
+            expected(&encoded.to_uppercase())
+        );
+
+        for _ in 0..20 {
+            let s = corrupt(&mut rng, &encoded);
+            assert_eq!(decode_hex(&s), expected(&s), "{:?}", s);
+        }
+    }
+}
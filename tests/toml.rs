@@ -0,0 +1,143 @@
+#![cfg(feature = "toml")]
+
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Kind {
+    Empty,
+    #[serde(with = "serde_bytes")]
+    Key(Vec<u8>),
+    Pair {
+        #[serde(with = "serde_bytes")]
+        public: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        private: Vec<u8>,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Inner {
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Config {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+    optional: Option<ByteBuf>,
+    missing: Option<ByteBuf>,
+    map: BTreeMap<String, ByteBuf>,
+    kind: Kind,
+    other: Kind,
+    inner: Inner,
+}
+
+fn config() -> Config {
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), ByteBuf::from(b"first".to_vec()));
+    map.insert("b".to_string(), ByteBuf::from(b"second".to_vec()));
+    Config {
+        name: "testing".to_string(),
+        bytes: b"testing".to_vec(),
+        optional: Some(ByteBuf::from(b"optional".to_vec())),
+        missing: None,
+        map,
+        kind: Kind::Key(b"key".to_vec()),
+        other: Kind::Pair {
+            public: b"public".to_vec(),
+            private: b"private".to_vec(),
+        },
+        inner: Inner {
+            bytes: b"inner".to_vec(),
+        },
+    }
+}
+
+#[test]
+fn serialize_struct_hex() {
+    let serialized = serde_bytes_repr::toml::to_string_hex(&config()).unwrap();
+    let expected = r#"name = "testing"
+bytes = "74657374696e67"
+optional = "6f7074696f6e616c"
+
+[map]
+a = "6669727374"
+b = "7365636f6e64"
+
+[kind]
+Key = "6b6579"
+
+[other.Pair]
+public = "7075626c6963"
+private = "70726976617465"
+
+[inner]
+bytes = "696e6e6572"
+"#;
+    assert_eq!(expected, serialized);
+}
+
+#[test]
+fn roundtrip_hex() {
+    let serialized = serde_bytes_repr::toml::to_string_hex(&config()).unwrap();
+    let deserialized: Config = serde_bytes_repr::toml::from_str_hex(&serialized).unwrap();
+    assert_eq!(config(), deserialized);
+}
+
+#[test]
+fn roundtrip_base64() {
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let serialized = serde_bytes_repr::toml::to_string_base64(
+        &config(),
+        base64::alphabet::URL_SAFE,
+        base64_config,
+    )
+    .unwrap();
+    assert!(serialized.contains(r#"bytes = "dGVzdGluZw==""#));
+
+    let deserialized: Config = serde_bytes_repr::toml::from_str_base64(
+        &serialized,
+        base64::alphabet::URL_SAFE,
+        base64_config,
+    )
+    .unwrap();
+    assert_eq!(config(), deserialized);
+}
+
+#[test]
+fn roundtrip_unit_variant() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Demo {
+        kind: Kind,
+    }
+
+    let demo = Demo { kind: Kind::Empty };
+    let serialized = serde_bytes_repr::toml::to_string_hex(&demo).unwrap();
+    assert_eq!("kind = \"Empty\"\n", serialized);
+    let deserialized: Demo = serde_bytes_repr::toml::from_str_hex(&serialized).unwrap();
+    assert_eq!(demo, deserialized);
+}
+
+#[test]
+fn deserialize_invalid_hex() {
+    let toml = r#"
+name = "testing"
+bytes = "746g7374696e67"
+"#;
+    #[derive(Deserialize, Debug)]
+    struct Demo {
+        #[allow(dead_code)]
+        name: String,
+        #[serde(with = "serde_bytes")]
+        #[allow(dead_code)]
+        bytes: Vec<u8>,
+    }
+    let err = serde_bytes_repr::toml::from_str_hex::<Demo>(toml).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("invalid value: character `g`, expected valid hex character at index 3"));
+}
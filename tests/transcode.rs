@@ -0,0 +1,335 @@
+#![cfg(feature = "alloc")]
+
+use serde_bytes_repr::{
+    transcode::{self, transcode},
+    ByteFmtDeserializer, ByteFmtSerializer, Encoding,
+};
+
+mod common;
+
+fn transcode_json(json: &str, from: Encoding, to: Encoding, byte_fields: &[&str]) -> String {
+    let mut json_de = serde_json::Deserializer::from_str(json);
+    let de = ByteFmtDeserializer::new(&mut json_de, from);
+
+    let mut out = vec![];
+    let mut json_ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::new(&mut json_ser, to);
+    transcode(de, ser, byte_fields).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn base64() -> Encoding {
+    Encoding::base64(
+        base64::alphabet::URL_SAFE,
+        base64::engine::GeneralPurposeConfig::new(),
+    )
+}
+
+#[test]
+fn transcode_hex_to_base64() {
+    let json = r#"{"users":[{"name":"6b6579","key":"74657374696e67","backup":null},{"name":"b","key":"6b6579","backup":"6b6579"}],"count":2,"ok":true}"#;
+    let transcoded = transcode_json(
+        json,
+        Encoding::hex(),
+        base64(),
+        &["/users/*/key", "/users/*/backup"],
+    );
+    assert_eq!(
+        r#"{"users":[{"name":"6b6579","key":"dGVzdGluZw==","backup":null},{"name":"b","key":"a2V5","backup":"a2V5"}],"count":2,"ok":true}"#,
+        transcoded
+    );
+}
+
+#[test]
+fn transcode_root_and_index() {
+    assert_eq!(
+        r#""a2V5""#,
+        transcode_json(r#""6b6579""#, Encoding::hex(), base64(), &[""])
+    );
+    assert_eq!(
+        r#"["6b6579","a2V5"]"#,
+        transcode_json(r#"["6b6579","6b6579"]"#, Encoding::hex(), base64(), &["/1"])
+    );
+}
+
+#[test]
+fn transcode_invalid() {
+    let mut json_de = serde_json::Deserializer::from_str(r#"{"a":{"key":"6b657"}}"#);
+    let de = ByteFmtDeserializer::new_hex(&mut json_de);
+    let mut out = vec![];
+    let mut json_ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::new(&mut json_ser, base64());
+    let err = transcode(de, ser, &["/*/key"]).unwrap_err();
+    assert_eq!(
+        "/a/key: invalid length 5, expected even length at line 1 column 19",
+        err.to_string()
+    );
+    match err {
+        transcode::Error::De(err) => assert_eq!((1, 19), (err.line(), err.column())),
+        transcode::Error::Ser(err) => panic!("serializer failed: {}", err),
+    }
+
+    let mut json_de = serde_json::Deserializer::from_str(r#"{"key":[1,2]}"#);
+    let de = ByteFmtDeserializer::new_hex(&mut json_de);
+    let mut out = vec![];
+    let mut json_ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::new(&mut json_ser, base64());
+    let err = transcode(de, ser, &["/key"]).unwrap_err();
+    assert_eq!(
+        "/key: invalid type: sequence, expected an encoded string at line 1 column 8",
+        err.to_string()
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn transcode_json_to_yaml() {
+    let json = r#"{"name":"testing","key":"74657374696e67"}"#;
+    let mut json_de = serde_json::Deserializer::from_str(json);
+    let de = ByteFmtDeserializer::new_hex(&mut json_de);
+
+    let mut out = vec![];
+    let mut yaml_ser = serde_yaml::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::new(&mut yaml_ser, base64());
+    transcode(de, ser, &["/key"]).unwrap();
+    assert_eq!(
+        "name: testing\nkey: dGVzdGluZw==\n",
+        String::from_utf8(out).unwrap()
+    );
+}
+
+#[test]
+fn transcode_globs() {
+    let json = r#"{"a":{"private_key":"6b6579","public_key":"6b6579","name":"6b6579"},"k1":"6b6579","k22":"6b6579"}"#;
+    assert_eq!(
+        r#"{"a":{"private_key":"a2V5","public_key":"a2V5","name":"6b6579"},"k1":"a2V5","k22":"6b6579"}"#,
+        transcode_json(json, Encoding::hex(), base64(), &["/**/*_key", "/k?"])
+    );
+}
+
+#[cfg(feature = "yaml")]
+fn transcode_yaml(yaml: &str, from: Encoding, byte_fields: &[&str]) -> Result<String, String> {
+    let yaml_de = serde_yaml::Deserializer::from_str(yaml);
+    let de = ByteFmtDeserializer::new(yaml_de, from);
+    let mut out = vec![];
+    let mut json_ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::hex(&mut json_ser);
+    match transcode(de, ser, byte_fields) {
+        Ok(()) => Ok(String::from_utf8(out).unwrap()),
+        Err(transcode::Error::De(err)) => Err(format!("de: {}", err)),
+        Err(transcode::Error::Ser(err)) => Err(format!("ser: {}", err)),
+    }
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn transcode_yaml_tags() {
+    let yaml = "key: !binary dGVzdGluZw==\nother: !Pair {a: 1}\n";
+    assert_eq!(
+        Ok(r#"{"key":"74657374696e67","other":{"Pair":{"a":1}}}"#.to_string()),
+        transcode_yaml(yaml, Encoding::yaml_binary(), &["/key"])
+    );
+    assert_eq!(
+        Ok(r#"{"k":{"Key":"6b6579"}}"#.to_string()),
+        transcode_yaml("k: !Key a2V5\n", base64(), &["/k/Key"])
+    );
+    assert_eq!(
+        Err("de: key: /key: invalid type: tagged value, expected an encoded string at line 1 column 6".to_string()),
+        transcode_yaml("key: !secret dGVzdGluZw==\n", Encoding::yaml_binary(), &["/key"])
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn transcode_128_bit_integers() {
+    // Passed on to the serializer, which fails at the top level and below it.
+    assert_eq!(
+        Err("ser: u128 is not supported".to_string()),
+        transcode_yaml("18446744073709551616\n", Encoding::hex(), &[])
+    );
+    assert_eq!(
+        Err("ser: n: u128 is not supported at line 1 column 4".to_string()),
+        transcode_yaml("n: 18446744073709551616\n", Encoding::hex(), &[])
+    );
+}
+
+#[cfg(any(
+    feature = "sha2",
+    feature = "gzip",
+    feature = "aes-gcm",
+    feature = "chacha20poly1305"
+))]
+#[derive(serde::Serialize)]
+struct Keyed {
+    #[serde(with = "serde_bytes")]
+    key: Vec<u8>,
+}
+
+/// Writes `key` as JSON through the serializer `options` set up.
+#[cfg(any(
+    feature = "sha2",
+    feature = "gzip",
+    feature = "aes-gcm",
+    feature = "chacha20poly1305"
+))]
+fn keyed(
+    key: &[u8],
+    options: impl FnOnce(common::Serializer<'_>) -> common::Serializer<'_>,
+) -> String {
+    common::to_json(&Keyed { key: key.to_vec() }, options)
+}
+
+/// Transcodes `/key` to Base64 through the deserializer `options` set up.
+fn transcode_with<'de>(
+    json: &'de str,
+    options: impl for<'a> FnOnce(common::Deserializer<'a, 'de>) -> common::Deserializer<'a, 'de>,
+) -> Result<String, String> {
+    let mut json_de = serde_json::Deserializer::from_str(json);
+    let de = options(ByteFmtDeserializer::new_hex(&mut json_de));
+    let mut out = vec![];
+    let mut json_ser = serde_json::Serializer::new(&mut out);
+    let ser = ByteFmtSerializer::new(&mut json_ser, base64());
+    match transcode(de, ser, &["/key"]) {
+        Ok(()) => Ok(String::from_utf8(out).unwrap()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+const KEY_BASE64: &str = r#"{"key":"a2V5"}"#;
+
+#[test]
+fn transcode_constant_time() {
+    use serde_bytes_repr::constant_time::ConstantTime;
+
+    for constant_time in [ConstantTime::new(), ConstantTime::new().fields(["key"])] {
+        let transcoded =
+            transcode_with(r#"{"key":"6b6579"}"#, |de| de.constant_time(constant_time));
+        assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+    }
+    let err = transcode_with(r#"{"key":"6b657"}"#, |de| {
+        de.constant_time(ConstantTime::new())
+    });
+    assert_eq!(
+        Err("/key: invalid length 5, expected even length at line 1 column 14".to_string()),
+        err
+    );
+}
+
+#[test]
+fn transcode_u8_sequences() {
+    let transcoded = transcode_with(r#"{"key":"6b6579"}"#, |de| de.u8_sequences());
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+}
+
+#[test]
+fn transcode_envelopes() {
+    use serde_bytes_repr::envelope::Envelope;
+
+    let json = r#"{"key":{"encoding":"hex","length":3,"data":"6b6579"}}"#;
+    let transcoded = transcode_with(json, |de| de.envelope(Envelope::new()));
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+    let transcoded = transcode_with(r#"{"key":"6b6579"}"#, |de| de.envelope(Envelope::new()));
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+    let json = r#"{"key":{"encoding":"hex","length":2,"data":"6b6579"}}"#;
+    assert_eq!(
+        Err("/key: invalid length 3, expected 2 bytes as declared by the envelope at line 1 column 52".to_string()),
+        transcode_with(json, |de| de.envelope(Envelope::new()))
+    );
+}
+
+#[test]
+#[cfg(feature = "sha2")]
+fn transcode_checksums() {
+    use serde_bytes_repr::checksum::Checksum;
+
+    let json = keyed(b"key", |ser| ser.checksum(Checksum::sha256().len(4)));
+    let transcoded = transcode_with(&json, |de| de.checksum(Checksum::sha256().len(4)));
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+    let err = transcode_with(r#"{"key":"6b657900000000"}"#, |de| {
+        de.checksum(Checksum::sha256().len(4))
+    })
+    .unwrap_err();
+    assert_eq!(
+        "/key: invalid value: checksum mismatch, expected bytes with a valid sha256 checksum at line 1 column 23",
+        err
+    );
+}
+
+#[test]
+#[cfg(feature = "hmac")]
+fn transcode_signatures() {
+    use serde_bytes_repr::sign::Hmac;
+
+    let json = keyed(b"key", |ser| ser.sign(Hmac::sha256(b"secret")));
+    let transcoded = transcode_with(&json, |de| de.verify(Hmac::sha256(b"secret")));
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+    let err = transcode_with(&json, |de| de.verify(Hmac::sha256(b"other"))).unwrap_err();
+    assert_eq!(
+        "/key: invalid value: bytes with an invalid signature, expected bytes signed with a known key at line 1 column 79",
+        err
+    );
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn transcode_decompress() {
+    use base64::Engine;
+    use serde_bytes_repr::compress::Compression;
+
+    let key = vec![b'k'; 100];
+    let json = keyed(&key, |ser| ser.compress(Compression::gzip()));
+    assert!(json.contains("gzip:"), "{}", json);
+    let transcoded = transcode_with(&json, |de| de.decompress(1000));
+    let expected = base64::engine::general_purpose::URL_SAFE.encode(&key);
+    assert_eq!(Ok(format!(r#"{{"key":"{}"}}"#, expected)), transcoded);
+}
+
+#[test]
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+fn transcode_decrypt() {
+    use serde_bytes_repr::encrypt::{Encryption, Key};
+
+    let encryption = || {
+        #[cfg(feature = "aes-gcm")]
+        return Encryption::new(Key::aes256_gcm("k1", &[7; 32]));
+        #[cfg(not(feature = "aes-gcm"))]
+        return Encryption::new(Key::chacha20_poly1305("k1", &[7; 32]));
+    };
+    let json = keyed(b"key", |ser| ser.encrypt(encryption()));
+    let transcoded = transcode_with(&json, |de| de.decrypt(encryption()));
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+}
+
+#[test]
+#[cfg(feature = "blob")]
+fn transcode_blobs() {
+    use serde_bytes_repr::blob::BlobStore;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    /// Holds the last blob only.
+    #[derive(Default)]
+    struct LastBlob(Mutex<Option<(String, Vec<u8>)>>);
+
+    impl BlobStore for LastBlob {
+        fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+            *self.0.lock().unwrap() = Some((key.into(), bytes.into()));
+            Ok(())
+        }
+
+        fn get(&self, key: &str) -> io::Result<Vec<u8>> {
+            match &*self.0.lock().unwrap() {
+                Some((stored, bytes)) if stored == key => Ok(bytes.clone()),
+                _ => Err(io::Error::new(io::ErrorKind::NotFound, "not found")),
+            }
+        }
+    }
+
+    let store = Arc::new(LastBlob::default());
+    let json = keyed(b"key", |ser| ser.blob_store(store.clone(), 2));
+    assert!(json.contains("$blob"), "{}", json);
+    let transcoded = transcode_with(&json, |de| de.blob_store(store));
+    assert_eq!(Ok(KEY_BASE64.to_string()), transcoded);
+}
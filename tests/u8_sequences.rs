@@ -0,0 +1,118 @@
+#![cfg(feature = "alloc")]
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+
+/// Like a type from another crate, which can't be annotated.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Key {
+    id: [u8; 4],
+    material: Vec<u8>,
+    chain: Vec<Vec<u8>>,
+    uses: Vec<u16>,
+    labels: Vec<String>,
+    empty: Vec<u8>,
+    pair: (u8, u8),
+}
+
+fn key() -> Key {
+    Key {
+        id: [0xde, 0xad, 0xbe, 0xef],
+        material: vec![1, 2, 3],
+        chain: vec![vec![0xab], vec![0xcd, 0xef]],
+        uses: vec![1, 300],
+        labels: vec!["a".to_string()],
+        empty: vec![],
+        pair: (7, 8),
+    }
+}
+
+const JSON: &str = r#"{"id":"deadbeef","material":"010203","chain":["ab","cdef"],"uses":[1,300],"labels":["a"],"empty":[],"pair":"0708"}"#;
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    value
+        .serialize(ByteFmtSerializer::hex(&mut ser).u8_sequences())
+        .unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn from_json<'de, T: Deserialize<'de>>(json: &'de str) -> Result<T, serde_json::Error> {
+    let mut de = serde_json::Deserializer::from_str(json);
+    T::deserialize(ByteFmtDeserializer::new_hex(&mut de).u8_sequences())
+}
+
+#[test]
+fn sequences_round_trip() {
+    assert_eq!(JSON, to_json(&key()));
+    assert_eq!(key(), from_json::<Key>(JSON).unwrap());
+}
+
+#[test]
+fn sequences_are_still_read() {
+    let json = serde_json::to_string(&key()).unwrap();
+    assert_eq!(key(), from_json::<Key>(&json).unwrap());
+}
+
+#[test]
+fn sequences_are_only_encoded_when_asked_to() {
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    key().serialize(ByteFmtSerializer::hex(&mut ser)).unwrap();
+    assert_eq!(serde_json::to_string(&key()).unwrap().as_bytes(), &out[..]);
+
+    let mut de = serde_json::Deserializer::from_str(JSON);
+    let err = Key::deserialize(ByteFmtDeserializer::new_hex(&mut de)).unwrap_err();
+    assert_eq!(
+        "invalid type: string \"deadbeef\", expected an array of length 4 at line 1 column 16",
+        err.to_string()
+    );
+}
+
+#[test]
+fn sequences_of_other_values_are_kept() {
+    // The first element that isn't a `u8` starts the sequence.
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Value {
+        Byte(u8),
+        Text(&'static str),
+    }
+    let values = vec![Value::Byte(1), Value::Byte(2), Value::Text("three")];
+    assert_eq!(r#"[1,2,"three"]"#, to_json(&values));
+    assert_eq!(r#"[[1,2],[3]]"#, to_json(&vec![vec![1u16, 2], vec![3]]));
+}
+
+#[test]
+fn bytes_have_the_expected_length() {
+    assert_eq!([1, 2], from_json::<[u8; 2]>(r#""0102""#).unwrap());
+    let err = from_json::<[u8; 2]>(r#""010203""#).unwrap_err();
+    assert_eq!(
+        "invalid length 3, expected 2 elements in sequence at line 1 column 8",
+        err.to_string()
+    );
+    let err = from_json::<Vec<u8>>(r#""0g""#).unwrap_err();
+    assert_eq!(
+        "invalid value: character `g`, expected valid hex character at index 1 at line 1 column 4",
+        err.to_string()
+    );
+}
+
+#[test]
+fn only_u8_are_decoded() {
+    let err = from_json::<Vec<u16>>(r#""0102""#).unwrap_err();
+    assert_eq!(
+        "encoded bytes can only be read as a sequence of `u8` at line 1 column 6",
+        err.to_string()
+    );
+    let err = from_json::<Key>(&JSON.replace("[1,300]", r#""0102""#)).unwrap_err();
+    assert_eq!(
+        "encoded bytes can only be read as a sequence of `u8` in `/uses` at line 1 column 72",
+        err.to_string()
+    );
+    let err = from_json::<(u8, u16)>(r#""0102""#).unwrap_err();
+    assert_eq!(
+        "encoded bytes can only be read as a sequence of `u8` at line 1 column 6",
+        err.to_string()
+    );
+}
@@ -0,0 +1,319 @@
+#![cfg(feature = "yaml")]
+
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use serde_bytes_repr::Encoding;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Kind {
+    Empty,
+    #[serde(with = "serde_bytes")]
+    Key(Vec<u8>),
+    Pair {
+        #[serde(with = "serde_bytes")]
+        public: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        private: Vec<u8>,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Inner {
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Config {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+    optional: Option<ByteBuf>,
+    missing: Option<ByteBuf>,
+    map: BTreeMap<String, ByteBuf>,
+    list: Vec<ByteBuf>,
+    kind: Kind,
+    other: Kind,
+    empty: Kind,
+    inner: Inner,
+}
+
+fn config() -> Config {
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), ByteBuf::from(b"first".to_vec()));
+    map.insert("b".to_string(), ByteBuf::from(b"second".to_vec()));
+    Config {
+        name: "testing".to_string(),
+        bytes: b"testing".to_vec(),
+        optional: Some(ByteBuf::from(b"optional".to_vec())),
+        missing: None,
+        map,
+        list: vec![
+            ByteBuf::from(b"one".to_vec()),
+            ByteBuf::from(b"two".to_vec()),
+        ],
+        kind: Kind::Key(b"key".to_vec()),
+        other: Kind::Pair {
+            public: b"public".to_vec(),
+            private: b"private".to_vec(),
+        },
+        empty: Kind::Empty,
+        inner: Inner {
+            bytes: b"inner".to_vec(),
+        },
+    }
+}
+
+#[test]
+fn serialize_struct_hex() {
+    let serialized = serde_bytes_repr::yaml::to_string_hex(&config()).unwrap();
+    let expected = r#"name: testing
+bytes: '74657374696e67'
+optional: 6f7074696f6e616c
+missing: null
+map:
+  a: '6669727374'
+  b: 7365636f6e64
+list:
+- 6f6e65
+- 74776f
+kind: !Key 6b6579
+other: !Pair
+  public: 7075626c6963
+  private: '70726976617465'
+empty: Empty
+inner:
+  bytes: 696e6e6572
+"#;
+    assert_eq!(expected, serialized);
+}
+
+#[test]
+fn roundtrip_hex() {
+    let serialized = serde_bytes_repr::yaml::to_string_hex(&config()).unwrap();
+    let deserialized: Config = serde_bytes_repr::yaml::from_str_hex(&serialized).unwrap();
+    assert_eq!(config(), deserialized);
+}
+
+#[test]
+fn roundtrip_base64() {
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let serialized = serde_bytes_repr::yaml::to_string_base64(
+        &config(),
+        base64::alphabet::STANDARD,
+        base64_config,
+    )
+    .unwrap();
+    assert!(serialized.contains("bytes: dGVzdGluZw==\n"));
+
+    let deserialized: Config = serde_bytes_repr::yaml::from_str_base64(
+        &serialized,
+        base64::alphabet::STANDARD,
+        base64_config,
+    )
+    .unwrap();
+    assert_eq!(config(), deserialized);
+}
+
+#[test]
+fn deserialize_plain_numeric_scalar_hex() {
+    #[derive(Deserialize, Debug)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+        optional: Option<ByteBuf>,
+    }
+
+    // Hand written documents don't quote hex that happens to look like a number.
+    let demo: Demo =
+        serde_bytes_repr::yaml::from_str_hex("bytes: 01020304\noptional: 1234\n").unwrap();
+    assert_eq!(vec![1, 2, 3, 4], demo.bytes);
+    assert_eq!(Some(ByteBuf::from(vec![0x12, 0x34])), demo.optional);
+}
+
+#[test]
+fn deserialize_invalid_base64() {
+    #[derive(Deserialize, Debug)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        #[allow(dead_code)]
+        bytes: Vec<u8>,
+    }
+
+    let base64_config = base64::engine::GeneralPurposeConfig::new();
+    let err = serde_bytes_repr::yaml::from_str_base64::<Demo>(
+        "bytes: dGVz%GluZw==\n",
+        base64::alphabet::STANDARD,
+        base64_config,
+    )
+    .unwrap_err();
+    assert_eq!(
+        "bytes: invalid value: character `%`, expected valid base64 character at index 4 in `/bytes` at line 1 column 8",
+        err.to_string()
+    );
+}
+
+#[test]
+fn serialize_binary_tag() {
+    #[derive(Serialize)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+        list: Vec<ByteBuf>,
+        text: String,
+    }
+
+    let demo = Demo {
+        bytes: b"testing".to_vec(),
+        list: vec![ByteBuf::from(b"one".to_vec())],
+        text: "!binary".to_string(),
+    };
+    let serialized = serde_bytes_repr::yaml::to_string_binary(&demo).unwrap();
+    assert_eq!(
+        "bytes: !binary dGVzdGluZw==\nlist:\n- !binary b25l\ntext: '!binary'\n",
+        serialized
+    );
+}
+
+#[test]
+fn serialize_binary_tag_folded() {
+    #[derive(Serialize)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+    }
+
+    let demo = Demo { bytes: vec![0; 60] };
+    let serialized = serde_bytes_repr::yaml::to_string_binary(&demo).unwrap();
+    let expected = format!(
+        "bytes: !binary |-\n  {}\n  {}\n",
+        "A".repeat(76),
+        "A".repeat(4)
+    );
+    assert_eq!(expected, serialized);
+}
+
+#[test]
+fn serialize_binary_untagged() {
+    let demo = ByteBuf::from(b"testing".to_vec());
+
+    let mut out = vec![];
+    let mut ser = serde_yaml::Serializer::new(&mut out);
+    let ser = serde_bytes_repr::ByteFmtSerializer::yaml_binary(&mut ser);
+    demo.serialize(ser).unwrap();
+    assert_eq!("dGVzdGluZw==\n", String::from_utf8(out).unwrap());
+
+    let mut out = vec![];
+    let mut ser = serde_json::Serializer::new(&mut out);
+    let ser = serde_bytes_repr::ByteFmtSerializer::new(&mut ser, Encoding::yaml_binary());
+    demo.serialize(ser).unwrap();
+    assert_eq!(r#""dGVzdGluZw==""#, String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn roundtrip_binary_tag() {
+    let serialized = serde_bytes_repr::yaml::to_string_binary(&config()).unwrap();
+    let deserialized: Config = serde_bytes_repr::yaml::from_str_binary(&serialized).unwrap();
+    assert_eq!(config(), deserialized);
+}
+
+#[test]
+fn deserialize_binary_tagged_and_untagged() {
+    #[derive(Deserialize, Debug)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        tagged: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        local: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        untagged: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        folded: Vec<u8>,
+    }
+
+    let yaml = "\
+tagged: !!binary dGVzdGluZw==
+local: !binary dGVzdGluZw==
+untagged: dGVzdGluZw==
+folded: !!binary |
+  dGVz
+  dGluZw==
+";
+    let demo: Demo = serde_bytes_repr::yaml::from_str_binary(yaml).unwrap();
+    assert_eq!(b"testing", demo.tagged.as_slice());
+    assert_eq!(b"testing", demo.local.as_slice());
+    assert_eq!(b"testing", demo.untagged.as_slice());
+    assert_eq!(b"testing", demo.folded.as_slice());
+}
+
+#[test]
+fn deserialize_binary_wrong_tag() {
+    #[derive(Deserialize, Debug)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        #[allow(dead_code)]
+        bytes: Vec<u8>,
+    }
+
+    let err = serde_bytes_repr::yaml::from_str_binary::<Demo>("bytes: !secret dGVzdGluZw==\n")
+        .unwrap_err();
+    assert_eq!(
+        "bytes: invalid value: tag `!secret`, expected `!!binary` tagged or untagged base64 in `/bytes` at line 1 column 8",
+        err.to_string()
+    );
+
+    let err = serde_bytes_repr::yaml::from_str_binary::<Demo>("bytes: !!int 1234\n").unwrap_err();
+    assert_eq!(
+        "bytes: invalid type: integer `1234`, expected byte array at line 1 column 8",
+        err.to_string()
+    );
+}
+
+#[test]
+fn deserialize_binary_invalid() {
+    #[derive(Deserialize, Debug)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        #[allow(dead_code)]
+        bytes: Vec<u8>,
+    }
+
+    let error = |yaml: &str| {
+        serde_bytes_repr::yaml::from_str_binary::<Demo>(yaml)
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        "bytes: invalid value: character `!`, expected valid base64 character at index 6 in `/bytes` at line 1 column 8",
+        error("bytes: !!binary |\n  dGVz\n  dG!uZw==\n")
+    );
+    assert_eq!(
+        "bytes: invalid value: invalid padding, expected valid padding in `/bytes` at line 1 column 8",
+        error("bytes: !!binary dA==dGVz\n")
+    );
+    assert_eq!(
+        "bytes: invalid value: invalid padding, expected valid padding in `/bytes` at line 1 column 8",
+        error("bytes: !!binary dGVzdA\n")
+    );
+}
+
+#[test]
+fn serialize_binary_tag_keeps_strings() {
+    #[derive(Serialize)]
+    struct Demo {
+        #[serde(with = "serde_bytes")]
+        bytes: Vec<u8>,
+        text: String,
+    }
+
+    let demo = Demo {
+        bytes: b"testing".to_vec(),
+        text: "first\n- !binary second".to_string(),
+    };
+    let serialized = serde_bytes_repr::yaml::to_string_binary(&demo).unwrap();
+    assert_eq!(
+        "bytes: !binary dGVzdGluZw==\ntext: |-\n  first\n  - !binary second\n",
+        serialized
+    );
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "secrecy")]
+use secrecy::{ExposeSecret, SecretSlice};
+use serde::{Deserialize, Serialize};
+use serde_bytes_repr::{ByteFmtDeserializer, ByteFmtSerializer};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Counts the freed blocks that still hold the secret while a test is watching.
+struct Watching;
+
+thread_local! {
+    static WATCHING: Cell<bool> = const { Cell::new(false) };
+    static LEAKED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Long enough that its HEX doesn't fit the stack buffer.
+const SECRET: [u8; 96] = {
+    let mut secret = [0; 96];
+    let mut i = 0;
+    while i < secret.len() {
+        secret[i] = (i as u8).wrapping_mul(37).wrapping_add(11);
+        i += 1;
+    }
+    secret
+};
+
+fn contains(block: &[u8], needle: &[u8]) -> bool {
+    block.windows(needle.len()).any(|w| w == needle)
+}
+
+unsafe impl GlobalAlloc for Watching {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if WATCHING.with(Cell::get) {
+            let block = core::slice::from_raw_parts(ptr, layout.size());
+            if contains(block, &SECRET[..16]) || contains(block, b"0b30557a9fc4e90e") {
+                LEAKED.with(|leaked| leaked.set(leaked.get() + 1));
+            }
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Watching = Watching;
+
+/// The number of freed blocks that held the secret or its HEX while running `f`.
+fn leaks<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    LEAKED.with(|leaked| leaked.set(0));
+    WATCHING.with(|watching| watching.set(true));
+    let result = f();
+    WATCHING.with(|watching| watching.set(false));
+    (result, LEAKED.with(Cell::get))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Account {
+    #[serde(with = "serde_bytes_repr::secret")]
+    private_key: SecretSlice<u8>,
+}
+
+#[test]
+fn secret_round_trip() {
+    assert_eq!("0b30557a9fc4e90e", hex::encode(&SECRET[..8]));
+    let account = Account {
+        private_key: SECRET.to_vec().into(),
+    };
+    // Big enough that the JSON writer doesn't reallocate.
+    let mut out = Vec::with_capacity(1024);
+    let ((), leaked) = leaks(|| {
+        let mut ser = serde_json::Serializer::new(&mut out);
+        account.serialize(ByteFmtSerializer::hex(&mut ser)).unwrap();
+    });
+    assert_eq!(0, leaked);
+    let json = String::from_utf8(out).unwrap();
+    assert_eq!(
+        format!(r#"{{"private_key":"{}"}}"#, hex::encode(SECRET)),
+        json
+    );
+
+    let (account, leaked) = leaks(|| {
+        let mut de = serde_json::Deserializer::from_str(&json);
+        Account::deserialize(ByteFmtDeserializer::new_hex(&mut de)).unwrap()
+    });
+    assert_eq!(0, leaked);
+    assert_eq!(&SECRET[..], account.private_key.expose_secret());
+
+    // Without the adapter, secrets are read from integer arrays and strings.
+    let json = serde_json::to_string(&SECRET.to_vec()).unwrap();
+    let (account, leaked) = leaks(|| {
+        serde_json::from_str::<Account>(&format!(r#"{{"private_key":{}}}"#, json)).unwrap()
+    });
+    assert_eq!(0, leaked);
+    assert_eq!(&SECRET[..], account.private_key.expose_secret());
+    let account: Account = serde_json::from_str(r#"{"private_key":"key"}"#).unwrap();
+    assert_eq!(b"key", account.private_key.expose_secret());
+}
+
+#[test]
+fn leaks_are_detected() {
+    let ((), leaked) = leaks(|| drop(SECRET.to_vec()));
+    assert_eq!(1, leaked);
+}